@@ -0,0 +1,83 @@
+//! Compares the timing of four single biquad filters and one filter with
+//! four SIMD lanes on 100s of noise per channel. Run with
+//! `cargo bench --bench biquad`.
+
+use std::time::{Duration, Instant};
+
+use dsp_analyze::NoiseGenerator;
+
+#[path = "../tests/biquad.rs"]
+#[allow(dead_code)]
+mod biquad;
+
+use biquad::{BiquadFilter2, BiquadFilter2x4, FilterParams};
+
+/// Sample rate in Hz.
+const SAMPLE_RATE: f32 = 48000.0;
+
+/// Number of timed runs, the fastest one is reported.
+const RUNS: usize = 3;
+
+/// Returns the shortest time of several runs of a function and its result.
+fn fastest<T>(mut func: impl FnMut() -> T) -> (Duration, T) {
+    let mut best = Duration::MAX;
+    let mut result = None;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        result = Some(func());
+        best = best.min(start.elapsed());
+    }
+
+    (best, result.unwrap())
+}
+
+fn main() {
+    let freq = 1000.0;
+    let params = [
+        FilterParams::Lowpass { freq, q: 0.7 },
+        FilterParams::Highpass { freq, q: 2.0 },
+        FilterParams::Peak {
+            freq,
+            q: 1.0,
+            gain: 6.0,
+        },
+        FilterParams::Notch { freq, q: 10.0 },
+    ];
+    let channels: [Vec<f32>; 4] = core::array::from_fn(|channel| {
+        let mut generator = NoiseGenerator::new(channel as u64 + 1);
+        let mut samples = vec![0.0; 4_800_000];
+        generator.process(&mut samples);
+        samples
+    });
+
+    let (single_time, single) = fastest(|| {
+        let mut single = channels.clone();
+        for (samples, params) in single.iter_mut().zip(params.iter()) {
+            let mut filter = BiquadFilter2::new(SAMPLE_RATE);
+            filter.set_params(params.clone());
+            filter.process_block(samples);
+        }
+        single
+    });
+
+    let (lanes_time, lanes) = fastest(|| {
+        let mut lanes = channels.clone();
+        let mut filter = BiquadFilter2x4::new(SAMPLE_RATE);
+        for (lane, params) in params.iter().enumerate() {
+            filter.set_params(lane, params.clone());
+        }
+        let [c0, c1, c2, c3] = &mut lanes;
+        filter.process_block([c0, c1, c2, c3]);
+        lanes
+    });
+
+    for (channel, expected) in lanes.iter().zip(single.iter()) {
+        for (sample, expected) in channel.iter().zip(expected.iter()) {
+            assert!((sample - expected).abs() < 1e-4, "{sample} vs {expected}");
+        }
+    }
+
+    println!("biquad 4 channels of 4.8M samples");
+    println!("  single filters: {single_time:?}");
+    println!("  SIMD lanes:     {lanes_time:?}");
+}
@@ -0,0 +1,55 @@
+//! Times the smoothing of a 1M-point spectrum. With the `rayon` feature,
+//! the parallel smoothing is compared to the smoothing on a single thread.
+//! Run with `cargo bench --bench smoothing`, with and without
+//! `--features rayon`.
+
+use std::time::{Duration, Instant};
+
+use dsp_analyze::smoothing::smooth;
+
+/// Number of bins of the spectrum.
+const BINS: usize = 1_000_000;
+
+/// Width of a bin in Hz, for a spectrum from DC to 24kHz.
+const BIN_WIDTH: f32 = 24000.0 / BINS as f32;
+
+/// Smoothing fraction of an octave.
+const FRACTION: f32 = 24.0;
+
+/// Returns the time of a function and its result.
+fn timed<T>(func: impl FnOnce() -> T) -> (Duration, T) {
+    let start = Instant::now();
+    let result = func();
+
+    (start.elapsed(), result)
+}
+
+fn main() {
+    let values: Vec<f32> = (0..BINS).map(|i| (i as f32 * 0.37).sin()).collect();
+
+    println!("smooth 1M bins, 1/24 octave");
+
+    #[cfg(feature = "rayon")]
+    {
+        let single = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap();
+        let (serial_time, serial) =
+            timed(|| single.install(|| smooth(&values, BIN_WIDTH, FRACTION)));
+        let (parallel_time, parallel) = timed(|| smooth(&values, BIN_WIDTH, FRACTION));
+        assert_eq!(serial, parallel);
+
+        println!("  1 thread:  {serial_time:?}");
+        println!(
+            "  {} threads: {parallel_time:?}",
+            rayon::current_num_threads()
+        );
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    {
+        let (serial_time, _) = timed(|| smooth(&values, BIN_WIDTH, FRACTION));
+        println!("  serial: {serial_time:?}");
+    }
+}
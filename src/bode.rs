@@ -2,13 +2,171 @@
 
 #![allow(unused)]
 
+use std::ops::Range;
+
+use plotters::coord::Shift;
 use plotters::prelude::*;
-use realfft::{num_complex::Complex, RealFftPlanner};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use realfft::{num_complex::Complex, num_traits::Float, FftNum, RealFftPlanner};
 
-use crate::plot::{AxisRange, Plot, Series};
-use crate::sweep_generator::SweepGenerator;
+use crate::edr::edr;
+use crate::impulse::ImpulseGenerator;
+use crate::mls::mls;
+use crate::noise::NoiseGenerator;
+use crate::plot::{self, AxisRange, Colormap, LineStyle, Plot, Series};
+use crate::response::FrequencyResponse;
+use crate::smoothing;
+use crate::sweep_generator::{SweepGenerator, SweepMode};
 use crate::wav_writer;
 
+/// Lowest frequency of the sweep test signal in Hz.
+const SWEEP_MIN_FREQ: f32 = 1.0;
+
+/// Highest frequency of the sweep test signal in Hz.
+const SWEEP_MAX_FREQ: f32 = 20000.0;
+
+/// Level in dB relative to the strongest input bin below which
+/// input bins are considered not excited.
+pub(crate) const INPUT_THRESHOLD_DB: f32 = -60.0;
+
+/// Magnitude in dB reported for bins that are not excited by the input.
+pub(crate) const MAGNITUDE_FLOOR_DB: f32 = -100.0;
+
+/// Step in dB to which the range of difference plots is rounded.
+const DIFFERENCE_STEP_DB: f32 = 1.0;
+
+/// Level relative to the output peak below which trailing samples are
+/// omitted from time domain plots.
+const TIME_PLOT_THRESHOLD: f32 = 0.001;
+
+/// Length in samples of the slices of waterfall plots.
+const WATERFALL_WINDOW: usize = 2048;
+
+/// Time in seconds between the slices of waterfall plots.
+const WATERFALL_STEP: f32 = 0.001;
+
+/// Number of slices of waterfall plots.
+const WATERFALL_SLICES: usize = 30;
+
+/// Range in dB below the peak shown by waterfall plots.
+const WATERFALL_RANGE_DB: f32 = 60.0;
+
+/// Excitation signal used by [`FftAnalyzer::run`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub enum TestSignal {
+    /// Unit impulse with a length of one second.
+    #[default]
+    Impulse,
+
+    /// Linear sine sweep from 1Hz to 20kHz within one second.
+    Sweep,
+
+    /// Logarithmic sine sweep from 1Hz to 20kHz within one second.
+    ///
+    /// The sweep spends time proportional to `1/f`, so each octave gets the
+    /// same energy. The input spectrum falls with 3dB per octave instead of
+    /// being flat, which the division by the input spectrum cancels, but the
+    /// signal-to-noise ratio is equalized across a logarithmic frequency axis
+    /// with a large gain at low frequencies compared to [`TestSignal::Sweep`].
+    LogSweep,
+
+    /// Sine burst of a number of cycles with a Hann envelope, followed by
+    /// silence up to a length of one second.
+    ///
+    /// Excites a narrow band around the frequency, intended for showing the
+    /// ringing of resonances with [`FftAnalyzer::plot_output_time`].
+    ToneBurst {
+        /// Frequency of the sine in Hz.
+        freq: f32,
+
+        /// Number of cycles of the sine.
+        cycles: u32,
+    },
+
+    /// Maximum-length sequence.
+    Mls {
+        /// Order of the sequence, the length is `2^order - 1` samples.
+        order: u32,
+    },
+}
+
+/// Compensation of a constant delay before computing the phase.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum DelayCompensation {
+    /// No compensation.
+    #[default]
+    Off,
+
+    /// Compensates the given delay in samples.
+    Samples(f32),
+
+    /// Compensates the delay of the impulse response peak.
+    Auto,
+}
+
+/// Interpolation between bins for values at arbitrary frequencies.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Interpolation {
+    /// Linear interpolation over the frequency.
+    #[default]
+    Linear,
+
+    /// Linear interpolation over the logarithm of the frequency.
+    Log,
+}
+
+/// Scale of the magnitude as returned by [`FftAnalyzer::magnitude`] and plotted
+/// by [`FftAnalyzer::plot_magnitude`].
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum MagnitudeScale {
+    /// Magnitude in dB.
+    #[default]
+    Db,
+
+    /// Linear magnitude, 1.0 being unity gain.
+    Linear,
+
+    /// Magnitude in dB relative to a reference level in dB.
+    DbRelative(f32),
+
+    /// Linear magnitude, plotted on a logarithmic axis.
+    LinearLog,
+}
+
+/// Scaling of the spectra returned by [`FftAnalyzer::input_spectrum`] and
+/// [`FftAnalyzer::output_spectrum`].
+///
+/// The transfer function is a ratio of two spectra, so the convention cancels
+/// there. [`FftAnalyzer::psd_welch`] and [`FftAnalyzer::spectrogram`] are
+/// calibrated on their own and are not affected either.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum FftNormalization {
+    /// Scaling by `1/sqrt(N)`, preserving the energy of the signal. A sine
+    /// of amplitude `A` on a bin has a magnitude of `A * sqrt(N) / 2`.
+    Unitary,
+
+    /// Scaling by `1/N`. A sine of amplitude `A` on a bin has a magnitude
+    /// of `A / 2`.
+    Forward,
+
+    /// No scaling. A sine of amplitude `A` on a bin has a magnitude
+    /// of `A * N / 2`.
+    #[default]
+    None,
+}
+
+impl FftNormalization {
+    /// Returns the factor by which a spectrum of `length` samples is scaled.
+    pub fn factor(&self, length: usize) -> f32 {
+        match self {
+            Self::Unitary => 1.0 / (length as f32).sqrt(),
+            Self::Forward => 1.0 / length as f32,
+            Self::None => 1.0,
+        }
+    }
+}
+
 /// Configuration for the analyzer.
 #[derive(Debug, Clone)]
 pub struct FftAnalyzerConfig {
@@ -17,20 +175,325 @@ pub struct FftAnalyzerConfig {
 
     /// Block size for each process call.
     pub block_size: usize,
+
+    /// Oversampling factor at which the process function is run.
+    ///
+    /// The excitation is upsampled and the output decimated by band-limited
+    /// resampling, so that components above the original Nyquist frequency
+    /// are removed instead of aliased. The block size is multiplied by the
+    /// factor. 1 disables oversampling.
+    pub oversample: usize,
+
+    /// Allows passing a shorter final block to the process function.
+    ///
+    /// If not set, the test signal is padded with zeros to a multiple
+    /// of the block size, so that all blocks are full-size.
+    pub allow_short_blocks: bool,
+
+    /// Excitation signal.
+    pub test_signal: TestSignal,
+
+    /// Duration in seconds of a raised-cosine taper at the end of the sweep
+    /// test signals, 0 for an abrupt end.
+    ///
+    /// See [`SweepGenerator::set_end_taper`].
+    pub sweep_end_taper: f32,
+
+    /// Duration in seconds of a pre-roll processed before the excitation to
+    /// let the processor settle, 0 for none. The pre-roll is rounded up to
+    /// whole blocks unless short blocks are allowed.
+    ///
+    /// The pre-roll repeats the end of the excitation, so the excitation
+    /// appears periodic to the processor and its output is in steady state,
+    /// e.g. for a [`TestSignal::Mls`]. For an excitation ending in silence,
+    /// like the unit impulse, the pre-roll is silence. The output during
+    /// the pre-roll is discarded and excluded from the spectrum.
+    pub pre_roll: f32,
+
+    /// Peak level of the test signal in dBFS.
+    ///
+    /// Lowering the level gives headroom for processors boosting the signal,
+    /// so that their output does not clip.
+    pub excitation_level_db: f32,
+
+    /// Level in dB of white noise mixed into the test signal, relative to
+    /// its peak amplitude. `None` for no noise.
+    pub excitation_noise_db: Option<f32>,
+
+    /// Seed of the excitation noise, equal seeds produce equal noise.
+    pub noise_seed: u64,
+
+    /// Delay compensation applied to the phase.
+    pub delay_compensation: DelayCompensation,
+
+    /// Interpolation used by [`FftAnalyzer::magnitude_at`] and [`FftAnalyzer::phase_at`].
+    pub interpolation: Interpolation,
+
+    /// Scale of the magnitude used by [`FftAnalyzer::magnitude`] and [`FftAnalyzer::plot_magnitude`].
+    pub magnitude_scale: MagnitudeScale,
+
+    /// Normalization of the spectra of the input and output signals.
+    pub fft_normalization: FftNormalization,
+
+    /// Font family and size of the title on plots.
+    pub title_font: (String, u32),
+
+    /// Shades the regions outside the valid band in bode plots, see
+    /// [`FftAnalyzer::valid_band`].
+    pub shade_invalid_band: bool,
+
+    /// Colormap of spectrograms.
+    pub colormap: Colormap,
+
+    /// Range of levels in dB spanned by the colormap, levels outside
+    /// are clamped.
+    pub colormap_range_db: Range<f32>,
 }
 
 impl Default for FftAnalyzerConfig {
     /// Returns the default configuration for the plotter:
     /// - Sample rate: 48kHz
     /// - Block size: 64 samples
+    /// - Oversampling: none
+    /// - Short blocks: not allowed
+    /// - Test signal: unit impulse
+    /// - Sweep end taper: none
+    /// - Pre-roll: none
+    /// - Excitation level: 0dBFS
+    /// - Excitation noise: none
+    /// - Noise seed: 1
+    /// - Delay compensation: off
+    /// - Interpolation: linear
+    /// - Magnitude scale: dB
+    /// - FFT normalization: none
+    /// - Title font: sans-serif, 40
+    /// - Invalid band shading: off
+    /// - Colormap: Viridis from -80dB to 0dB
     fn default() -> Self {
         Self {
             sample_rate: 48000.0,
             block_size: 64,
+            oversample: 1,
+            allow_short_blocks: false,
+            test_signal: TestSignal::default(),
+            sweep_end_taper: 0.0,
+            pre_roll: 0.0,
+            excitation_level_db: 0.0,
+            excitation_noise_db: None,
+            noise_seed: 1,
+            delay_compensation: DelayCompensation::default(),
+            interpolation: Interpolation::default(),
+            magnitude_scale: MagnitudeScale::default(),
+            fft_normalization: FftNormalization::default(),
+            title_font: (plot::TITLE_FONT_FAMILY.to_string(), plot::TITLE_FONT_SIZE),
+            shade_invalid_band: false,
+            colormap: Colormap::default(),
+            colormap_range_db: -80.0..0.0,
         }
     }
 }
 
+impl FftAnalyzerConfig {
+    /// Returns the samples of the configured test signal.
+    pub(crate) fn test_signal_samples(&self) -> Vec<f32> {
+        let mut samples = match self.test_signal {
+            TestSignal::Impulse => unit_impulse(self.sample_rate as usize),
+            TestSignal::Sweep => sweep(
+                self.sample_rate,
+                self.block_size,
+                SweepMode::Linear,
+                self.sweep_end_taper,
+            ),
+            TestSignal::LogSweep => sweep(
+                self.sample_rate,
+                self.block_size,
+                SweepMode::Logarithmic,
+                self.sweep_end_taper,
+            ),
+            TestSignal::ToneBurst { freq, cycles } => tone_burst(self.sample_rate, freq, cycles),
+            TestSignal::Mls { order } => mls(order),
+        };
+
+        if self.excitation_level_db != 0.0 {
+            let gain = 10.0f32.powf(self.excitation_level_db / 20.0);
+            for sample in samples.iter_mut() {
+                *sample *= gain;
+            }
+        }
+
+        if let Some(level) = self.excitation_noise_db {
+            let peak = samples.iter().fold(0.0f32, |peak, v| peak.max(v.abs()));
+            let mut generator = NoiseGenerator::new(self.noise_seed);
+            generator.set_gain(peak * 10.0f32.powf(level / 20.0));
+            for sample in samples.iter_mut() {
+                *sample += generator.next_sample();
+            }
+        }
+
+        samples
+    }
+
+    /// Pads the samples with zeros to a multiple of the block size
+    /// unless short blocks are allowed.
+    pub(crate) fn pad_to_blocks<T: Clone + Default>(&self, samples: &mut Vec<T>) {
+        if !self.allow_short_blocks {
+            let length = samples.len().next_multiple_of(self.block_size);
+            samples.resize(length, T::default());
+        }
+    }
+
+    /// Passes the samples block-wise to the process function at the
+    /// configured oversampling factor, preceded by the configured pre-roll.
+    pub(crate) fn process<T, F>(&self, in_samples: &[T], out_samples: &mut [T], func: &mut F)
+    where
+        T: FftNum + Float,
+        F: FnMut(&[T], &mut [T]) + ?Sized,
+    {
+        let pre_roll = self.pre_roll_samples();
+        if pre_roll == 0 || in_samples.is_empty() {
+            self.process_oversampled(in_samples, out_samples, func);
+            return;
+        }
+
+        // Repeats the end of the input cyclically before its start.
+        let length = in_samples.len();
+        let offset = length - pre_roll % length;
+        let extended_in: Vec<T> = (0..pre_roll + length)
+            .map(|i| in_samples[(i + offset) % length])
+            .collect();
+        let mut extended_out = extended_in.clone();
+        self.process_oversampled(&extended_in, &mut extended_out, func);
+        out_samples.copy_from_slice(&extended_out[pre_roll..]);
+    }
+
+    /// Returns the length of the pre-roll in samples.
+    fn pre_roll_samples(&self) -> usize {
+        let length = (self.pre_roll.max(0.0) * self.sample_rate).round() as usize;
+        if self.allow_short_blocks {
+            length
+        } else {
+            length.next_multiple_of(self.block_size)
+        }
+    }
+
+    /// Passes the samples block-wise to the process function at the
+    /// configured oversampling factor.
+    fn process_oversampled<T, F>(&self, in_samples: &[T], out_samples: &mut [T], func: &mut F)
+    where
+        T: FftNum + Float,
+        F: FnMut(&[T], &mut [T]) + ?Sized,
+    {
+        if self.oversample <= 1 {
+            process_blocks(in_samples, out_samples, self.block_size, func);
+            return;
+        }
+
+        let length = in_samples.len() * self.oversample;
+        let oversampled_in = resample_periodic(in_samples, length);
+        let mut oversampled_out = oversampled_in.clone();
+        process_blocks(
+            &oversampled_in,
+            &mut oversampled_out,
+            self.block_size * self.oversample,
+            func,
+        );
+        out_samples.copy_from_slice(&resample_periodic(&oversampled_out, in_samples.len()));
+    }
+
+    /// Returns the frequency band in Hz excited by the configured test signal.
+    pub(crate) fn test_signal_band(&self) -> Range<f32> {
+        match self.test_signal {
+            TestSignal::Sweep | TestSignal::LogSweep => SWEEP_MIN_FREQ..SWEEP_MAX_FREQ,
+            _ => 0.0..self.sample_rate / 2.0,
+        }
+    }
+}
+
+/// Error variants.
+#[derive(Debug)]
+pub enum AnalyzerError {
+    /// Excitation signal contains no samples.
+    EmptySignal,
+
+    /// Excitation signal is too short to produce a spectrum, contains the length.
+    SignalTooShort(usize),
+
+    /// Output contains a non-finite sample, contains the index and the
+    /// value of the first one.
+    NonFiniteOutput(usize, f32),
+
+    /// Reference and measured signals differ in length, contains both lengths.
+    LengthMismatch(usize, usize),
+
+    /// Writing an output file failed.
+    Io(std::io::Error),
+}
+
+impl core::fmt::Display for AnalyzerError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::EmptySignal => write!(f, "Excitation signal is empty"),
+            Self::SignalTooShort(len) => {
+                write!(f, "Excitation signal of {len} samples is too short")
+            }
+            Self::NonFiniteOutput(index, value) => {
+                write!(
+                    f,
+                    "Output sample {index} is {value}, the processor may be unstable"
+                )
+            }
+            Self::LengthMismatch(reference, measured) => {
+                write!(
+                    f,
+                    "Reference of {reference} samples and measured signal of {measured} samples differ in length"
+                )
+            }
+            Self::Io(error) => write!(f, "Writing output failed: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for AnalyzerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for AnalyzerError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+/// Custom result type.
+pub type Result<T> = core::result::Result<T, AnalyzerError>;
+
+/// Boxed processing function as passed to [`FftAnalyzer::run_many`]
+/// and [`FftAnalyzer::run_stages`].
+pub type Processor<'a> = Box<dyn FnMut(&[f32], &mut [f32]) + 'a>;
+
+/// Boxed processing function that can be sent to another thread, as passed
+/// to [`FftAnalyzer::par_run_many`].
+#[cfg(feature = "rayon")]
+pub type SendProcessor<'a> = Box<dyn FnMut(&[f32], &mut [f32]) + Send + 'a>;
+
+/// Characteristics of a notch as found by [`FftAnalyzer::measure_notch`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NotchInfo {
+    /// Center frequency of the null in Hz.
+    pub freq: f32,
+
+    /// Depth of the null in dB below the peak gain.
+    pub depth: f32,
+
+    /// Width in Hz of the band in which the magnitude is 3dB or more
+    /// below the peak gain, `None` if the band is not within the valid band.
+    pub bandwidth: Option<f32>,
+}
+
 /// FFT analyzer.
 #[derive(Debug)]
 pub struct FftAnalyzer {
@@ -48,6 +511,15 @@ pub struct FftAnalyzer {
 
     /// Phase of the spectrum.
     pub spectrum_phase: Vec<f32>,
+
+    /// Frequency band in Hz in which the spectrum is valid.
+    ///
+    /// Bins outside this band or not excited by the input are reported
+    /// with a magnitude of -100dB and a phase of 0°.
+    pub valid_band: Range<f32>,
+
+    /// Transfer function as ratio of the output to the input spectrum.
+    transfer: Vec<Complex<f32>>,
 }
 
 impl FftAnalyzer {
@@ -59,15 +531,64 @@ impl FftAnalyzer {
             out_samples: Vec::new(),
             spectrum_magnitude: Vec::new(),
             spectrum_phase: Vec::new(),
+            valid_band: 0.0..0.0,
+            transfer: Vec::new(),
         }
     }
 
+    /// Returns a new instance with the default configuration at a sample rate in Hz.
+    pub fn with_sample_rate(sample_rate: f32) -> Self {
+        Self::new(FftAnalyzerConfig {
+            sample_rate,
+            ..Default::default()
+        })
+    }
+
+    /// Returns a new instance with the default configuration at a block size
+    /// in samples.
+    pub fn with_block_size(block_size: usize) -> Self {
+        Self::new(FftAnalyzerConfig {
+            block_size,
+            ..Default::default()
+        })
+    }
+
+    /// Returns a new instance of the analyzer with the spectrum computed
+    /// directly from an impulse response, without running a test signal.
+    ///
+    /// The input samples hold a unit impulse and the output samples the
+    /// impulse response, so that all plot and save methods can be used.
+    ///
+    /// Returns an error if the impulse response contains fewer than 2
+    /// samples or a non-finite sample.
+    pub fn from_impulse_response(ir: &[f32], sample_rate: f32) -> Result<Self> {
+        validate_signal(ir)?;
+        validate_output(ir)?;
+
+        let mut analyzer = Self::new(FftAnalyzerConfig {
+            sample_rate,
+            ..Default::default()
+        });
+        analyzer.in_samples = unit_impulse(ir.len());
+        analyzer.out_samples = ir.to_vec();
+
+        let transfer = fft(ir);
+        analyzer.spectrum_magnitude = magnitude_db(&transfer);
+        analyzer.spectrum_phase = phase_deg(&transfer);
+        analyzer.valid_band = 0.0..sample_rate / 2.0;
+        analyzer.transfer = transfer;
+
+        Ok(analyzer)
+    }
+
     /// Clears the spectrum data.
     pub fn clear(&mut self) {
         self.in_samples.clear();
         self.out_samples.clear();
         self.spectrum_magnitude.clear();
         self.spectrum_phase.clear();
+        self.valid_band = 0.0..0.0;
+        self.transfer.clear();
     }
 
     /// Runs the test signal through the provided function and
@@ -75,40 +596,795 @@ impl FftAnalyzer {
     ///
     /// The function is called in a loop, each iteration is passed
     /// the number of samples equal to the configured block size.
+    /// The test signal is padded with zeros to a multiple of the block size,
+    /// unless short blocks are allowed in the configuration.
     ///
     /// The function closure takes two arguments:
     /// - The first is a read-only buffer containing the samples of the test signal.
     /// - The second argument is a writable buffer for the processed samples.
     ///   It is initially filled with a copy of the input samples.
-    pub fn run<F>(&mut self, mut func: F)
+    ///
+    /// Returns an error if the output contains a non-finite sample, e.g. from
+    /// an unstable filter. The output samples are kept for inspection then,
+    /// but no spectrum is computed.
+    pub fn run<F>(&mut self, func: F) -> Result<()>
+    where
+        F: FnMut(&[f32], &mut [f32]),
+    {
+        let input = self.config.test_signal_samples();
+        let band = self.config.test_signal_band();
+        self.analyze(input, band, func)
+    }
+
+    /// Runs a user-supplied excitation signal through the provided function
+    /// and analyzes the result.
+    ///
+    /// The spectrum is computed as the ratio of the output spectrum to the
+    /// input spectrum, so any signal exciting the band of interest can be used.
+    /// The closure is called in the same way as for [`FftAnalyzer::run`].
+    ///
+    /// Bins that are not excited by the signal are masked, see
+    /// [`FftAnalyzer::valid_band`].
+    ///
+    /// Returns an error if the signal is empty or too short for a spectrum,
+    /// or if the output contains a non-finite sample.
+    pub fn run_with_signal<F>(&mut self, input: Vec<f32>, func: F) -> Result<()>
+    where
+        F: FnMut(&[f32], &mut [f32]),
+    {
+        let band = 0.0..self.config.sample_rate / 2.0;
+        self.analyze(input, band, func)
+    }
+
+    /// Runs the excitation through the function and computes the spectrum
+    /// within the given band.
+    fn analyze<F>(&mut self, mut input: Vec<f32>, band: Range<f32>, mut func: F) -> Result<()>
     where
         F: FnMut(&[f32], &mut [f32]),
     {
+        validate_signal(&input)?;
+        self.config.pad_to_blocks(&mut input);
+
         self.clear();
 
-        self.in_samples = unit_impulse(self.config.sample_rate as usize);
+        self.in_samples = input;
         self.out_samples.clone_from(&self.in_samples);
-        let chunk_size = self.config.block_size;
+        self.config
+            .process(&self.in_samples, &mut self.out_samples, &mut func);
+        validate_output(&self.out_samples)?;
+        self.compute_spectrum(band);
+
+        Ok(())
+    }
+
+    /// Computes the spectrum from two captured signals, the reference at the
+    /// input of the device under test and the measured signal at its output.
+    ///
+    /// This is a dual-channel measurement: unlike [`FftAnalyzer::run`], the
+    /// transfer function does not depend on the generated excitation, so any
+    /// alteration of the excitation before the device input, e.g. by a DAC or
+    /// amplifier, is not attributed to the device. The signals are stored as
+    /// input and output samples. For an exact result, the capture should hold
+    /// whole periods of a periodic excitation or the full decay of the output.
+    ///
+    /// Returns an error if the reference is too short for a spectrum, the
+    /// lengths differ or the measured signal contains a non-finite sample.
+    /// The analyzer is left unchanged then.
+    pub fn analyze_ref(&mut self, reference: &[f32], measured: &[f32]) -> Result<()> {
+        validate_signal(reference)?;
+        if reference.len() != measured.len() {
+            return Err(AnalyzerError::LengthMismatch(
+                reference.len(),
+                measured.len(),
+            ));
+        }
+        validate_output(measured)?;
 
-        for (in_samples, out_samples) in self
+        self.clear();
+        self.in_samples = reference.to_vec();
+        self.out_samples = measured.to_vec();
+        self.compute_spectrum(0.0..self.config.sample_rate / 2.0);
+
+        Ok(())
+    }
+
+    /// Computes the spectrum from the input and output samples within the
+    /// given band.
+    fn compute_spectrum(&mut self, band: Range<f32>) {
+        let in_spectrum = fft(&self.in_samples);
+        let mask = excitation_mask(&in_spectrum, &band, self.config.sample_rate);
+        let mut transfer = transfer_function(&in_spectrum, &self.out_samples, &mask);
+        compensate_delay(
+            &mut transfer,
+            self.config.delay_compensation,
+            self.in_samples.len(),
+        );
+        self.spectrum_magnitude = magnitude_db(&transfer);
+        self.spectrum_phase = phase_deg(&transfer);
+        self.valid_band = band;
+        self.transfer = transfer;
+    }
+
+    /// Discards the leading silence of the input and output samples and
+    /// recomputes the spectrum, e.g. for a recording that starts before the
+    /// excitation.
+    ///
+    /// The samples are cut before the first input sample with a level above
+    /// `threshold_db` in dBFS, so the input and output stay aligned and the
+    /// latency of the processor is preserved. Returns the number of samples
+    /// discarded.
+    ///
+    /// Returns an error if fewer than 2 samples would remain, the samples
+    /// are left unchanged then.
+    pub fn trim_leading_silence(&mut self, threshold_db: f32) -> Result<usize> {
+        let threshold = 10.0f32.powf(threshold_db / 20.0);
+        let start = self
             .in_samples
-            .chunks(chunk_size)
-            .zip(self.out_samples.chunks_mut(chunk_size))
-        {
-            func(in_samples, out_samples);
+            .iter()
+            .position(|sample| sample.abs() > threshold)
+            .unwrap_or(self.in_samples.len());
+        validate_signal(&self.in_samples[start..])?;
+
+        if start > 0 {
+            self.in_samples.drain(..start);
+            self.out_samples.drain(..start);
+            self.compute_spectrum(self.valid_band.clone());
         }
 
-        let out_spectrum = fft(&self.out_samples);
+        Ok(start)
+    }
+
+    /// Runs the test signal through several processors for comparison and
+    /// returns the response of each one.
+    ///
+    /// The test signal is generated and transformed only once and shared by
+    /// all processors, which are run one after another. Afterwards, the input
+    /// samples hold the test signal while the output samples and the
+    /// spectrum fields are left empty.
+    ///
+    /// Returns an error if the output of a processor contains a non-finite
+    /// sample. The remaining processors are not run then.
+    pub fn run_many(&mut self, funcs: Vec<Processor<'_>>) -> Result<Vec<FrequencyResponse>> {
+        let (in_spectrum, mask) = self.prepare_shared();
+
+        funcs
+            .into_iter()
+            .map(|mut func| self.shared_response(&in_spectrum, &mask, &mut func))
+            .collect()
+    }
+
+    /// Runs the test signal through several processors in parallel and
+    /// returns the response of each one, like [`FftAnalyzer::run_many`].
+    ///
+    /// The processors are distributed over the threads of the rayon pool,
+    /// so they must be [`Send`]. Returns the error of the first processor
+    /// in the list whose output contains a non-finite sample, all others
+    /// are run regardless.
+    #[cfg(feature = "rayon")]
+    pub fn par_run_many(
+        &mut self,
+        funcs: Vec<SendProcessor<'_>>,
+    ) -> Result<Vec<FrequencyResponse>> {
+        let (in_spectrum, mask) = self.prepare_shared();
+
+        let results: Vec<Result<FrequencyResponse>> = funcs
+            .into_par_iter()
+            .map(|mut func| self.shared_response(&in_spectrum, &mask, &mut func))
+            .collect();
+        results.into_iter().collect()
+    }
+
+    /// Generates the test signal shared by several processors and returns
+    /// its spectrum and excitation mask.
+    fn prepare_shared(&mut self) -> (Vec<Complex<f32>>, Vec<bool>) {
+        self.clear();
+
+        self.in_samples = self.config.test_signal_samples();
+        self.config.pad_to_blocks(&mut self.in_samples);
+        self.valid_band = self.config.test_signal_band();
+        let in_spectrum = fft(&self.in_samples);
+        let mask = excitation_mask(&in_spectrum, &self.valid_band, self.config.sample_rate);
+
+        (in_spectrum, mask)
+    }
+
+    /// Runs the shared test signal through a processor and returns its response.
+    fn shared_response<F>(
+        &self,
+        in_spectrum: &[Complex<f32>],
+        mask: &[bool],
+        func: &mut F,
+    ) -> Result<FrequencyResponse>
+    where
+        F: FnMut(&[f32], &mut [f32]) + ?Sized,
+    {
+        let mut out_samples = self.in_samples.clone();
+        self.config
+            .process(&self.in_samples, &mut out_samples, func);
+        validate_output(&out_samples)?;
+
+        let mut transfer = transfer_function(in_spectrum, &out_samples, mask);
+        compensate_delay(
+            &mut transfer,
+            self.config.delay_compensation,
+            out_samples.len(),
+        );
+        Ok(FrequencyResponse::new(
+            self.config.sample_rate,
+            magnitude_db(&transfer),
+            phase_deg(&transfer),
+            self.valid_band.clone(),
+        ))
+    }
+
+    /// Runs the test signal through a chain of processing stages and
+    /// returns the response of the chain after each stage.
+    ///
+    /// Each stage is passed the output of the previous one as input, the
+    /// first stage the test signal. All responses are relative to the test
+    /// signal. Afterwards, the output samples and the spectrum fields hold
+    /// the result of the complete chain.
+    ///
+    /// Returns an error if the output of a stage contains a non-finite
+    /// sample. The output samples hold the output of that stage then, but
+    /// no spectrum is computed and the remaining stages are not run.
+    pub fn run_stages(&mut self, stages: Vec<Processor<'_>>) -> Result<Vec<FrequencyResponse>> {
+        self.clear();
+
+        self.in_samples = self.config.test_signal_samples();
+        self.config.pad_to_blocks(&mut self.in_samples);
+        self.valid_band = self.config.test_signal_band();
+        let in_spectrum = fft(&self.in_samples);
+        let mask = excitation_mask(&in_spectrum, &self.valid_band, self.config.sample_rate);
+
+        let mut stage_samples = self.in_samples.clone();
+        let mut responses = Vec::with_capacity(stages.len());
+        for mut stage in stages {
+            let mut out_samples = stage_samples.clone();
+            self.config
+                .process(&stage_samples, &mut out_samples, &mut stage);
+            if let Err(error) = validate_output(&out_samples) {
+                self.out_samples = out_samples;
+                return Err(error);
+            }
+
+            let mut transfer = transfer_function(&in_spectrum, &out_samples, &mask);
+            compensate_delay(
+                &mut transfer,
+                self.config.delay_compensation,
+                out_samples.len(),
+            );
+            responses.push(FrequencyResponse::new(
+                self.config.sample_rate,
+                magnitude_db(&transfer),
+                phase_deg(&transfer),
+                self.valid_band.clone(),
+            ));
+
+            stage_samples = out_samples;
+            self.transfer = transfer;
+        }
+
+        self.out_samples = stage_samples;
+        self.spectrum_magnitude = magnitude_db(&self.transfer);
+        self.spectrum_phase = phase_deg(&self.transfer);
+
+        Ok(responses)
+    }
+
+    /// Runs the test signal through the function several times and returns
+    /// the highest and lowest magnitude in dB each bin reached, e.g. to show
+    /// the variability of a noisy or modulated processor.
+    ///
+    /// The function is called as for [`FftAnalyzer::run`] and keeps its
+    /// state between the runs. Afterwards, the output samples and the
+    /// spectrum fields hold the result of the last run.
+    ///
+    /// Returns an error if the output of a run contains a non-finite sample.
+    /// The output samples hold the output of that run then, but no spectrum
+    /// is computed and the remaining runs are skipped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `runs` is zero.
+    pub fn run_with_hold<F>(&mut self, runs: usize, mut func: F) -> Result<(Vec<f32>, Vec<f32>)>
+    where
+        F: FnMut(&[f32], &mut [f32]),
+    {
+        assert!(runs > 0, "Number of runs is zero");
+        self.clear();
+
+        self.in_samples = self.config.test_signal_samples();
+        self.config.pad_to_blocks(&mut self.in_samples);
+        self.valid_band = self.config.test_signal_band();
+        let in_spectrum = fft(&self.in_samples);
+        let mask = excitation_mask(&in_spectrum, &self.valid_band, self.config.sample_rate);
+
+        let mut max_hold = vec![f32::NEG_INFINITY; in_spectrum.len()];
+        let mut min_hold = vec![f32::INFINITY; in_spectrum.len()];
+        for _ in 0..runs {
+            let mut out_samples = self.in_samples.clone();
+            self.config
+                .process(&self.in_samples, &mut out_samples, &mut func);
+            if let Err(error) = validate_output(&out_samples) {
+                self.out_samples = out_samples;
+                return Err(error);
+            }
+
+            let mut transfer = transfer_function(&in_spectrum, &out_samples, &mask);
+            compensate_delay(
+                &mut transfer,
+                self.config.delay_compensation,
+                out_samples.len(),
+            );
+            for ((max, min), magnitude) in max_hold
+                .iter_mut()
+                .zip(min_hold.iter_mut())
+                .zip(magnitude_db(&transfer))
+            {
+                *max = max.max(magnitude);
+                *min = min.min(magnitude);
+            }
+
+            self.out_samples = out_samples;
+            self.transfer = transfer;
+        }
+
+        self.spectrum_magnitude = magnitude_db(&self.transfer);
+        self.spectrum_phase = phase_deg(&self.transfer);
+
+        Ok((max_hold, min_hold))
+    }
+
+    /// Returns the number of samples transformed by the FFT, which is the
+    /// length of the excitation of the last run, or of the configured test
+    /// signal before the first run.
+    ///
+    /// The spectrum has `fft_length() / 2 + 1` bins.
+    pub fn fft_length(&self) -> usize {
+        if !self.in_samples.is_empty() {
+            return self.in_samples.len();
+        }
+
+        let mut samples = self.config.test_signal_samples();
+        self.config.pad_to_blocks(&mut samples);
+        samples.len()
+    }
+
+    /// Returns the distance of the bins of the spectrum in Hz,
+    /// see [`FftAnalyzer::fft_length`].
+    pub fn frequency_resolution(&self) -> f32 {
+        self.config.sample_rate / self.fft_length() as f32
+    }
+
+    /// Returns the magnitude in dB at a frequency, interpolated between
+    /// the two closest bins.
+    pub fn magnitude_at(&self, freq: f32) -> f32 {
+        self.interpolate(&self.spectrum_magnitude, freq, false)
+    }
+
+    /// Returns the phase in degrees at a frequency, interpolated between
+    /// the two closest bins along the shorter way around the circle.
+    pub fn phase_at(&self, freq: f32) -> f32 {
+        self.interpolate(&self.spectrum_phase, freq, true)
+    }
+
+    /// Interpolates spectrum values at a frequency.
+    fn interpolate(&self, values: &[f32], freq: f32, wrap_phase: bool) -> f32 {
+        assert!(!values.is_empty(), "Spectrum is empty");
+
+        interpolate(
+            values,
+            self.config.sample_rate,
+            freq,
+            self.config.interpolation,
+            wrap_phase,
+        )
+    }
+
+    /// Runs a sine of one second at a frequency through the provided function
+    /// and returns the magnitude in dB and the phase in degrees at exactly
+    /// that frequency.
+    ///
+    /// The closure is called in the same way as for [`FftAnalyzer::run`]. The
+    /// first half of the tone lets the processor settle, the second half,
+    /// shortened to a whole number of cycles, is evaluated by the Goertzel
+    /// algorithm instead of a full FFT. Afterwards, the input and output
+    /// samples hold the tone while the spectrum fields are left empty.
+    /// Returns an error if the output contains a non-finite sample.
+    pub fn probe_frequency<F>(&mut self, freq: f32, mut func: F) -> Result<(f32, f32)>
+    where
+        F: FnMut(&[f32], &mut [f32]),
+    {
+        let sample_rate = self.config.sample_rate;
+        assert!(
+            freq > 0.0 && freq < sample_rate / 2.0,
+            "Frequency {freq}Hz is outside of the range up to Nyquist"
+        );
 
-        // TODO: check if clamping the magnitude is required after FFT results are improved.
-        self.spectrum_magnitude = out_spectrum
+        self.clear();
+
+        let length = sample_rate as usize;
+        self.in_samples = (0..length)
+            .map(|n| (std::f32::consts::TAU * freq * n as f32 / sample_rate).sin())
+            .collect();
+        self.config.pad_to_blocks(&mut self.in_samples);
+        self.out_samples.clone_from(&self.in_samples);
+        self.config
+            .process(&self.in_samples, &mut self.out_samples, &mut func);
+        validate_output(&self.out_samples)?;
+
+        let period = sample_rate / freq;
+        let cycles = ((length / 2) as f32 / period).floor().max(1.0);
+        let window = length - (cycles * period).round() as usize..length;
+        let ratio = goertzel(&self.out_samples[window.clone()], freq, sample_rate)
+            / goertzel(&self.in_samples[window], freq, sample_rate);
+
+        Ok((magnitude_db(&[ratio])[0], phase_deg(&[ratio])[0]))
+    }
+
+    /// Finds the deepest local minimum of the magnitude within the valid band
+    /// and returns its characteristics, `None` if there is no local minimum.
+    ///
+    /// Consecutive bins of equal magnitude, such as a null clamped to the
+    /// magnitude floor, are treated as one minimum centered between them.
+    pub fn measure_notch(&self) -> Option<NotchInfo> {
+        let response = self.response();
+        let bins = response.valid_bins();
+        let magnitude = &response.magnitude_db;
+        let frequencies = &response.frequencies;
+
+        let mut null: Option<Range<usize>> = None;
+        let mut start = bins.start;
+        while start < bins.end {
+            let mut end = start + 1;
+            while end < bins.end && magnitude[end] == magnitude[start] {
+                end += 1;
+            }
+            let is_minimum = start > bins.start
+                && end < bins.end
+                && magnitude[start - 1] > magnitude[start]
+                && magnitude[end] > magnitude[start];
+            if is_minimum
+                && null
+                    .as_ref()
+                    .is_none_or(|n| magnitude[start] < magnitude[n.start])
+            {
+                null = Some(start..end);
+            }
+            start = end;
+        }
+        let null = null?;
+
+        let level = response.peak_gain - 3.0;
+        let crossing = |i: usize| {
+            let (m0, m1) = (magnitude[i - 1], magnitude[i]);
+            let (f0, f1) = (frequencies[i - 1], frequencies[i]);
+            f0 + (f1 - f0) * (level - m0) / (m1 - m0)
+        };
+        let lower = (bins.start + 1..=null.start)
+            .rev()
+            .find(|&i| magnitude[i - 1] >= level)
+            .map(crossing);
+        let upper = (null.end..bins.end)
+            .find(|&i| magnitude[i] >= level)
+            .map(crossing);
+
+        Some(NotchInfo {
+            freq: (frequencies[null.start] + frequencies[null.end - 1]) / 2.0,
+            depth: response.peak_gain - magnitude[null.start],
+            bandwidth: lower.zip(upper).map(|(lower, upper)| upper - lower),
+        })
+    }
+
+    /// Returns the highest absolute value of the output samples.
+    pub fn output_peak(&self) -> f32 {
+        peak(&self.out_samples)
+    }
+
+    /// Returns the RMS level of the output samples.
+    pub fn output_rms(&self) -> f32 {
+        rms(&self.out_samples)
+    }
+
+    /// Returns the crest factor of the output samples in dB, the ratio of
+    /// the peak to the RMS level, or 0dB for silence.
+    pub fn crest_factor_db(&self) -> f32 {
+        crest_factor_db(&self.out_samples)
+    }
+
+    /// Returns the crest factor of the input samples in dB,
+    /// see [`FftAnalyzer::crest_factor_db`].
+    pub fn input_crest_factor_db(&self) -> f32 {
+        crest_factor_db(&self.in_samples)
+    }
+
+    /// Returns if any output sample exceeds ±1.0, which would clip
+    /// on a fixed-point output.
+    pub fn clipped(&self) -> bool {
+        self.out_samples.iter().any(|sample| sample.abs() > 1.0)
+    }
+
+    /// Returns the number of output samples exceeding ±1.0.
+    pub fn clipped_samples(&self) -> usize {
+        self.out_samples
             .iter()
-            .map(|v| (20.0 * f32::log10(v.norm())).clamp(-100.0, 100.0))
+            .filter(|sample| sample.abs() > 1.0)
+            .count()
+    }
+
+    /// Returns the power spectral density of the output samples in dB/Hz
+    /// estimated by Welch's method, for bins spread evenly from DC to Nyquist.
+    ///
+    /// The output is split into segments of `segment_len` samples, of which
+    /// consecutive ones overlap by `overlap` samples. The periodograms of the
+    /// Hann-windowed segments are averaged and normalized by the equivalent
+    /// noise bandwidth of the window, so that the result is one-sided and
+    /// independent of the segment length. More segments lower the variance
+    /// at the cost of the frequency resolution.
+    pub fn psd_welch(&self, segment_len: usize, overlap: usize) -> Vec<f32> {
+        assert!(
+            segment_len >= 2 && segment_len <= self.out_samples.len(),
+            "Segment length of {segment_len} samples does not fit the output"
+        );
+        assert!(
+            overlap < segment_len,
+            "Overlap of {overlap} samples exceeds the segment length"
+        );
+
+        let window: Vec<f32> = (0..segment_len)
+            .map(|i| {
+                let phase = std::f32::consts::TAU * i as f32 / segment_len as f32;
+                0.5 - 0.5 * phase.cos()
+            })
             .collect();
-        self.spectrum_phase = out_spectrum
+        let window_power: f32 = window.iter().map(|w| w * w).sum();
+
+        let hop = segment_len - overlap;
+        let segments = (self.out_samples.len() - segment_len) / hop + 1;
+        let mut power = vec![0.0; segment_len / 2 + 1];
+        for segment in 0..segments {
+            let start = segment * hop;
+            let windowed: Vec<f32> = self.out_samples[start..start + segment_len]
+                .iter()
+                .zip(window.iter())
+                .map(|(sample, w)| sample * w)
+                .collect();
+            for (bin, value) in power.iter_mut().zip(fft(&windowed)) {
+                *bin += value.norm_sqr();
+            }
+        }
+
+        let last = power.len() - 1;
+        let scale = 1.0 / (self.config.sample_rate * window_power * segments as f32);
+        power
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                // All bins except DC and an even Nyquist have a negative
+                // frequency counterpart.
+                let one_sided = if i == 0 || (i == last && segment_len.is_multiple_of(2)) {
+                    1.0
+                } else {
+                    2.0
+                };
+                10.0 * (p * scale * one_sided).log10()
+            })
+            .collect()
+    }
+
+    /// Returns the short-time spectra of the output samples in dB, one frame
+    /// of `fft_size / 2 + 1` bins spread evenly from DC to Nyquist every
+    /// `hop` samples.
+    ///
+    /// The frames are Hann-windowed and scaled so that a full-scale sine
+    /// reads 0dB. Levels are limited to the magnitude floor of -100dB.
+    pub fn spectrogram(&self, fft_size: usize, hop: usize) -> Vec<Vec<f32>> {
+        assert!(
+            fft_size >= 2 && fft_size <= self.out_samples.len(),
+            "FFT size of {fft_size} samples does not fit the output"
+        );
+
+        stft(&self.out_samples, fft_size, hop)
             .iter()
-            .map(|v| v.arg() / std::f32::consts::PI * 180.0)
+            .map(|frame| magnitude_db(frame))
+            .collect()
+    }
+
+    /// Plots the spectrogram of the output samples as PNG file,
+    /// see [`FftAnalyzer::spectrogram`].
+    ///
+    /// The levels are colored by the configured colormap.
+    pub fn plot_spectrogram(
+        &self,
+        fft_size: usize,
+        hop: usize,
+        title: &str,
+        filename: impl AsRef<std::path::Path>,
+    ) -> Result<()> {
+        plot::with_png(filename, |root| {
+            self.render_spectrogram(fft_size, hop, title, root)
+        })
+    }
+
+    /// Renders the spectrogram of the output samples into a drawing area,
+    /// see [`FftAnalyzer::plot_spectrogram`].
+    pub fn render_spectrogram<DB: DrawingBackend>(
+        &self,
+        fft_size: usize,
+        hop: usize,
+        title: &str,
+        area: &DrawingArea<DB, Shift>,
+    ) {
+        plot::render_spectrogram(
+            area,
+            title,
+            &self.config.title_font,
+            &self.spectrogram(fft_size, hop),
+            self.config.sample_rate,
+            hop,
+            self.config.colormap,
+            &self.config.colormap_range_db,
+        );
+    }
+
+    /// Plots the energy decay relief of the impulse response as PNG file,
+    /// see [`edr`](crate::edr).
+    ///
+    /// The impulse response is derived from the transfer function, so any
+    /// test signal can be used. The levels are colored by the configured
+    /// colormap.
+    pub fn plot_edr(
+        &self,
+        fft_size: usize,
+        hop: usize,
+        title: &str,
+        filename: impl AsRef<std::path::Path>,
+    ) -> Result<()> {
+        plot::with_png(filename, |root| self.render_edr(fft_size, hop, title, root))
+    }
+
+    /// Renders the energy decay relief of the impulse response into a
+    /// drawing area, see [`FftAnalyzer::plot_edr`].
+    pub fn render_edr<DB: DrawingBackend>(
+        &self,
+        fft_size: usize,
+        hop: usize,
+        title: &str,
+        area: &DrawingArea<DB, Shift>,
+    ) {
+        let impulse_response = ifft(&self.transfer, self.out_samples.len());
+        plot::render_spectrogram(
+            area,
+            title,
+            &self.config.title_font,
+            &edr(&impulse_response, fft_size, hop),
+            self.config.sample_rate,
+            hop,
+            self.config.colormap,
+            &self.config.colormap_range_db,
+        );
+    }
+
+    /// Returns the cumulative spectral decay of the impulse response in dB,
+    /// `slices` spectra of `window_len / 2 + 1` bins spread evenly from DC to
+    /// Nyquist, the window starting `hop` samples later for each slice.
+    ///
+    /// The impulse response is derived from the transfer function, so any
+    /// test signal can be used. The windows are rectangular with a
+    /// raised-cosine taper over their last quarter, windows reaching past the
+    /// end of the response are padded with zeros.
+    pub fn cumulative_spectral_decay(
+        &self,
+        window_len: usize,
+        hop: usize,
+        slices: usize,
+    ) -> Vec<Vec<f32>> {
+        assert!(
+            window_len >= 2 && window_len <= self.out_samples.len(),
+            "Window of {window_len} samples does not fit the output"
+        );
+
+        let impulse_response = ifft(&self.transfer, self.out_samples.len());
+        let taper_len = window_len / 4;
+        let window: Vec<f32> = (0..window_len)
+            .map(|i| match (i + taper_len).checked_sub(window_len) {
+                Some(position) => {
+                    let phase = std::f32::consts::PI * (position + 1) as f32 / taper_len as f32;
+                    0.5 + 0.5 * phase.cos()
+                }
+                None => 1.0,
+            })
             .collect();
+
+        (0..slices)
+            .map(|slice| {
+                let start = slice * hop;
+                let windowed: Vec<f32> = window
+                    .iter()
+                    .enumerate()
+                    .map(|(i, w)| impulse_response.get(start + i).copied().unwrap_or(0.0) * w)
+                    .collect();
+                magnitude_db(&fft(&windowed))
+            })
+            .collect()
+    }
+
+    /// Plots the cumulative spectral decay as waterfall PNG file,
+    /// see [`FftAnalyzer::cumulative_spectral_decay`].
+    ///
+    /// 30 slices of 2048 samples are shown in steps of 1ms, the later ones
+    /// offset to the back, down to 60dB below the peak.
+    pub fn plot_waterfall(&self, title: &str, filename: impl AsRef<std::path::Path>) -> Result<()> {
+        plot::with_png(filename, |root| self.render_waterfall(title, root))
+    }
+
+    /// Renders the waterfall into a drawing area,
+    /// see [`FftAnalyzer::plot_waterfall`].
+    pub fn render_waterfall<DB: DrawingBackend>(&self, title: &str, area: &DrawingArea<DB, Shift>) {
+        let hop = ((self.config.sample_rate * WATERFALL_STEP).round() as usize).max(1);
+        plot::render_waterfall(
+            area,
+            title,
+            &self.config.title_font,
+            &self.cumulative_spectral_decay(WATERFALL_WINDOW, hop, WATERFALL_SLICES),
+            self.config.sample_rate,
+            self.config.colormap,
+            WATERFALL_RANGE_DB,
+        );
+    }
+
+    /// Returns the frequency response of the last run.
+    pub fn response(&self) -> FrequencyResponse {
+        FrequencyResponse::new(
+            self.config.sample_rate,
+            self.spectrum_magnitude.clone(),
+            self.spectrum_phase.clone(),
+            self.valid_band.clone(),
+        )
+    }
+
+    /// Runs the test signal through a processor for each named configuration,
+    /// e.g. a set of filter parameters, and returns the frequency responses.
+    ///
+    /// `processor` returns the processing function for a configuration, which
+    /// is called in the same way as for [`FftAnalyzer::run`]. The magnitude and
+    /// phase of each configuration are plotted to `magnitude.svg` and
+    /// `phase.svg` in `out_dir/<name>/`. An index listing the summary of each
+    /// response is written to `out_dir/index.txt`.
+    ///
+    /// Returns the first error of a run or of writing a file. The analyzer
+    /// holds the result of the last configuration afterwards.
+    pub fn analyze_all<C, P, F>(
+        &mut self,
+        configs: &[(String, C)],
+        out_dir: impl AsRef<std::path::Path>,
+        mut processor: P,
+    ) -> Result<Vec<FrequencyResponse>>
+    where
+        P: FnMut(&C) -> F,
+        F: FnMut(&[f32], &mut [f32]),
+    {
+        let out_dir = out_dir.as_ref();
+        let mut responses = Vec::with_capacity(configs.len());
+        let mut index = String::new();
+
+        for (name, config) in configs {
+            self.run(processor(config))?;
+
+            let dir = out_dir.join(name);
+            self.plot_magnitude(name, dir.join("magnitude.svg"))?;
+            self.plot_phase(name, dir.join("phase.svg"))?;
+
+            let response = self.response();
+            index.push_str(&format!("{name}: {response}\n"));
+            responses.push(response);
+        }
+
+        std::fs::create_dir_all(out_dir)?;
+        std::fs::write(out_dir.join("index.txt"), index)?;
+
+        Ok(responses)
     }
 
     /// Saves the input signal as WAV file.
@@ -121,68 +1397,698 @@ impl FftAnalyzer {
         wav_writer::write(filename, self.config.sample_rate as u32, &self.out_samples).unwrap();
     }
 
-    /// Plots the magnitude as SVG file.
-    pub fn plot_magnitude(&self, title: &str, filename: impl AsRef<std::path::Path>) {
-        Plot {
-            title,
-            bode: true,
-            series: &[Series {
+    /// Saves the output signal with a gain in dB applied as WAV file,
+    /// e.g. to make the output of a quiet measurement audible.
+    ///
+    /// Only the written samples are scaled, the output samples and the
+    /// spectrum are left unchanged.
+    pub fn save_output_with_gain(
+        &self,
+        filename: impl AsRef<std::path::Path> + core::fmt::Display,
+        gain_db: f32,
+    ) {
+        let gain = 10.0f32.powf(gain_db / 20.0);
+        let samples: Vec<f32> = self.out_samples.iter().map(|v| v * gain).collect();
+        wav_writer::write(filename, self.config.sample_rate as u32, &samples).unwrap();
+    }
+
+    /// Saves the input and output signals as 2-channel WAV file,
+    /// with the input in the left and the output in the right channel.
+    pub fn save_measurement(&self, filename: impl AsRef<std::path::Path> + core::fmt::Display) {
+        wav_writer::write_channels(
+            filename,
+            self.config.sample_rate as u32,
+            &[&self.in_samples, &self.out_samples],
+        )
+        .unwrap();
+    }
+
+    /// Returns the time in seconds, the input and the output of each sample.
+    pub fn time_domain(&self) -> impl Iterator<Item = (f32, f32, f32)> + '_ {
+        let sample_rate = self.config.sample_rate;
+        self.in_samples
+            .iter()
+            .zip(self.out_samples.iter())
+            .enumerate()
+            .map(move |(index, (input, output))| (index as f32 / sample_rate, *input, *output))
+    }
+
+    /// Saves the time, input and output of each sample as CSV file,
+    /// see [`FftAnalyzer::time_domain`].
+    ///
+    /// The first line holds the column names `time,input,output`.
+    pub fn save_time_domain_csv(&self, filename: impl AsRef<std::path::Path>) {
+        use std::fmt::Write;
+
+        let path = filename.as_ref();
+        let _ = std::fs::create_dir_all(path.parent().expect("Filename is empty"));
+
+        let mut csv = String::from("time,input,output\n");
+        for (time, input, output) in self.time_domain() {
+            writeln!(csv, "{time},{input},{output}").unwrap();
+        }
+        std::fs::write(path, csv).unwrap();
+    }
+
+    /// Returns the linear magnitude of the spectrum of the input samples,
+    /// scaled by the configured FFT normalization.
+    pub fn input_spectrum(&self) -> Vec<f32> {
+        normalized_spectrum(&self.in_samples, self.config.fft_normalization)
+    }
+
+    /// Returns the linear magnitude of the spectrum of the output samples,
+    /// scaled by the configured FFT normalization.
+    pub fn output_spectrum(&self) -> Vec<f32> {
+        normalized_spectrum(&self.out_samples, self.config.fft_normalization)
+    }
+
+    /// Returns the magnitude of the spectrum in the configured scale.
+    ///
+    /// The values are derived from the transfer function of the last run.
+    pub fn magnitude(&self) -> Vec<f32> {
+        match self.config.magnitude_scale {
+            MagnitudeScale::Db => magnitude_db(&self.transfer),
+            MagnitudeScale::Linear | MagnitudeScale::LinearLog => {
+                self.transfer.iter().map(|v| v.norm()).collect()
+            }
+            MagnitudeScale::DbRelative(reference) => magnitude_db(&self.transfer)
+                .iter()
+                .map(|v| v - reference)
+                .collect(),
+        }
+    }
+
+    /// Plots the magnitude in the configured scale as SVG file.
+    pub fn plot_magnitude(&self, title: &str, filename: impl AsRef<std::path::Path>) -> Result<()> {
+        plot::with_svg(filename, |root| self.render_magnitude(title, root))
+    }
+
+    /// Returns the difference in dB of the magnitude to the magnitude
+    /// of another analyzer for each bin.
+    ///
+    /// Both analyzers must have spectra of the same length.
+    pub fn magnitude_difference(&self, other: &FftAnalyzer) -> Vec<f32> {
+        assert_eq!(
+            self.spectrum_magnitude.len(),
+            other.spectrum_magnitude.len(),
+            "Spectra differ in length"
+        );
+
+        self.spectrum_magnitude
+            .iter()
+            .zip(other.spectrum_magnitude.iter())
+            .map(|(a, b)| a - b)
+            .collect()
+    }
+
+    /// Plots the difference in dB of the magnitude to the magnitude of
+    /// another analyzer as SVG file, see [`FftAnalyzer::magnitude_difference`].
+    ///
+    /// The Y axis is symmetric around 0dB, so that small deviations
+    /// remain visible.
+    pub fn plot_magnitude_difference(
+        &self,
+        other: &FftAnalyzer,
+        title: &str,
+        filename: impl AsRef<std::path::Path>,
+    ) -> Result<()> {
+        let difference = self.magnitude_difference(other);
+        self.plot_deviation("Difference", &difference, title, filename)
+    }
+
+    /// Returns the deviation in dB of the magnitude from an ideal response,
+    /// e.g. the analytic response of a filter, given in dB for each bin.
+    ///
+    /// Bins outside the valid band, see [`FftAnalyzer::valid_band`], or not
+    /// excited by the input have an error of 0dB. Nulls of the measured
+    /// response within the excited bins are reported like any other bin.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ideal response differs in length from the spectrum.
+    pub fn magnitude_error(&self, ideal: &[f32]) -> Vec<f32> {
+        assert_eq!(
+            self.spectrum_magnitude.len(),
+            ideal.len(),
+            "Ideal response differs in length from the spectrum"
+        );
+
+        let in_spectrum = fft(&self.in_samples);
+        let mask = excitation_mask(&in_spectrum, &self.valid_band, self.config.sample_rate);
+        self.spectrum_magnitude
+            .iter()
+            .zip(ideal.iter())
+            .zip(mask)
+            .map(
+                |((measured, ideal), excited)| {
+                    if excited {
+                        measured - ideal
+                    } else {
+                        0.0
+                    }
+                },
+            )
+            .collect()
+    }
+
+    /// Plots the deviation of the magnitude from an ideal response as SVG file,
+    /// see [`FftAnalyzer::magnitude_error`].
+    ///
+    /// The Y axis is symmetric around 0dB and spans the largest deviation,
+    /// rounded up to 1dB.
+    pub fn plot_magnitude_error(
+        &self,
+        ideal: &[f32],
+        title: &str,
+        filename: impl AsRef<std::path::Path>,
+    ) -> Result<()> {
+        let error = self.magnitude_error(ideal);
+        self.plot_deviation("Error", &error, title, filename)
+    }
+
+    /// Plots the max-hold and min-hold curves returned by
+    /// [`FftAnalyzer::run_with_hold`] as SVG file, with the area between
+    /// them shaded.
+    pub fn plot_magnitude_hold(
+        &self,
+        max_hold: &[f32],
+        min_hold: &[f32],
+        title: &str,
+        filename: impl AsRef<std::path::Path>,
+    ) -> Result<()> {
+        let mut plot = Plot::builder()
+            .title(title)
+            .bode(true)
+            .add_series(Series {
+                label: "Max hold",
+                samplerate: self.config.sample_rate,
+                series: max_hold,
+                color: &BLUE,
+                ..Default::default()
+            })
+            .add_series(Series {
+                label: "Min hold",
+                samplerate: self.config.sample_rate,
+                series: min_hold,
+                color: &BLUE,
+                style: LineStyle::Dashed,
+                ..Default::default()
+            })
+            .fill_between(0, 1)
+            .y_range(AxisRange::AutoDb)
+            .title_font(self.config.title_font.clone())
+            .build();
+        plot.valid_band = self.shaded_band();
+        plot.create_svg(filename)
+    }
+
+    /// Plots deviations in dB on a Y axis symmetric around 0dB as SVG file.
+    fn plot_deviation(
+        &self,
+        label: &str,
+        deviation: &[f32],
+        title: &str,
+        filename: impl AsRef<std::path::Path>,
+    ) -> Result<()> {
+        let limit = deviation.iter().fold(0.0f32, |limit, v| limit.max(v.abs()));
+        let limit =
+            ((limit / DIFFERENCE_STEP_DB).ceil() * DIFFERENCE_STEP_DB).max(DIFFERENCE_STEP_DB);
+        let mut plot = Plot::builder()
+            .title(title)
+            .bode(true)
+            .add_series(Series {
+                label,
+                samplerate: self.config.sample_rate,
+                series: deviation,
+                color: &BLUE,
+                ..Default::default()
+            })
+            .y_range(AxisRange::ManualLin(-limit..limit))
+            .title_font(self.config.title_font.clone())
+            .build();
+        plot.valid_band = self.shaded_band();
+        plot.create_svg(filename)
+    }
+
+    /// Renders the magnitude in the configured scale into a drawing area,
+    /// e.g. one panel of a figure split with [`DrawingArea::split_evenly`].
+    pub fn render_magnitude<DB: DrawingBackend>(&self, title: &str, area: &DrawingArea<DB, Shift>) {
+        self.render_magnitude_in(title, area, None);
+    }
+
+    /// Plots the magnitude as SVG file with the frequency axis limited to
+    /// a range in Hz, e.g. the audio band at a high sample rate.
+    ///
+    /// Bins outside the range are not drawn.
+    pub fn plot_magnitude_range(
+        &self,
+        title: &str,
+        filename: impl AsRef<std::path::Path>,
+        freq_range: Range<f32>,
+    ) -> Result<()> {
+        plot::with_svg(filename, |root| {
+            self.render_magnitude_in(title, root, Some(freq_range))
+        })
+    }
+
+    /// Draws the magnitude, optionally limited to a frequency range in Hz.
+    fn render_magnitude_in<DB: DrawingBackend>(
+        &self,
+        title: &str,
+        area: &DrawingArea<DB, Shift>,
+        freq_range: Option<Range<f32>>,
+    ) {
+        let magnitude = self.magnitude();
+        let y_range = match self.config.magnitude_scale {
+            MagnitudeScale::LinearLog => AxisRange::ManualLog(self.magnitude_range()),
+            _ => AxisRange::ManualLin(self.magnitude_range()),
+        };
+        let mut plot = Plot::builder()
+            .title(title)
+            .bode(true)
+            .add_series(Series {
                 label: "Magnitude",
                 samplerate: self.config.sample_rate,
-                series: self.spectrum_magnitude.as_slice(),
+                series: magnitude.as_slice(),
                 color: &BLUE,
-            }],
-            y_range: AxisRange::AutoLin,
+                ..Default::default()
+            })
+            .y_range(y_range)
+            .title_font(self.config.title_font.clone())
+            .build();
+        plot.x_range = freq_range;
+        plot.valid_band = self.shaded_band();
+        plot.render_into(area);
+    }
+
+    /// Returns the Y axis range used by [`FftAnalyzer::plot_magnitude`].
+    ///
+    /// For the dB scales, bins below -120dB are ignored and the bounds are
+    /// rounded outwards to multiples of 6dB. The linear scale starts at zero
+    /// and leaves some headroom above the highest value. The logarithmic
+    /// scale spans the decades of the positive values down to -120dB.
+    pub fn magnitude_range(&self) -> core::ops::Range<f32> {
+        let magnitude = self.magnitude();
+        match self.config.magnitude_scale {
+            MagnitudeScale::Db | MagnitudeScale::DbRelative(_) => plot::db_range(&magnitude),
+            MagnitudeScale::Linear => {
+                let max = magnitude.iter().copied().fold(0.0, f32::max);
+                if max > 0.0 {
+                    0.0..max * 1.1
+                } else {
+                    0.0..1.0
+                }
+            }
+            MagnitudeScale::LinearLog => plot::log_range(&magnitude),
         }
-        .create_svg(filename);
     }
 
     /// Plots the phase as SVG file.
-    pub fn plot_phase(&self, title: &str, filename: impl AsRef<std::path::Path>) {
-        Plot {
-            title,
-            bode: true,
-            series: &[Series {
+    pub fn plot_phase(&self, title: &str, filename: impl AsRef<std::path::Path>) -> Result<()> {
+        plot::with_svg(filename, |root| self.render_phase(title, root))
+    }
+
+    /// Renders the phase into a drawing area, e.g. one panel of a figure
+    /// split with [`DrawingArea::split_evenly`].
+    pub fn render_phase<DB: DrawingBackend>(&self, title: &str, area: &DrawingArea<DB, Shift>) {
+        let mut plot = Plot::builder()
+            .title(title)
+            .bode(true)
+            .add_series(Series {
                 label: "Phase",
                 samplerate: self.config.sample_rate,
                 series: self.spectrum_phase.as_slice(),
                 color: &RED,
-            }],
-            y_range: AxisRange::ManualLin(-180.0..180.0),
+                ..Default::default()
+            })
+            .y_range(AxisRange::ManualLin(-180.0..180.0))
+            .title_font(self.config.title_font.clone())
+            .build();
+        plot.valid_band = self.shaded_band();
+        plot.render_into(area);
+    }
+
+    /// Plots the phase smoothed over `1 / fraction` octave as SVG file.
+    pub fn plot_phase_smoothed(
+        &self,
+        fraction: f32,
+        title: &str,
+        filename: impl AsRef<std::path::Path>,
+    ) -> Result<()> {
+        let bin_width =
+            self.config.sample_rate / 2.0 / (self.spectrum_phase.len() - 1).max(1) as f32;
+        let phase = smoothing::smooth_phase(&self.spectrum_phase, bin_width, fraction);
+        let mut plot = Plot::builder()
+            .title(title)
+            .bode(true)
+            .add_series(Series {
+                label: "Phase (smoothed)",
+                samplerate: self.config.sample_rate,
+                series: phase.as_slice(),
+                color: &RED,
+                ..Default::default()
+            })
+            .y_range(AxisRange::ManualLin(-180.0..180.0))
+            .title_font(self.config.title_font.clone())
+            .build();
+        plot.valid_band = self.shaded_band();
+        plot.create_svg(filename)
+    }
+
+    /// Returns the valid band if it is shaded in bode plots.
+    fn shaded_band(&self) -> Option<Range<f32>> {
+        self.config
+            .shade_invalid_band
+            .then(|| self.valid_band.clone())
+    }
+
+    /// Plots the input and output samples over time as SVG file.
+    ///
+    /// Trailing samples are omitted once both signals stay below -60dB
+    /// relative to the output peak, so that the decay of a resonance after
+    /// a [`TestSignal::ToneBurst`] fills the plot.
+    pub fn plot_output_time(
+        &self,
+        title: &str,
+        filename: impl AsRef<std::path::Path>,
+    ) -> Result<()> {
+        let threshold = self.output_peak() * TIME_PLOT_THRESHOLD;
+        let length = self
+            .in_samples
+            .iter()
+            .zip(self.out_samples.iter())
+            .rposition(|(input, output)| input.abs() > threshold || output.abs() > threshold)
+            .map_or(self.out_samples.len(), |index| index + 1);
+
+        Plot::builder()
+            .title(title)
+            .add_series(Series {
+                label: "Input",
+                samplerate: self.config.sample_rate,
+                series: &self.in_samples[..length],
+                color: &BLUE,
+                ..Default::default()
+            })
+            .add_series(Series {
+                label: "Output",
+                samplerate: self.config.sample_rate,
+                series: &self.out_samples[..length],
+                color: &RED,
+                ..Default::default()
+            })
+            .y_range(AxisRange::AutoLin)
+            .title_font(self.config.title_font.clone())
+            .build()
+            .create_svg(filename)
+    }
+}
+
+/// Checks if a signal can be used as excitation.
+pub(crate) fn validate_signal<T>(input: &[T]) -> Result<()> {
+    match input.len() {
+        0 => Err(AnalyzerError::EmptySignal),
+        1 => Err(AnalyzerError::SignalTooShort(1)),
+        _ => Ok(()),
+    }
+}
+
+/// Returns the highest absolute value of the samples.
+fn peak(samples: &[f32]) -> f32 {
+    samples
+        .iter()
+        .fold(0.0, |peak, sample| peak.max(sample.abs()))
+}
+
+/// Returns the RMS level of the samples, 0.0 if there are none.
+fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+
+    (samples.iter().map(|sample| sample * sample).sum::<f32>() / samples.len() as f32).sqrt()
+}
+
+/// Returns the ratio of the peak to the RMS level of the samples in dB,
+/// 0dB for silence.
+fn crest_factor_db(samples: &[f32]) -> f32 {
+    let rms = rms(samples);
+    if rms == 0.0 {
+        return 0.0;
+    }
+
+    20.0 * (peak(samples) / rms).log10()
+}
+
+/// Checks that all output samples are finite.
+pub(crate) fn validate_output<T: Float>(output: &[T]) -> Result<()> {
+    match output.iter().position(|sample| !sample.is_finite()) {
+        Some(index) => Err(AnalyzerError::NonFiniteOutput(
+            index,
+            output[index].to_f32().unwrap_or(f32::NAN),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Passes the signal block-wise to the processing function.
+pub(crate) fn process_blocks<T, F>(
+    in_samples: &[T],
+    out_samples: &mut [T],
+    block_size: usize,
+    func: &mut F,
+) where
+    F: FnMut(&[T], &mut [T]) + ?Sized,
+{
+    for (in_block, out_block) in in_samples
+        .chunks(block_size)
+        .zip(out_samples.chunks_mut(block_size))
+    {
+        func(in_block, out_block);
+    }
+}
+
+/// Returns for each bin of the input spectrum if it lies within the band
+/// and is excited above [`INPUT_THRESHOLD_DB`].
+pub(crate) fn excitation_mask<T: Float>(
+    in_spectrum: &[Complex<T>],
+    band: &Range<f32>,
+    sample_rate: f32,
+) -> Vec<bool> {
+    let max_norm = in_spectrum.iter().map(|v| v.norm()).fold(T::zero(), T::max);
+    let threshold = max_norm * T::from(10.0f32.powf(INPUT_THRESHOLD_DB / 20.0)).unwrap();
+    let bin_width = sample_rate / 2.0 / (in_spectrum.len() - 1).max(1) as f32;
+
+    in_spectrum
+        .iter()
+        .enumerate()
+        .map(|(i, v)| {
+            let freq = i as f32 * bin_width;
+            freq >= band.start && freq <= band.end && v.norm() > threshold
+        })
+        .collect()
+}
+
+/// Returns the transfer function as ratio of the output to the input spectrum.
+///
+/// Bins not set in the mask are set to zero. The DC bin and, for an even
+/// number of samples, the Nyquist bin are real-valued in both spectra, so the
+/// imaginary part of their ratio is only rounding noise and is dropped. Their
+/// phase is thus exactly 0° or ±180°.
+pub(crate) fn transfer_function<T: FftNum + Float>(
+    in_spectrum: &[Complex<T>],
+    out_samples: &[T],
+    mask: &[bool],
+) -> Vec<Complex<T>> {
+    let mut transfer: Vec<Complex<T>> = fft(out_samples)
+        .iter()
+        .zip(in_spectrum.iter())
+        .zip(mask.iter())
+        .map(|((out_bin, in_bin), valid)| {
+            if *valid {
+                out_bin / in_bin
+            } else {
+                Complex::new(T::zero(), T::zero())
+            }
+        })
+        .collect();
+
+    if let Some(dc) = transfer.first_mut() {
+        dc.im = T::zero();
+    }
+    if out_samples.len().is_multiple_of(2) {
+        if let Some(nyquist) = transfer.last_mut() {
+            nyquist.im = T::zero();
         }
-        .create_svg(filename);
     }
+
+    transfer
+}
+
+/// Rotates the phase of a transfer function to compensate a constant delay.
+///
+/// `length` is the number of samples the spectrum was computed from.
+pub(crate) fn compensate_delay<T: FftNum + Float>(
+    transfer: &mut [Complex<T>],
+    compensation: DelayCompensation,
+    length: usize,
+) {
+    let delay = match compensation {
+        DelayCompensation::Off => return,
+        DelayCompensation::Samples(delay) => delay,
+        DelayCompensation::Auto => ifft(transfer, length)
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| {
+                Float::abs(**a)
+                    .partial_cmp(&Float::abs(**b))
+                    .unwrap_or(core::cmp::Ordering::Equal)
+            })
+            .map(|(index, _)| index as f32)
+            .unwrap_or(0.0),
+    };
+
+    for (k, bin) in transfer.iter_mut().enumerate() {
+        let angle =
+            T::from(std::f32::consts::TAU).unwrap() * T::from(k).unwrap() * T::from(delay).unwrap()
+                / T::from(length).unwrap();
+        *bin = *bin * Complex::from_polar(T::one(), angle);
+    }
+}
+
+/// Interpolates the values of bins spread evenly from DC to Nyquist at a
+/// frequency. Phases in degrees are interpolated along the shorter way
+/// around the circle if `wrap_phase` is set.
+pub(crate) fn interpolate(
+    values: &[f32],
+    sample_rate: f32,
+    freq: f32,
+    interpolation: Interpolation,
+    wrap_phase: bool,
+) -> f32 {
+    let last = values.len() - 1;
+    let bin_width = sample_rate / 2.0 / last.max(1) as f32;
+    let position = (freq / bin_width).clamp(0.0, last as f32);
+    let index = (position.floor() as usize).min(last.saturating_sub(1));
+    if index == last {
+        return values[index];
+    }
+
+    let (f0, f1) = (index as f32 * bin_width, (index + 1) as f32 * bin_width);
+    let freq = freq.clamp(f0, f1);
+    let t = match interpolation {
+        Interpolation::Log if f0 > 0.0 => (freq / f0).ln() / (f1 / f0).ln(),
+        _ => (freq - f0) / (f1 - f0),
+    };
+
+    let (v0, v1) = (values[index], values[index + 1]);
+    let delta = if wrap_phase {
+        (v1 - v0 + 180.0).rem_euclid(360.0) - 180.0
+    } else {
+        v1 - v0
+    };
+    let value = v0 + delta * t;
+
+    if wrap_phase && value.abs() > 180.0 {
+        value - 360.0 * value.signum()
+    } else {
+        value
+    }
+}
+
+/// Returns the linear magnitude of the spectrum of the samples, scaled
+/// according to the normalization.
+fn normalized_spectrum(samples: &[f32], normalization: FftNormalization) -> Vec<f32> {
+    if samples.is_empty() {
+        return Vec::new();
+    }
+
+    let factor = normalization.factor(samples.len());
+    fft(samples).iter().map(|v| v.norm() * factor).collect()
+}
+
+/// Returns the magnitude of a spectrum in dB.
+pub(crate) fn magnitude_db<T: Float>(spectrum: &[Complex<T>]) -> Vec<T> {
+    // TODO: check if clamping the magnitude is required after FFT results are improved.
+    let floor = T::from(MAGNITUDE_FLOOR_DB).unwrap();
+    let ceiling = T::from(100.0).unwrap();
+    spectrum
+        .iter()
+        .map(|v| {
+            (T::from(20.0).unwrap() * v.norm().log10())
+                .max(floor)
+                .min(ceiling)
+        })
+        .collect()
+}
+
+/// Returns the phase of a spectrum in degrees.
+pub(crate) fn phase_deg<T: Float>(spectrum: &[Complex<T>]) -> Vec<T> {
+    let pi = T::from(std::f64::consts::PI).unwrap();
+    let half_turn = T::from(180.0).unwrap();
+    spectrum.iter().map(|v| v.arg() / pi * half_turn).collect()
 }
 
 /// Returns a `Vec` containing a unit impulse.
 fn unit_impulse(length: usize) -> Vec<f32> {
-    let mut samples = vec![0.0; length];
-    samples[0] = 1.0;
+    ImpulseGenerator::new(0).generate(length)
+}
+
+/// Returns a `Vec` containing a sine burst of `cycles` cycles with a Hann
+/// envelope, padded with zeros to a length of one second.
+fn tone_burst(sample_rate: f32, freq: f32, cycles: u32) -> Vec<f32> {
+    let burst_length = (cycles as f32 * sample_rate / freq).round() as usize;
+    let mut samples = vec![0.0; burst_length.max(sample_rate as usize)];
+
+    for (n, sample) in samples.iter_mut().take(burst_length).enumerate() {
+        let envelope = 0.5 - 0.5 * (core::f32::consts::TAU * n as f32 / burst_length as f32).cos();
+        *sample = envelope * (core::f32::consts::TAU * freq * n as f32 / sample_rate).sin();
+    }
 
     samples
 }
 
 /// Returns a `Vec` of sweep samples.
-fn sweep(sample_rate: f32) -> Vec<f32> {
+///
+/// The generator is run in chunks of `block_size` samples. The number of
+/// generated samples does not depend on the chunk size.
+fn sweep(sample_rate: f32, block_size: usize, mode: SweepMode, end_taper: f32) -> Vec<f32> {
     let mut sweep_generator = SweepGenerator::new(sample_rate);
-    sweep_generator.set_range(1.0, 20000.0);
+    sweep_generator.set_mode(mode);
+    sweep_generator.set_end_taper(end_taper);
+    sweep_generator.set_range(SWEEP_MIN_FREQ, SWEEP_MAX_FREQ);
     sweep_generator.set_time(1.0);
     sweep_generator.start();
 
-    let mut buffer = [0.0; 16];
+    let mut buffer = vec![0.0; block_size.max(1)];
     let mut samples = Vec::new();
 
-    while sweep_generator.process(&mut buffer).is_ok() {
-        samples.extend(buffer);
+    while let Ok(count) = sweep_generator.process(&mut buffer) {
+        samples.extend_from_slice(&buffer[..count]);
     }
 
     samples
 }
 
+/// Resamples a signal to `length` samples by band-limited interpolation.
+///
+/// The spectrum is truncated or extended with zeros, so the signal is
+/// treated as periodic.
+fn resample_periodic<T: FftNum + Float>(samples: &[T], length: usize) -> Vec<T> {
+    let mut spectrum = fft(samples);
+    spectrum.resize(length / 2 + 1, Complex::new(T::zero(), T::zero()));
+
+    let scale = T::from(length).unwrap() / T::from(samples.len()).unwrap();
+    for bin in spectrum.iter_mut() {
+        *bin = *bin * scale;
+    }
+
+    ifft(&spectrum, length)
+}
+
 /// Runs the FFT over the input samples and returns the spectrum.
-fn fft(indata: &[f32]) -> Vec<Complex<f32>> {
+pub(crate) fn fft<T: FftNum>(indata: &[T]) -> Vec<Complex<T>> {
     // Make a planner.
-    let mut real_planner = RealFftPlanner::<f32>::new();
+    let mut real_planner = RealFftPlanner::<T>::new();
 
     // Create an FFT.
     let r2c = real_planner.plan_fft_forward(indata.len());
@@ -196,3 +2102,73 @@ fn fft(indata: &[f32]) -> Vec<Complex<f32>> {
 
     spectrum
 }
+
+/// Returns the DFT of the samples at a frequency by the Goertzel algorithm.
+///
+/// The result carries an additional phase factor depending on the number of
+/// samples, which cancels in the ratio of two signals of equal length.
+pub(crate) fn goertzel(samples: &[f32], freq: f32, sample_rate: f32) -> Complex<f32> {
+    // The recursion is run in double precision, as it accumulates
+    // rounding errors over the samples.
+    let omega = std::f64::consts::TAU * freq as f64 / sample_rate as f64;
+    let coeff = 2.0 * omega.cos();
+
+    let (s1, s2) = samples.iter().fold((0.0, 0.0), |(s1, s2), sample| {
+        (*sample as f64 + coeff * s1 - s2, s1)
+    });
+
+    Complex::new((s1 - omega.cos() * s2) as f32, (omega.sin() * s2) as f32)
+}
+
+/// Returns the short-time spectra of the samples, one frame of
+/// `fft_size / 2 + 1` bins every `hop` samples, for the frames fitting
+/// into the samples.
+///
+/// The frames are Hann-windowed and scaled so that a full-scale sine
+/// has a magnitude of 1.
+pub(crate) fn stft(samples: &[f32], fft_size: usize, hop: usize) -> Vec<Vec<Complex<f32>>> {
+    assert!(
+        fft_size >= 2 && fft_size <= samples.len(),
+        "FFT size of {fft_size} samples does not fit the signal"
+    );
+    assert!(hop > 0, "Hop size is zero");
+
+    let window: Vec<f32> = (0..fft_size)
+        .map(|i| {
+            let phase = std::f32::consts::TAU * i as f32 / fft_size as f32;
+            0.5 - 0.5 * phase.cos()
+        })
+        .collect();
+    let scale = 2.0 / window.iter().sum::<f32>();
+
+    (0..=(samples.len() - fft_size) / hop)
+        .map(|frame| {
+            let start = frame * hop;
+            let windowed: Vec<f32> = samples[start..start + fft_size]
+                .iter()
+                .zip(window.iter())
+                .map(|(sample, w)| sample * w * scale)
+                .collect();
+            fft(&windowed)
+        })
+        .collect()
+}
+
+/// Runs the inverse FFT over a spectrum and returns `length` normalized samples.
+pub(crate) fn ifft<T: FftNum>(spectrum: &[Complex<T>], length: usize) -> Vec<T> {
+    let mut real_planner = RealFftPlanner::<T>::new();
+    let c2r = real_planner.plan_fft_inverse(length);
+
+    // The DC and Nyquist bins of a real signal have no imaginary part.
+    let mut spectrum = spectrum.to_owned();
+    spectrum[0].im = T::zero();
+    if length.is_multiple_of(2) {
+        spectrum[length / 2].im = T::zero();
+    }
+
+    let mut outdata = c2r.make_output_vec();
+    c2r.process(&mut spectrum, &mut outdata).unwrap();
+
+    let scale = T::one() / T::from_usize(length).unwrap();
+    outdata.iter().map(|v| *v * scale).collect()
+}
@@ -0,0 +1,173 @@
+//! FFT analyzer with double precision.
+
+use std::ops::Range;
+
+use plotters::prelude::*;
+
+use crate::bode::{
+    compensate_delay, excitation_mask, fft, magnitude_db, phase_deg, transfer_function,
+    validate_output, validate_signal, FftAnalyzerConfig, Result,
+};
+use crate::plot::{AxisRange, Plot, Series};
+use crate::response::FrequencyResponse;
+
+/// FFT analyzer processing and transforming the signals in `f64`.
+///
+/// Works like [`crate::FftAnalyzer`], but reduces the accumulation of rounding
+/// errors for long signals and processors with a high Q. The test signal
+/// is generated as by [`crate::FftAnalyzer`] and converted to `f64`, which is
+/// lossless for the unit impulse and the MLS.
+#[derive(Debug)]
+pub struct FftAnalyzer64 {
+    /// Current configuration.
+    pub config: FftAnalyzerConfig,
+
+    /// Input samples.
+    pub in_samples: Vec<f64>,
+
+    /// Output samples.
+    pub out_samples: Vec<f64>,
+
+    /// Magnitude of the spectrum.
+    pub spectrum_magnitude: Vec<f64>,
+
+    /// Phase of the spectrum.
+    pub spectrum_phase: Vec<f64>,
+
+    /// Frequency band in Hz in which the spectrum is valid.
+    pub valid_band: Range<f32>,
+}
+
+impl FftAnalyzer64 {
+    /// Returns a new instance of the analyzer.
+    pub fn new(config: FftAnalyzerConfig) -> Self {
+        Self {
+            config,
+            in_samples: Vec::new(),
+            out_samples: Vec::new(),
+            spectrum_magnitude: Vec::new(),
+            spectrum_phase: Vec::new(),
+            valid_band: 0.0..0.0,
+        }
+    }
+
+    /// Clears the spectrum data.
+    pub fn clear(&mut self) {
+        self.in_samples.clear();
+        self.out_samples.clear();
+        self.spectrum_magnitude.clear();
+        self.spectrum_phase.clear();
+        self.valid_band = 0.0..0.0;
+    }
+
+    /// Runs the test signal through the provided function and
+    /// analyzes the result.
+    ///
+    /// The closure is called in the same way as for [`crate::FftAnalyzer::run`].
+    /// Returns an error if the output contains a non-finite sample.
+    pub fn run<F>(&mut self, func: F) -> Result<()>
+    where
+        F: FnMut(&[f64], &mut [f64]),
+    {
+        let input = self
+            .config
+            .test_signal_samples()
+            .iter()
+            .map(|sample| *sample as f64)
+            .collect();
+        let band = self.config.test_signal_band();
+        self.analyze(input, band, func)
+    }
+
+    /// Runs a user-supplied excitation signal through the provided function
+    /// and analyzes the result.
+    ///
+    /// See [`crate::FftAnalyzer::run_with_signal`] for details.
+    pub fn run_with_signal<F>(&mut self, input: Vec<f64>, func: F) -> Result<()>
+    where
+        F: FnMut(&[f64], &mut [f64]),
+    {
+        let band = 0.0..self.config.sample_rate / 2.0;
+        self.analyze(input, band, func)
+    }
+
+    /// Runs the excitation through the function and computes the spectrum
+    /// within the given band.
+    fn analyze<F>(&mut self, mut input: Vec<f64>, band: Range<f32>, mut func: F) -> Result<()>
+    where
+        F: FnMut(&[f64], &mut [f64]),
+    {
+        validate_signal(&input)?;
+        self.config.pad_to_blocks(&mut input);
+
+        self.clear();
+
+        self.in_samples = input;
+        self.out_samples.clone_from(&self.in_samples);
+        self.config
+            .process(&self.in_samples, &mut self.out_samples, &mut func);
+        validate_output(&self.out_samples)?;
+
+        let in_spectrum = fft(&self.in_samples);
+        let mask = excitation_mask(&in_spectrum, &band, self.config.sample_rate);
+        let mut transfer = transfer_function(&in_spectrum, &self.out_samples, &mask);
+        compensate_delay(
+            &mut transfer,
+            self.config.delay_compensation,
+            self.in_samples.len(),
+        );
+        self.spectrum_magnitude = magnitude_db(&transfer);
+        self.spectrum_phase = phase_deg(&transfer);
+        self.valid_band = band;
+
+        Ok(())
+    }
+
+    /// Returns the frequency response of the last run, converted to `f32`.
+    pub fn response(&self) -> FrequencyResponse {
+        FrequencyResponse::new(
+            self.config.sample_rate,
+            self.spectrum_magnitude.iter().map(|v| *v as f32).collect(),
+            self.spectrum_phase.iter().map(|v| *v as f32).collect(),
+            self.valid_band.clone(),
+        )
+    }
+
+    /// Plots the magnitude as SVG file.
+    pub fn plot_magnitude(&self, title: &str, filename: impl AsRef<std::path::Path>) -> Result<()> {
+        let magnitude: Vec<f32> = self.spectrum_magnitude.iter().map(|v| *v as f32).collect();
+        Plot::builder()
+            .title(title)
+            .bode(true)
+            .add_series(Series {
+                label: "Magnitude",
+                samplerate: self.config.sample_rate,
+                series: magnitude.as_slice(),
+                color: &BLUE,
+                ..Default::default()
+            })
+            .y_range(AxisRange::AutoDb)
+            .title_font(self.config.title_font.clone())
+            .build()
+            .create_svg(filename)
+    }
+
+    /// Plots the phase as SVG file.
+    pub fn plot_phase(&self, title: &str, filename: impl AsRef<std::path::Path>) -> Result<()> {
+        let phase: Vec<f32> = self.spectrum_phase.iter().map(|v| *v as f32).collect();
+        Plot::builder()
+            .title(title)
+            .bode(true)
+            .add_series(Series {
+                label: "Phase",
+                samplerate: self.config.sample_rate,
+                series: phase.as_slice(),
+                color: &RED,
+                ..Default::default()
+            })
+            .y_range(AxisRange::ManualLin(-180.0..180.0))
+            .title_font(self.config.title_font.clone())
+            .build()
+            .create_svg(filename)
+    }
+}
@@ -0,0 +1,229 @@
+//! FFT analyzer for complex signals.
+
+use plotters::prelude::*;
+use realfft::num_complex::Complex;
+use rustfft::FftPlanner;
+
+use crate::bode::{magnitude_db, phase_deg, FftAnalyzerConfig, Result, INPUT_THRESHOLD_DB};
+use crate::bode::{process_blocks, validate_signal, AnalyzerError};
+use crate::plot::{self, LegendConfig, Series};
+
+/// FFT analyzer for complex signals, e.g. IQ baseband signals.
+///
+/// Works like [`crate::FftAnalyzer`], but passes complex samples to the
+/// processor and transforms them with a complex FFT, so that positive and
+/// negative frequencies are told apart. The spectrum holds one bin per
+/// sample from minus to plus Nyquist, with DC at index `len / 2`.
+///
+/// The test signal is generated as by [`crate::FftAnalyzer`] with an imaginary
+/// part of zero, so it excites both sides of the spectrum equally. The
+/// oversampling factor and the delay compensation of the configuration are
+/// not applied.
+#[derive(Debug)]
+pub struct ComplexFftAnalyzer {
+    /// Current configuration.
+    pub config: FftAnalyzerConfig,
+
+    /// Input samples.
+    pub in_samples: Vec<Complex<f32>>,
+
+    /// Output samples.
+    pub out_samples: Vec<Complex<f32>>,
+
+    /// Magnitude of the spectrum in dB from minus to plus Nyquist.
+    pub spectrum_magnitude: Vec<f32>,
+
+    /// Phase of the spectrum in degrees from minus to plus Nyquist.
+    pub spectrum_phase: Vec<f32>,
+}
+
+impl ComplexFftAnalyzer {
+    /// Returns a new instance of the analyzer.
+    pub fn new(config: FftAnalyzerConfig) -> Self {
+        Self {
+            config,
+            in_samples: Vec::new(),
+            out_samples: Vec::new(),
+            spectrum_magnitude: Vec::new(),
+            spectrum_phase: Vec::new(),
+        }
+    }
+
+    /// Clears the spectrum data.
+    pub fn clear(&mut self) {
+        self.in_samples.clear();
+        self.out_samples.clear();
+        self.spectrum_magnitude.clear();
+        self.spectrum_phase.clear();
+    }
+
+    /// Runs the test signal through the provided function and
+    /// analyzes the result.
+    ///
+    /// The closure is called in the same way as for [`crate::FftAnalyzer::run`].
+    /// Returns an error if the output contains a non-finite sample.
+    pub fn run<F>(&mut self, func: F) -> Result<()>
+    where
+        F: FnMut(&[Complex<f32>], &mut [Complex<f32>]),
+    {
+        let input = self
+            .config
+            .test_signal_samples()
+            .iter()
+            .map(|sample| Complex::new(*sample, 0.0))
+            .collect();
+        self.analyze(input, func)
+    }
+
+    /// Runs a user-supplied excitation signal through the provided function
+    /// and analyzes the result.
+    ///
+    /// See [`crate::FftAnalyzer::run_with_signal`] for details. Only bins excited
+    /// by the signal are valid, e.g. a complex exponential only excites
+    /// one side of the spectrum.
+    pub fn run_with_signal<F>(&mut self, input: Vec<Complex<f32>>, func: F) -> Result<()>
+    where
+        F: FnMut(&[Complex<f32>], &mut [Complex<f32>]),
+    {
+        self.analyze(input, func)
+    }
+
+    /// Runs the excitation through the function and computes the spectrum.
+    fn analyze<F>(&mut self, mut input: Vec<Complex<f32>>, mut func: F) -> Result<()>
+    where
+        F: FnMut(&[Complex<f32>], &mut [Complex<f32>]),
+    {
+        validate_signal(&input)?;
+        self.config.pad_to_blocks(&mut input);
+
+        self.clear();
+
+        self.in_samples = input;
+        self.out_samples.clone_from(&self.in_samples);
+        process_blocks(
+            &self.in_samples,
+            &mut self.out_samples,
+            self.config.block_size,
+            &mut func,
+        );
+        if let Some((index, sample)) = self
+            .out_samples
+            .iter()
+            .enumerate()
+            .find(|(_, sample)| !sample.is_finite())
+        {
+            let value = if sample.re.is_finite() {
+                sample.im
+            } else {
+                sample.re
+            };
+            return Err(AnalyzerError::NonFiniteOutput(index, value));
+        }
+
+        let in_spectrum = complex_fft(&self.in_samples);
+        let out_spectrum = complex_fft(&self.out_samples);
+        let max_norm = in_spectrum.iter().map(|v| v.norm()).fold(0.0, f32::max);
+        let threshold = max_norm * 10.0f32.powf(INPUT_THRESHOLD_DB / 20.0);
+
+        // Reorder the bins from minus to plus Nyquist.
+        let mut transfer: Vec<Complex<f32>> = out_spectrum
+            .iter()
+            .zip(in_spectrum.iter())
+            .map(|(out_bin, in_bin)| {
+                if in_bin.norm() > threshold {
+                    out_bin / in_bin
+                } else {
+                    Complex::new(0.0, 0.0)
+                }
+            })
+            .collect();
+        let half = transfer.len() / 2;
+        transfer.rotate_right(half);
+
+        self.spectrum_magnitude = magnitude_db(&transfer);
+        self.spectrum_phase = phase_deg(&transfer);
+
+        Ok(())
+    }
+
+    /// Returns the frequency in Hz of each bin, from minus to plus Nyquist.
+    pub fn frequencies(&self) -> Vec<f32> {
+        let length = self.spectrum_magnitude.len();
+        let bin_width = self.config.sample_rate / length as f32;
+        (0..length)
+            .map(|bin| (bin as f32 - (length / 2) as f32) * bin_width)
+            .collect()
+    }
+
+    /// Returns the index of the bin closest to a frequency in Hz,
+    /// negative frequencies included.
+    pub fn bin_index(&self, freq: f32) -> usize {
+        let length = self.spectrum_magnitude.len();
+        let bin_width = self.config.sample_rate / length as f32;
+        ((freq / bin_width).round() + (length / 2) as f32).clamp(0.0, (length - 1) as f32) as usize
+    }
+
+    /// Returns the magnitude in dB at the bin closest to a frequency.
+    pub fn magnitude_at(&self, freq: f32) -> f32 {
+        self.spectrum_magnitude[self.bin_index(freq)]
+    }
+
+    /// Returns the phase in degrees at the bin closest to a frequency.
+    pub fn phase_at(&self, freq: f32) -> f32 {
+        self.spectrum_phase[self.bin_index(freq)]
+    }
+
+    /// Plots the magnitude over a linear axis from minus to plus Nyquist
+    /// as SVG file.
+    pub fn plot_magnitude(&self, title: &str, filename: impl AsRef<std::path::Path>) -> Result<()> {
+        plot::with_svg(filename, |root| {
+            plot::render_two_sided(
+                root,
+                title,
+                &self.config.title_font,
+                &Series {
+                    label: "Magnitude",
+                    samplerate: self.config.sample_rate,
+                    series: &self.spectrum_magnitude,
+                    color: &BLUE,
+                    ..Default::default()
+                },
+                &LegendConfig::default(),
+                plot::db_range(&self.spectrum_magnitude),
+            )
+        })
+    }
+
+    /// Plots the phase over a linear axis from minus to plus Nyquist
+    /// as SVG file.
+    pub fn plot_phase(&self, title: &str, filename: impl AsRef<std::path::Path>) -> Result<()> {
+        plot::with_svg(filename, |root| {
+            plot::render_two_sided(
+                root,
+                title,
+                &self.config.title_font,
+                &Series {
+                    label: "Phase",
+                    samplerate: self.config.sample_rate,
+                    series: &self.spectrum_phase,
+                    color: &RED,
+                    ..Default::default()
+                },
+                &LegendConfig::default(),
+                -180.0..180.0,
+            )
+        })
+    }
+}
+
+/// Runs the complex FFT over the samples and returns the spectrum,
+/// starting at DC.
+fn complex_fft(samples: &[Complex<f32>]) -> Vec<Complex<f32>> {
+    let mut planner = FftPlanner::new();
+    let fft = planner.plan_fft_forward(samples.len());
+
+    let mut spectrum = samples.to_owned();
+    fft.process(&mut spectrum);
+
+    spectrum
+}
@@ -0,0 +1,48 @@
+//! Energy decay relief of impulse responses.
+
+use crate::bode::{stft, MAGNITUDE_FLOOR_DB};
+
+/// Returns the energy decay relief of an impulse response in dB, one frame
+/// of `fft_size / 2 + 1` bins spread evenly from DC to Nyquist every `hop`
+/// samples.
+///
+/// Each value is the energy of the short-time spectra from its frame to
+/// the end of the response, the Schroeder backward integration applied to
+/// each bin separately, so the decay of each band reads as a slope over the
+/// frames. The levels are relative to the highest energy of the first frame
+/// and limited to the magnitude floor of -100dB.
+///
+/// # Panics
+///
+/// Panics if the FFT size is below 2 or exceeds the length of the response,
+/// or if the hop size is zero.
+pub fn edr(ir: &[f32], fft_size: usize, hop: usize) -> Vec<Vec<f32>> {
+    let frames = stft(ir, fft_size, hop);
+
+    // Backward integration of the energy over the frames.
+    let mut energy = vec![vec![0.0f64; fft_size / 2 + 1]; frames.len()];
+    let mut remaining = vec![0.0f64; fft_size / 2 + 1];
+    for (frame, spectrum) in energy.iter_mut().zip(frames.iter()).rev() {
+        for ((sum, value), bin) in remaining.iter_mut().zip(frame.iter_mut()).zip(spectrum) {
+            *sum += bin.norm_sqr() as f64;
+            *value = *sum;
+        }
+    }
+
+    let reference = energy[0].iter().copied().fold(0.0, f64::max);
+    energy
+        .iter()
+        .map(|frame| {
+            frame
+                .iter()
+                .map(|value| {
+                    if reference > 0.0 {
+                        ((10.0 * (value / reference).log10()) as f32).max(MAGNITUDE_FLOOR_DB)
+                    } else {
+                        MAGNITUDE_FLOOR_DB
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
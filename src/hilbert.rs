@@ -0,0 +1,37 @@
+//! Envelope extraction via the Hilbert transform.
+
+use realfft::num_complex::Complex;
+use rustfft::FftPlanner;
+
+use crate::bode::fft;
+
+/// Returns the instantaneous amplitude envelope of a signal as magnitude
+/// of its analytic signal.
+///
+/// The analytic signal is computed offline over the whole signal by
+/// removing the negative frequencies from its spectrum, so unlike an
+/// envelope follower the envelope has no delay or ripple. The signal is
+/// treated as periodic, so the envelope is less accurate near the edges
+/// unless the signal fades in and out.
+pub fn hilbert_envelope(samples: &[f32]) -> Vec<f32> {
+    let length = samples.len();
+    if length < 2 {
+        return samples.iter().map(|sample| sample.abs()).collect();
+    }
+
+    // Double the positive frequencies, keep DC and Nyquist and zero the
+    // negative frequencies.
+    let mut spectrum = fft(samples);
+    for (bin, value) in spectrum.iter_mut().enumerate() {
+        if bin != 0 && !(length.is_multiple_of(2) && bin == length / 2) {
+            *value *= 2.0;
+        }
+    }
+    spectrum.resize(length, Complex::new(0.0, 0.0));
+
+    let mut planner = FftPlanner::new();
+    planner.plan_fft_inverse(length).process(&mut spectrum);
+
+    let scale = 1.0 / length as f32;
+    spectrum.iter().map(|value| value.norm() * scale).collect()
+}
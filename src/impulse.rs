@@ -0,0 +1,93 @@
+//! Impulse generator.
+
+/// Generator of a single impulse followed by zeros.
+///
+/// The impulse is placed at a sample position counted from the start or
+/// the last reset, so the delay of a processor can be measured with
+/// sample accuracy.
+#[derive(Debug, Clone)]
+pub struct ImpulseGenerator {
+    /// Position of the impulse in samples.
+    position: usize,
+
+    /// Amplitude of the impulse.
+    amplitude: f32,
+
+    /// Index of the next sample.
+    index: usize,
+}
+
+impl Default for ImpulseGenerator {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl ImpulseGenerator {
+    /// Returns a new instance with the impulse at a position in samples
+    /// and an amplitude of 1.0.
+    pub fn new(position: usize) -> Self {
+        Self {
+            position,
+            amplitude: 1.0,
+            index: 0,
+        }
+    }
+
+    /// Sets the position of the impulse in samples.
+    pub fn set_position(&mut self, position: usize) {
+        self.position = position;
+    }
+
+    /// Returns the position of the impulse in samples.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Sets the amplitude of the impulse.
+    pub fn set_amplitude(&mut self, amplitude: f32) {
+        self.amplitude = amplitude;
+    }
+
+    /// Returns the amplitude of the impulse.
+    pub fn amplitude(&self) -> f32 {
+        self.amplitude
+    }
+
+    /// Restarts the generator, so the impulse is emitted again.
+    pub fn reset(&mut self) {
+        self.index = 0;
+    }
+
+    /// Returns the next sample.
+    pub fn next_sample(&mut self) -> f32 {
+        let sample = if self.index == self.position {
+            self.amplitude
+        } else {
+            0.0
+        };
+        self.index = self.index.saturating_add(1);
+
+        sample
+    }
+
+    /// Fills a block with samples.
+    pub fn process(&mut self, buffer: &mut [f32]) {
+        for sample in buffer.iter_mut() {
+            *sample = self.next_sample();
+        }
+    }
+
+    /// Returns a `Vec` of a length in samples containing the impulse,
+    /// starting from the beginning regardless of the current state.
+    pub fn generate(&self, length: usize) -> Vec<f32> {
+        let mut generator = Self {
+            index: 0,
+            ..self.clone()
+        };
+        let mut samples = vec![0.0; length];
+        generator.process(&mut samples);
+
+        samples
+    }
+}
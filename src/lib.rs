@@ -1,8 +1,43 @@
 #![doc = include_str!("../README.md")]
 
 mod bode;
-mod plot;
+mod bode64;
+mod bode_complex;
+mod edr;
+mod hilbert;
+mod impulse;
+mod mls;
+mod noise;
+pub mod plot;
+mod resampler;
+mod response;
+mod session;
+pub mod smoothing;
+mod streaming;
 mod sweep_generator;
+mod vector_backend;
+pub mod wav_reader;
 pub mod wav_writer;
 
-pub use bode::{FftAnalyzer, FftAnalyzerConfig};
+pub use plotters;
+pub use realfft::num_complex::Complex;
+
+#[cfg(feature = "rayon")]
+pub use bode::SendProcessor;
+pub use bode::{
+    AnalyzerError, DelayCompensation, FftAnalyzer, FftAnalyzerConfig, FftNormalization,
+    Interpolation, MagnitudeScale, NotchInfo, Processor, TestSignal,
+};
+pub use bode64::FftAnalyzer64;
+pub use bode_complex::ComplexFftAnalyzer;
+pub use edr::edr;
+pub use hilbert::hilbert_envelope;
+pub use impulse::ImpulseGenerator;
+pub use mls::{mls, mls_deconvolve, MLS_MAX_ORDER, MLS_MIN_ORDER};
+pub use noise::NoiseGenerator;
+pub use plot::{AxisRange, Colormap, LegendConfig, LineStyle, Plot, PlotBuilder, Series};
+pub use resampler::{resample, Decimator, Interpolator};
+pub use response::{FrequencyResponse, MaskViolation};
+pub use session::{AnalyzerSession, SpectrumResult};
+pub use streaming::StreamingAnalyzer;
+pub use sweep_generator::{SweepDirection, SweepError, SweepGenerator, SweepMode};
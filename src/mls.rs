@@ -0,0 +1,104 @@
+//! Maximum-length sequence (MLS) generation and deconvolution.
+
+use realfft::{num_complex::Complex, RealFftPlanner};
+
+/// Feedback taps of the maximal-length LFSR for each order, starting at order 2.
+const TAPS: [&[u32]; 19] = [
+    &[2, 1],
+    &[3, 2],
+    &[4, 3],
+    &[5, 3],
+    &[6, 5],
+    &[7, 6],
+    &[8, 6, 5, 4],
+    &[9, 5],
+    &[10, 7],
+    &[11, 9],
+    &[12, 11, 10, 4],
+    &[13, 12, 11, 8],
+    &[14, 13, 12, 2],
+    &[15, 14],
+    &[16, 15, 13, 4],
+    &[17, 14],
+    &[18, 11],
+    &[19, 18, 17, 14],
+    &[20, 17],
+];
+
+/// Lowest supported order.
+pub const MLS_MIN_ORDER: u32 = 2;
+
+/// Highest supported order.
+pub const MLS_MAX_ORDER: u32 = 20;
+
+/// Returns a maximum-length sequence of `2^order - 1` samples with values of ±1.
+///
+/// The sequence is generated by a Fibonacci LFSR. Supported orders are
+/// in the range of [`MLS_MIN_ORDER`] to [`MLS_MAX_ORDER`].
+pub fn mls(order: u32) -> Vec<f32> {
+    assert!(
+        (MLS_MIN_ORDER..=MLS_MAX_ORDER).contains(&order),
+        "MLS order {order} is not supported"
+    );
+
+    let mask = TAPS[(order - MLS_MIN_ORDER) as usize]
+        .iter()
+        .fold(0u32, |mask, tap| mask | 1 << (order - tap));
+    let length = (1usize << order) - 1;
+    let mut state = (1u32 << order) - 1;
+
+    (0..length)
+        .map(|_| {
+            let sample = if state & 1 == 0 { 1.0 } else { -1.0 };
+            let feedback = (state & mask).count_ones() & 1;
+            state = (state >> 1) | (feedback << (order - 1));
+            sample
+        })
+        .collect()
+}
+
+/// Recovers the impulse response from the response to one period of an MLS.
+///
+/// Computes the circular cross-correlation of `response` and `sequence`
+/// normalized by `sequence.len() + 1`. The response must be captured in
+/// steady state, i.e. after the system has been excited by at least one
+/// previous period of the sequence, and have the same length as the sequence.
+/// The result is the periodic impulse response with a length of one period.
+pub fn mls_deconvolve(response: &[f32], sequence: &[f32]) -> Vec<f32> {
+    assert_eq!(
+        response.len(),
+        sequence.len(),
+        "Response and sequence lengths differ"
+    );
+
+    let length = sequence.len();
+    let mut planner = RealFftPlanner::<f32>::new();
+    let r2c = planner.plan_fft_forward(length);
+    let c2r = planner.plan_fft_inverse(length);
+
+    let mut response_spectrum = r2c.make_output_vec();
+    r2c.process(&mut response.to_owned(), &mut response_spectrum)
+        .unwrap();
+    let mut sequence_spectrum = r2c.make_output_vec();
+    r2c.process(&mut sequence.to_owned(), &mut sequence_spectrum)
+        .unwrap();
+
+    let mut correlation: Vec<Complex<f32>> = response_spectrum
+        .iter()
+        .zip(sequence_spectrum.iter())
+        .map(|(r, s)| r * s.conj())
+        .collect();
+
+    // The inverse transform requires purely real DC and Nyquist bins.
+    correlation[0].im = 0.0;
+    if length.is_multiple_of(2) {
+        correlation[length / 2].im = 0.0;
+    }
+
+    let mut impulse_response = c2r.make_output_vec();
+    c2r.process(&mut correlation, &mut impulse_response)
+        .unwrap();
+
+    let scale = 1.0 / (length as f32 * (length as f32 + 1.0));
+    impulse_response.iter().map(|v| v * scale).collect()
+}
@@ -0,0 +1,64 @@
+//! White noise generator.
+
+/// Seed used if none or zero is given.
+const DEFAULT_SEED: u64 = 0x2545_f491_4f6c_dd1d;
+
+/// Generator of uniformly distributed white noise.
+///
+/// The samples are produced by a xorshift generator, so the sequence
+/// is fully determined by the seed and reproducible across runs.
+#[derive(Debug, Clone)]
+pub struct NoiseGenerator {
+    /// Generator state, never zero.
+    state: u64,
+
+    /// Gain.
+    gain: f32,
+}
+
+impl Default for NoiseGenerator {
+    fn default() -> Self {
+        Self::new(DEFAULT_SEED)
+    }
+}
+
+impl NoiseGenerator {
+    /// Returns a new instance with a seed.
+    pub fn new(seed: u64) -> Self {
+        let mut generator = Self {
+            state: DEFAULT_SEED,
+            gain: 1.0,
+        };
+        generator.set_seed(seed);
+
+        generator
+    }
+
+    /// Restarts the sequence from a seed. A seed of zero is replaced by a default seed.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.state = if seed == 0 { DEFAULT_SEED } else { seed };
+    }
+
+    /// Sets the gain, the samples are within ±gain.
+    pub fn set_gain(&mut self, gain: f32) {
+        self.gain = gain;
+    }
+
+    /// Returns the next sample.
+    pub fn next_sample(&mut self) -> f32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+
+        // Use the upper 24 bits, which fit into the mantissa.
+        let value = (self.state >> 40) as f32 / (1u32 << 24) as f32;
+        (value * 2.0 - 1.0) * self.gain
+    }
+
+    /// Fills a block with samples.
+    pub fn process(&mut self, buffer: &mut [f32]) {
+        for sample in buffer.iter_mut() {
+            *sample = self.next_sample();
+        }
+    }
+}
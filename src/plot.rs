@@ -1,16 +1,83 @@
 //! Plot functions.
 //!
 //! Taken from <https://github.com/SolarLiner/valib/src/util.rs>.
-
-#![allow(unused)]
+//!
+//! The plots of the analyzers are built from these types, which can also be
+//! used to plot other data in the same style:
+//!
+//! ```rust no_run
+//! use dsp_analyze::plot::{AxisRange, Plot, Series};
+//! use dsp_analyze::plotters::style::BLUE;
+//!
+//! let samples: Vec<f32> = (0..480).map(|i| (i as f32 * 0.1).sin()).collect();
+//!
+//! Plot::builder()
+//!     .title("Sine")
+//!     .add_series(Series {
+//!         label: "Signal",
+//!         samplerate: 48000.0,
+//!         series: &samples,
+//!         color: &BLUE,
+//!         ..Default::default()
+//!     })
+//!     .y_range(AxisRange::ManualLin(-1.0..1.0))
+//!     .build()
+//!     .create_svg("out/sine.svg")
+//!     .unwrap();
+//! ```
 
 use std::{ops::Range, path::Path};
 
 use plotters::coord::{self, ranged1d::ValueFormatter};
+use plotters::element::{DashedPathElement, DottedPathElement, IntoDynElement};
 use plotters::{chart::SeriesAnno, prelude::*};
 
+use crate::bode::AnalyzerError;
+use crate::vector_backend::{VectorBackend, VectorFormat};
+
 const PLOT_SIZE: (u32, u32) = (600, 400);
 
+/// Font family of the title used by default.
+pub const TITLE_FONT_FAMILY: &str = "sans-serif";
+
+/// Font size of the title used by default.
+pub const TITLE_FONT_SIZE: u32 = 40;
+
+/// Width of the left and height of the bottom label area in pixels used by default.
+pub const LABEL_AREA_SIZE: u32 = 40;
+
+/// Color of the shading outside the valid band of a plot.
+const INVALID_BAND_COLOR: RGBColor = RGBColor(160, 160, 160);
+
+/// Lowest level in dB taken into account for automatic dB ranges.
+const DB_FLOOR: f32 = -120.0;
+
+/// Step in dB to which automatic dB ranges are rounded.
+const DB_STEP: f32 = 6.0;
+
+/// Ratio of the value by which the range of a constant series is padded.
+const Y_PAD_RATIO: f32 = 0.1;
+
+/// Lowest frequency in Hz shown by waterfall plots.
+const WATERFALL_MIN_FREQ: f32 = 20.0;
+
+/// Factor by which the frequencies of the last slice of a waterfall are
+/// shifted to the right.
+const WATERFALL_SHIFT: f32 = 2.0;
+
+/// Ratio of the level range by which the last slice of a waterfall is
+/// shifted up.
+const WATERFALL_RISE: f32 = 0.2;
+
+/// Length of dashes and gaps of dashed lines in pixels per pixel of width.
+const DASH_SIZE: u32 = 4;
+
+/// Distance of the dots of dotted lines in pixels per pixel of width.
+const DOT_SPACING: u32 = 4;
+
+/// Length of the line samples in the legend in pixels.
+const LEGEND_LENGTH: i32 = 20;
+
 fn assert_ok(res: Result<(), impl std::fmt::Display>) {
     match res {
         Ok(()) => {}
@@ -18,14 +85,59 @@ fn assert_ok(res: Result<(), impl std::fmt::Display>) {
     }
 }
 
+/// Style of the line of a [`Series`].
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum LineStyle {
+    /// Continuous line.
+    #[default]
+    Solid,
+
+    /// Line of dashes.
+    Dashed,
+
+    /// Line of dots.
+    Dotted,
+}
+
+/// Data series drawn as a line into a [`Plot`].
+///
+/// The default is an empty black solid line of one pixel width at 48kHz,
+/// so that only the relevant fields need to be set.
 pub struct Series<'a> {
+    /// Label shown in the legend.
     pub label: &'a str,
+
+    /// Sample rate in Hz.
     pub samplerate: f32,
+
+    /// Values, either samples over time or spectrum bins from DC to Nyquist.
     pub series: &'a [f32],
+
+    /// Color of the line.
     pub color: &'a RGBColor,
+
+    /// Width of the line in pixels.
+    pub width: u32,
+
+    /// Style of the line.
+    pub style: LineStyle,
+}
+
+impl Default for Series<'_> {
+    fn default() -> Self {
+        Self {
+            label: "",
+            samplerate: 48000.0,
+            series: &[],
+            color: &BLACK,
+            width: 1,
+            style: LineStyle::default(),
+        }
+    }
 }
 
 impl<'a> Series<'a> {
+    /// Checks that the series has a positive sample rate and finite data.
     pub fn validate(&self) -> Result<(), String> {
         if self.samplerate <= 0. {
             return Err(format!("Series: {:?}: Samplerate is negative", self.label));
@@ -33,11 +145,16 @@ impl<'a> Series<'a> {
         if self.series.is_empty() {
             return Err(format!("Series: {:?}: No data", self.label));
         }
+        if !self.series.iter().any(|v| v.is_finite()) {
+            return Err(format!("Series: {:?}: No finite data", self.label));
+        }
 
         Ok(())
     }
 
-    pub fn timescale(&self, bode: bool) -> Range<f32> {
+    /// Returns the range of the X axis in seconds, or in Hz up to Nyquist
+    /// for a bode plot.
+    pub(crate) fn timescale(&self, bode: bool) -> Range<f32> {
         assert_ok(self.validate());
         if bode {
             0.0..self.samplerate / 2.0
@@ -47,69 +164,276 @@ impl<'a> Series<'a> {
         }
     }
 
+    /// Returns the range of the finite values.
+    ///
+    /// A constant series is padded by 10% of its value,
+    /// or by 1.0 if it is zero, so that the range is never empty.
     pub fn y_range(&self) -> Range<f32> {
         assert_ok(self.validate());
-        let min = self
-            .series
-            .iter()
-            .copied()
-            .min_by(f32::total_cmp)
-            .unwrap()
-            .clamp(f32::MIN, f32::MAX);
-        let max = self
+        let (min, max) = self
             .series
             .iter()
             .copied()
-            .max_by(f32::total_cmp)
-            .unwrap()
-            .clamp(f32::MIN, f32::MAX);
-        min..max
+            .filter(|v| v.is_finite())
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), v| {
+                (min.min(v), max.max(v))
+            });
+
+        if min < max {
+            return min..max;
+        }
+
+        let pad = if min == 0.0 {
+            1.0
+        } else {
+            min.abs() * Y_PAD_RATIO
+        };
+        min - pad..max + pad
     }
 
-    fn as_series<DB: DrawingBackend>(&self, bode: bool) -> LineSeries<DB, (f32, f32)> {
-        LineSeries::new(
-            self.series.iter().copied().enumerate().map(|(i, y)| {
+    /// Returns the range of the values in dB, ignoring values below -120 dB
+    /// and non-finite values. The bounds are rounded outwards to 6 dB.
+    pub fn db_range(&self) -> Range<f32> {
+        assert_ok(self.validate());
+        db_range(self.series)
+    }
+
+    /// Returns the range of the positive values, ignoring values below the
+    /// linear equivalent of -120 dB. The bounds are rounded outwards to decades.
+    pub fn log_range(&self) -> Range<f32> {
+        assert_ok(self.validate());
+        log_range(self.series)
+    }
+
+    /// Returns the points of the series within the X range. Non-positive
+    /// values are skipped for a logarithmic Y axis, as they cannot be displayed.
+    fn points(
+        &self,
+        bode: bool,
+        log_y: bool,
+        x_range: Range<f32>,
+    ) -> impl Iterator<Item = (f32, f32)> + Clone + '_ {
+        // Spectrum bins are spread evenly from DC to Nyquist.
+        let bin_width = self.samplerate / 2.0 / (self.series.len() - 1).max(1) as f32;
+        self.series
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(move |(_, y)| !log_y || *y > 0.0)
+            .map(move |(i, y)| {
                 let x = if bode {
-                    i as f32
+                    i as f32 * bin_width
                 } else {
                     i as f32 / self.samplerate
                 };
                 (x, y)
-            }),
-            self.color,
-        )
+            })
+            .filter(move |(x, _)| x_range.contains(x) || *x == x_range.end)
+    }
+
+    /// Returns the style of the line.
+    fn shape_style(&self) -> ShapeStyle {
+        ShapeStyle::from(self.color).stroke_width(self.width)
     }
 
-    fn apply_legend(&self, ann: &mut SeriesAnno<impl DrawingBackend>) {
-        let color = *self.color;
+    /// Draws the line in its style into a chart.
+    fn draw<'b, 'c, DB, X, Y>(
+        &self,
+        ctx: &'b mut ChartContext<'c, DB, Cartesian2d<X, Y>>,
+        bode: bool,
+        log_y: bool,
+        x_range: Range<f32>,
+    ) -> &'b mut SeriesAnno<'c, DB>
+    where
+        DB: DrawingBackend,
+        X: Ranged<ValueType = f32>,
+        Y: Ranged<ValueType = f32>,
+    {
+        self.draw_points(ctx, self.points(bode, log_y, x_range))
+    }
+
+    /// Draws the line through the given points in its style into a chart.
+    fn draw_points<'b, 'c, DB, X, Y>(
+        &self,
+        ctx: &'b mut ChartContext<'c, DB, Cartesian2d<X, Y>>,
+        points: impl Iterator<Item = (f32, f32)> + Clone,
+    ) -> &'b mut SeriesAnno<'c, DB>
+    where
+        DB: DrawingBackend,
+        X: Ranged<ValueType = f32>,
+        Y: Ranged<ValueType = f32>,
+    {
+        let style = self.shape_style();
+        let (dash, dot) = (DASH_SIZE * self.width, DOT_SPACING * self.width);
+        let radius = self.width.div_ceil(2);
+        match self.style {
+            LineStyle::Solid => ctx.draw_series(LineSeries::new(points, style)),
+            LineStyle::Dashed => ctx.draw_series(DashedLineSeries::new(points, dash, dash, style)),
+            LineStyle::Dotted => ctx.draw_series(DottedLineSeries::new(points, 0, dot, move |c| {
+                Circle::new(c, radius, style.filled())
+            })),
+        }
+        .unwrap()
+    }
+
+    /// Sets the label and a sample of the line in the legend.
+    fn apply_legend<'c, DB: DrawingBackend + 'c>(&self, ann: &mut SeriesAnno<'c, DB>) {
+        let line_style = self.style;
+        let style = self.shape_style();
+        let (dash, dot) = (DASH_SIZE * self.width, DOT_SPACING * self.width);
+        let radius = self.width.div_ceil(2);
         ann.label(self.label);
-        ann.legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], color));
+        ann.legend(move |(x, y)| {
+            let points = [(x, y), (x + LEGEND_LENGTH, y)];
+            match line_style {
+                LineStyle::Solid => PathElement::new(points, style).into_dyn(),
+                LineStyle::Dashed => DashedPathElement::new(points, dash, dash, style).into_dyn(),
+                LineStyle::Dotted => DottedPathElement::new(points, 0, dot, move |c| {
+                    Circle::new(c, radius, style.filled())
+                })
+                .into_dyn(),
+            }
+        });
     }
 }
 
+/// Range of the Y axis of a [`Plot`].
 pub enum AxisRange {
+    /// Linear axis spanning the values of all series.
     AutoLin,
+
+    /// Linear axis for values in dB, see [`Series::db_range`].
+    AutoDb,
+
+    /// Logarithmic axis for linear values, see [`Series::log_range`].
+    /// Only applied to bode plots.
     AutoLog,
+
+    /// Linear axis with a fixed range.
     ManualLin(Range<f32>),
+
+    /// Logarithmic axis with a fixed, positive range.
+    /// Only applied to bode plots.
     ManualLog(Range<f32>),
 }
 
+/// Visibility and position of the legend of a [`Plot`].
+///
+/// The default shows the legend at the middle of the right side.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LegendConfig {
+    /// If the legend is drawn.
+    pub show: bool,
+
+    /// Position of the legend within the chart.
+    pub position: SeriesLabelPosition,
+}
+
+impl Default for LegendConfig {
+    fn default() -> Self {
+        Self {
+            show: true,
+            position: SeriesLabelPosition::MiddleRight,
+        }
+    }
+}
+
+/// Plot of one or more series.
 pub struct Plot<'a> {
+    /// Title shown above the chart.
     pub title: &'a str,
+
+    /// If the series are spectra plotted over a logarithmic frequency axis
+    /// instead of signals plotted over time.
     pub bode: bool,
-    pub series: &'a [Series<'a>],
+
+    /// Series to draw.
+    pub series: Vec<Series<'a>>,
+
+    /// Range of the Y axis.
     pub y_range: AxisRange,
+
+    /// Range of the X axis in seconds, or in Hz for a bode plot. Points
+    /// outside the range are not drawn. `None` to span all series.
+    pub x_range: Option<Range<f32>>,
+
+    /// Font family and size of the title.
+    pub title_font: (String, u32),
+
+    /// Visibility and position of the legend.
+    pub legend: LegendConfig,
+
+    /// Range of the X axis in which the data is valid, e.g. the band excited
+    /// by the test signal. The regions outside are shaded. `None` for no shading.
+    pub valid_band: Option<Range<f32>>,
+
+    /// Indices of two series, the area between them is filled with the
+    /// color of the first one, e.g. for max-hold and min-hold curves.
+    /// `None` for no fill.
+    pub fill_between: Option<(usize, usize)>,
+
+    /// Width of the label area left of the chart in pixels, holding the
+    /// Y axis tick labels.
+    pub left_margin: u32,
+
+    /// Height of the label area below the chart in pixels, holding the
+    /// X axis tick labels.
+    pub bottom_margin: u32,
 }
 
 impl<'a> Plot<'a> {
+    /// Returns a builder for a plot without title and series, with a
+    /// linear time axis and an automatic linear Y axis.
+    pub fn builder() -> PlotBuilder<'a> {
+        PlotBuilder::default()
+    }
+
+    /// Checks that the plot has valid series and non-empty axis ranges.
     pub fn validate(&self) -> Result<(), String> {
         if self.series.is_empty() {
             return Err(format!("Plot {:?}: no series", self.title));
         }
         self.series.iter().try_for_each(|s| s.validate())?;
+        if let AxisRange::ManualLog(range) = &self.y_range {
+            if range.start <= 0.0 || range.end <= range.start {
+                return Err(format!(
+                    "Plot {:?}: log range {range:?} is not positive",
+                    self.title
+                ));
+            }
+        }
+        if let AxisRange::ManualLin(range) = &self.y_range {
+            if range.is_empty() {
+                return Err(format!(
+                    "Plot {:?}: linear range {range:?} is empty",
+                    self.title
+                ));
+            }
+        }
+        if let Some(range) = &self.x_range {
+            if range.is_empty() || (self.bode && range.start <= 0.0) {
+                return Err(format!(
+                    "Plot {:?}: X range {range:?} is empty or not positive",
+                    self.title
+                ));
+            }
+        }
+        if let Some((first, second)) = self.fill_between {
+            if first.max(second) >= self.series.len() {
+                return Err(format!(
+                    "Plot {:?}: fill between series {first} and {second} out of range",
+                    self.title
+                ));
+            }
+        }
         Ok(())
     }
 
+    /// Draws the plot into a drawing area.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the plot is invalid, see [`Plot::validate`].
     pub fn render_into(&self, output: &DrawingArea<impl DrawingBackend, coord::Shift>) {
         use plotters::prelude::*;
         assert_ok(self.validate());
@@ -124,14 +448,14 @@ impl<'a> Plot<'a> {
                 start..end
             })
             .unwrap();
-        let timescale = if self.bode {
-            timescale.start * 2.0..timescale.end * 2.0
-        } else {
-            timescale
+        let timescale = match &self.x_range {
+            Some(range) => range.clone(),
+            None if self.bode => timescale.start * 2.0..timescale.end * 2.0,
+            None => timescale,
         };
 
         let yrange = match &self.y_range {
-            AxisRange::AutoLin | AxisRange::AutoLog => self
+            AxisRange::AutoLin => self
                 .series
                 .iter()
                 .map(|s| s.y_range())
@@ -141,16 +465,36 @@ impl<'a> Plot<'a> {
                     start..end
                 })
                 .unwrap(),
+            AxisRange::AutoLog => self
+                .series
+                .iter()
+                .map(|s| s.log_range())
+                .reduce(|l, r| {
+                    let start = l.start.min(r.start);
+                    let end = l.end.max(r.end);
+                    start..end
+                })
+                .unwrap(),
+            AxisRange::AutoDb => self
+                .series
+                .iter()
+                .map(|s| s.db_range())
+                .reduce(|l, r| {
+                    let start = l.start.min(r.start);
+                    let end = l.end.max(r.end);
+                    start..end
+                })
+                .unwrap(),
             AxisRange::ManualLin(range) | AxisRange::ManualLog(range) => range.to_owned(),
         };
 
         let mut ctx = ChartBuilder::on(output);
-        ctx.set_label_area_size(LabelAreaPosition::Left, 40)
-            .set_label_area_size(LabelAreaPosition::Bottom, 40)
-            .caption(self.title, ("sans-serif", 40));
+        ctx.set_label_area_size(LabelAreaPosition::Left, self.left_margin)
+            .set_label_area_size(LabelAreaPosition::Bottom, self.bottom_margin)
+            .caption(self.title, (self.title_font.0.as_str(), self.title_font.1));
         if self.bode {
             match &self.y_range {
-                AxisRange::AutoLin | AxisRange::ManualLin(_) => {
+                AxisRange::AutoLin | AxisRange::AutoDb | AxisRange::ManualLin(_) => {
                     let ctx = ctx
                         .build_cartesian_2d(timescale.log_scale(), yrange)
                         .unwrap();
@@ -179,22 +523,533 @@ impl<'a> Plot<'a> {
     ) {
         ctx.configure_mesh().draw().unwrap();
 
-        for series in self.series {
-            let ann = ctx.draw_series(series.as_series(self.bode)).unwrap();
+        if let Some(band) = &self.valid_band {
+            let (x_range, y_range) = (ctx.x_range(), ctx.y_range());
+            let style = INVALID_BAND_COLOR.mix(0.3).filled();
+            let regions = [
+                x_range.start..band.start.min(x_range.end),
+                band.end.max(x_range.start)..x_range.end,
+            ];
+            ctx.draw_series(
+                regions
+                    .iter()
+                    .filter(|region| !region.is_empty())
+                    .map(|region| {
+                        Rectangle::new(
+                            [(region.start, y_range.start), (region.end, y_range.end)],
+                            style,
+                        )
+                    }),
+            )
+            .unwrap();
+        }
+
+        // Only bode plots use a logarithmic Y axis.
+        let log_y =
+            self.bode && matches!(self.y_range, AxisRange::AutoLog | AxisRange::ManualLog(_));
+        if let Some((first, second)) = self.fill_between {
+            let (first, second) = (&self.series[first], &self.series[second]);
+            // Outline along the first series and back along the second one.
+            let mut outline: Vec<_> = first
+                .points(self.bode, log_y, ctx.x_range())
+                .filter(|(_, y)| y.is_finite())
+                .collect();
+            let mut back: Vec<_> = second
+                .points(self.bode, log_y, ctx.x_range())
+                .filter(|(_, y)| y.is_finite())
+                .collect();
+            back.reverse();
+            outline.append(&mut back);
+            ctx.draw_series(std::iter::once(Polygon::new(
+                outline,
+                first.color.mix(0.3).filled(),
+            )))
+            .unwrap();
+        }
+
+        for series in &self.series {
+            let x_range = ctx.x_range();
+            let ann = series.draw(&mut ctx, self.bode, log_y, x_range);
             series.apply_legend(ann);
         }
 
+        if self.legend.show {
+            ctx.configure_series_labels()
+                .position(self.legend.position.clone())
+                .background_style(WHITE.mix(0.8))
+                .draw()
+                .unwrap();
+        }
+    }
+
+    /// Draws the plot into an SVG file, creating its directory if needed.
+    ///
+    /// Returns an error if the directory or the file cannot be written.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the plot is invalid, see [`Plot::validate`].
+    pub fn create_svg(&self, filename: impl AsRef<Path>) -> Result<(), AnalyzerError> {
+        with_svg(filename, |root| self.render_into(root))
+    }
+
+    /// Draws the plot into a PDF file, creating its directory if needed.
+    ///
+    /// Text is set in the standard PDF fonts, which are not embedded.
+    ///
+    /// Returns an error if the directory or the file cannot be written.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the plot is invalid, see [`Plot::validate`].
+    pub fn create_pdf(&self, filename: impl AsRef<Path>) -> Result<(), AnalyzerError> {
+        with_vector(filename, VectorFormat::Pdf, |root| self.render_into(root))
+    }
+
+    /// Draws the plot into an EPS file, creating its directory if needed.
+    ///
+    /// Text is set in the standard PostScript fonts, which are not embedded.
+    ///
+    /// Returns an error if the directory or the file cannot be written.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the plot is invalid, see [`Plot::validate`].
+    pub fn create_eps(&self, filename: impl AsRef<Path>) -> Result<(), AnalyzerError> {
+        with_vector(filename, VectorFormat::Eps, |root| self.render_into(root))
+    }
+}
+
+/// Colormap mapping levels to colors, e.g. for the cells of a spectrogram.
+///
+/// Viridis and Magma are perceptually uniform and increase monotonically
+/// in lightness, like Grayscale.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Colormap {
+    /// Dark blue over green to yellow.
+    #[default]
+    Viridis,
+
+    /// Black over purple and orange to light yellow.
+    Magma,
+
+    /// Black to white.
+    Grayscale,
+}
+
+/// Colors of Viridis at evenly spaced positions.
+const VIRIDIS: [(u8, u8, u8); 9] = [
+    (68, 1, 84),
+    (71, 44, 122),
+    (59, 81, 139),
+    (44, 113, 142),
+    (33, 144, 141),
+    (39, 173, 129),
+    (92, 200, 99),
+    (170, 220, 50),
+    (253, 231, 37),
+];
+
+/// Colors of Magma at evenly spaced positions.
+const MAGMA: [(u8, u8, u8); 9] = [
+    (0, 0, 4),
+    (28, 16, 68),
+    (79, 18, 123),
+    (129, 37, 129),
+    (181, 54, 122),
+    (229, 80, 100),
+    (251, 135, 97),
+    (254, 194, 135),
+    (252, 253, 191),
+];
+
+impl Colormap {
+    /// Returns the color of a value from 0.0 to 1.0, values outside are clamped.
+    pub fn color(&self, value: f32) -> RGBColor {
+        let value = if value.is_nan() {
+            0.0
+        } else {
+            value.clamp(0.0, 1.0)
+        };
+        let table: &[(u8, u8, u8)] = match self {
+            Self::Viridis => &VIRIDIS,
+            Self::Magma => &MAGMA,
+            Self::Grayscale => &[(0, 0, 0), (255, 255, 255)],
+        };
+
+        let position = value * (table.len() - 1) as f32;
+        let index = (position.floor() as usize).min(table.len() - 2);
+        let t = position - index as f32;
+        let (c0, c1) = (table[index], table[index + 1]);
+        let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+
+        RGBColor(mix(c0.0, c1.0), mix(c0.1, c1.1), mix(c0.2, c1.2))
+    }
+
+    /// Returns the color of a level in dB within a range, levels outside
+    /// the range are clamped.
+    pub fn color_db(&self, level_db: f32, range: &Range<f32>) -> RGBColor {
+        self.color((level_db - range.start) / (range.end - range.start))
+    }
+}
+
+/// Renders spectra as pseudo-3D waterfall over a logarithmic frequency axis.
+///
+/// The first slice is in front, each later one is shifted to the right and
+/// up and hidden by the slices before it. Levels are shown down to `range_db`
+/// below the peak of all slices.
+pub(crate) fn render_waterfall<DB: DrawingBackend>(
+    area: &DrawingArea<DB, coord::Shift>,
+    title: &str,
+    title_font: &(String, u32),
+    slices: &[Vec<f32>],
+    sample_rate: f32,
+    colormap: Colormap,
+    range_db: f32,
+) {
+    assert!(!slices.is_empty(), "Waterfall {title:?}: no slices");
+
+    let bins = slices[0].len();
+    let bin_width = sample_rate / 2.0 / (bins - 1).max(1) as f32;
+    let peak = slices
+        .iter()
+        .flatten()
+        .fold(f32::NEG_INFINITY, |peak, level| peak.max(*level));
+    let floor = peak - range_db;
+    let shift = WATERFALL_SHIFT.powf(1.0 / slices.len() as f32);
+    let rise = range_db * WATERFALL_RISE / slices.len() as f32;
+
+    let mut ctx = ChartBuilder::on(area);
+    ctx.set_label_area_size(LabelAreaPosition::Left, LABEL_AREA_SIZE)
+        .set_label_area_size(LabelAreaPosition::Bottom, LABEL_AREA_SIZE)
+        .caption(title, (title_font.0.as_str(), title_font.1));
+    let mut ctx = ctx
+        .build_cartesian_2d(
+            (WATERFALL_MIN_FREQ..sample_rate / 2.0 * WATERFALL_SHIFT).log_scale(),
+            floor..peak + range_db * WATERFALL_RISE,
+        )
+        .unwrap();
+    ctx.configure_mesh().disable_mesh().draw().unwrap();
+
+    for (index, levels) in slices.iter().enumerate().rev() {
+        let scale = shift.powi(index as i32);
+        let offset = index as f32 * rise;
+        let points: Vec<(f32, f32)> = levels
+            .iter()
+            .enumerate()
+            .map(|(bin, level)| (bin as f32 * bin_width, level))
+            .filter(|(freq, _)| *freq >= WATERFALL_MIN_FREQ)
+            .map(|(freq, level)| (freq * scale, level.max(floor) + offset))
+            .collect();
+        let (Some(first), Some(last)) = (points.first(), points.last()) else {
+            continue;
+        };
+
+        let mut outline = points.clone();
+        outline.push((last.0, floor + offset));
+        outline.push((first.0, floor + offset));
+        ctx.draw_series(std::iter::once(Polygon::new(outline, WHITE.filled())))
+            .unwrap();
+
+        let color = colormap.color(0.8 * index as f32 / slices.len() as f32);
+        ctx.draw_series(std::iter::once(PathElement::new(points, color)))
+            .unwrap();
+    }
+}
+
+/// Renders a spectrogram as heatmap over time and a linear frequency axis.
+///
+/// Each frame holds the levels in dB of bins spread evenly from DC to
+/// Nyquist and starts `hop` samples after the previous one.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn render_spectrogram<DB: DrawingBackend>(
+    area: &DrawingArea<DB, coord::Shift>,
+    title: &str,
+    title_font: &(String, u32),
+    frames: &[Vec<f32>],
+    sample_rate: f32,
+    hop: usize,
+    colormap: Colormap,
+    range: &Range<f32>,
+) {
+    assert!(!frames.is_empty(), "Spectrogram {title:?}: no frames");
+    assert!(
+        !range.is_empty(),
+        "Spectrogram {title:?}: range {range:?} is empty"
+    );
+
+    let frame_time = hop as f32 / sample_rate;
+    let bins = frames[0].len();
+    let bin_width = sample_rate / 2.0 / (bins - 1).max(1) as f32;
+
+    let mut ctx = ChartBuilder::on(area);
+    ctx.set_label_area_size(LabelAreaPosition::Left, LABEL_AREA_SIZE)
+        .set_label_area_size(LabelAreaPosition::Bottom, LABEL_AREA_SIZE)
+        .caption(title, (title_font.0.as_str(), title_font.1));
+    let mut ctx = ctx
+        .build_cartesian_2d(
+            0.0..frames.len() as f32 * frame_time,
+            0.0..sample_rate / 2.0,
+        )
+        .unwrap();
+    ctx.configure_mesh().disable_mesh().draw().unwrap();
+
+    ctx.draw_series(frames.iter().enumerate().flat_map(|(frame, levels)| {
+        levels.iter().enumerate().map(move |(bin, level)| {
+            let (t0, f0) = (frame as f32 * frame_time, (bin as f32 - 0.5) * bin_width);
+            Rectangle::new(
+                [(t0, f0.max(0.0)), (t0 + frame_time, f0 + bin_width)],
+                colormap.color_db(*level, range).filled(),
+            )
+        })
+    }))
+    .unwrap();
+}
+
+/// Renders a two-sided spectrum over a linear frequency axis from minus
+/// to plus Nyquist, the values of the series spread evenly from the lowest
+/// frequency.
+pub(crate) fn render_two_sided<DB: DrawingBackend>(
+    area: &DrawingArea<DB, coord::Shift>,
+    title: &str,
+    title_font: &(String, u32),
+    series: &Series,
+    legend: &LegendConfig,
+    y_range: Range<f32>,
+) {
+    assert_ok(series.validate());
+    assert!(
+        !y_range.is_empty(),
+        "Plot {title:?}: range {y_range:?} is empty"
+    );
+
+    let sample_rate = series.samplerate;
+    let bin_width = sample_rate / series.series.len() as f32;
+    let start = -((series.series.len() / 2) as f32) * bin_width;
+
+    let mut ctx = ChartBuilder::on(area);
+    ctx.set_label_area_size(LabelAreaPosition::Left, LABEL_AREA_SIZE)
+        .set_label_area_size(LabelAreaPosition::Bottom, LABEL_AREA_SIZE)
+        .caption(title, (title_font.0.as_str(), title_font.1));
+    let mut ctx = ctx
+        .build_cartesian_2d(-sample_rate / 2.0..sample_rate / 2.0, y_range)
+        .unwrap();
+    ctx.configure_mesh().draw().unwrap();
+
+    let points = series
+        .series
+        .iter()
+        .enumerate()
+        .map(move |(bin, value)| (start + bin as f32 * bin_width, *value));
+    let ann = series.draw_points(&mut ctx, points);
+    series.apply_legend(ann);
+
+    if legend.show {
         ctx.configure_series_labels()
+            .position(legend.position.clone())
             .background_style(WHITE.mix(0.8))
             .draw()
             .unwrap();
     }
+}
+
+/// Builder for a [`Plot`], see [`Plot::builder`].
+pub struct PlotBuilder<'a> {
+    plot: Plot<'a>,
+}
+
+impl Default for PlotBuilder<'_> {
+    fn default() -> Self {
+        Self {
+            plot: Plot {
+                title: "",
+                bode: false,
+                series: Vec::new(),
+                y_range: AxisRange::AutoLin,
+                x_range: None,
+                title_font: (TITLE_FONT_FAMILY.to_string(), TITLE_FONT_SIZE),
+                legend: LegendConfig::default(),
+                valid_band: None,
+                fill_between: None,
+                left_margin: LABEL_AREA_SIZE,
+                bottom_margin: LABEL_AREA_SIZE,
+            },
+        }
+    }
+}
+
+impl<'a> PlotBuilder<'a> {
+    /// Sets the title.
+    pub fn title(mut self, title: &'a str) -> Self {
+        self.plot.title = title;
+        self
+    }
+
+    /// Sets if the series are spectra plotted over a logarithmic frequency
+    /// axis instead of signals plotted over time.
+    pub fn bode(mut self, bode: bool) -> Self {
+        self.plot.bode = bode;
+        self
+    }
+
+    /// Sets the range of the Y axis.
+    pub fn y_range(mut self, y_range: AxisRange) -> Self {
+        self.plot.y_range = y_range;
+        self
+    }
+
+    /// Sets the range of the X axis in seconds, or in Hz for a bode plot.
+    pub fn x_range(mut self, x_range: Range<f32>) -> Self {
+        self.plot.x_range = Some(x_range);
+        self
+    }
+
+    /// Sets the font family and size of the title.
+    pub fn title_font(mut self, title_font: (String, u32)) -> Self {
+        self.plot.title_font = title_font;
+        self
+    }
+
+    /// Sets the visibility and position of the legend.
+    pub fn legend(mut self, legend: LegendConfig) -> Self {
+        self.plot.legend = legend;
+        self
+    }
+
+    /// Sets the range of the X axis in which the data is valid, the regions
+    /// outside are shaded.
+    pub fn valid_band(mut self, valid_band: Range<f32>) -> Self {
+        self.plot.valid_band = Some(valid_band);
+        self
+    }
+
+    /// Sets the width of the label area left of the chart in pixels.
+    pub fn left_margin(mut self, left_margin: u32) -> Self {
+        self.plot.left_margin = left_margin;
+        self
+    }
+
+    /// Sets the height of the label area below the chart in pixels.
+    pub fn bottom_margin(mut self, bottom_margin: u32) -> Self {
+        self.plot.bottom_margin = bottom_margin;
+        self
+    }
+
+    /// Sets the indices of two series, the area between them is filled
+    /// with the color of the first one.
+    pub fn fill_between(mut self, first: usize, second: usize) -> Self {
+        self.plot.fill_between = Some((first, second));
+        self
+    }
+
+    /// Adds a series.
+    pub fn add_series(mut self, series: Series<'a>) -> Self {
+        self.plot.series.push(series);
+        self
+    }
+
+    /// Returns the plot.
+    pub fn build(self) -> Plot<'a> {
+        self.plot
+    }
+}
+
+/// Creates an SVG file with a white background and draws into it.
+pub(crate) fn with_svg(
+    filename: impl AsRef<Path>,
+    draw: impl FnOnce(&DrawingArea<SVGBackend, coord::Shift>),
+) -> Result<(), AnalyzerError> {
+    let path = filename.as_ref();
+    std::fs::create_dir_all(path.parent().expect("Filename is empty"))?;
+    let root = SVGBackend::new(path, PLOT_SIZE).into_drawing_area();
+    root.fill(&WHITE).unwrap();
+    draw(&root);
+    root.present().map_err(write_error)
+}
+
+/// Creates a PNG file with a white background and draws into it.
+pub(crate) fn with_png(
+    filename: impl AsRef<Path>,
+    draw: impl FnOnce(&DrawingArea<BitMapBackend, coord::Shift>),
+) -> Result<(), AnalyzerError> {
+    let path = filename.as_ref();
+    std::fs::create_dir_all(path.parent().expect("Filename is empty"))?;
+    let root = BitMapBackend::new(path, PLOT_SIZE).into_drawing_area();
+    root.fill(&WHITE).unwrap();
+    draw(&root);
+    root.present().map_err(write_error)
+}
+
+/// Creates a PDF or EPS file with a white background and draws into it.
+pub(crate) fn with_vector(
+    filename: impl AsRef<Path>,
+    format: VectorFormat,
+    draw: impl FnOnce(&DrawingArea<VectorBackend, coord::Shift>),
+) -> Result<(), AnalyzerError> {
+    let path = filename.as_ref();
+    std::fs::create_dir_all(path.parent().expect("Filename is empty"))?;
+    let root = VectorBackend::new(path, format, PLOT_SIZE).into_drawing_area();
+    root.fill(&WHITE).unwrap();
+    draw(&root);
+    root.present().map_err(write_error)
+}
+
+/// Returns the error of a drawing backend failing to write its file.
+fn write_error(error: impl std::error::Error + Send + Sync + 'static) -> AnalyzerError {
+    AnalyzerError::Io(std::io::Error::other(error))
+}
+
+/// Returns a readable axis range for values in dB.
+///
+/// Values below [`DB_FLOOR`] and non-finite values such as the `-inf` of a
+/// zero magnitude are ignored. The bounds are rounded outwards to multiples
+/// of [`DB_STEP`], so that the range is never empty.
+pub(crate) fn db_range(values: &[f32]) -> Range<f32> {
+    let (min, max) = values
+        .iter()
+        .copied()
+        .filter(|v| v.is_finite() && *v >= DB_FLOOR)
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), v| {
+            (min.min(v), max.max(v))
+        });
+
+    if min > max {
+        return DB_FLOOR..DB_FLOOR + DB_STEP;
+    }
+
+    let start = (min / DB_STEP).floor() * DB_STEP;
+    let end = (max / DB_STEP).ceil() * DB_STEP;
+    if end > start {
+        start..end
+    } else {
+        start..start + DB_STEP
+    }
+}
+
+/// Returns a readable logarithmic axis range for linear values.
+///
+/// Non-positive values, non-finite values and values below the linear
+/// equivalent of [`DB_FLOOR`] are ignored. The bounds are rounded outwards
+/// to decades, so that the range is never empty.
+pub(crate) fn log_range(values: &[f32]) -> Range<f32> {
+    let floor = 10.0f32.powf(DB_FLOOR / 20.0);
+    let (min, max) = values
+        .iter()
+        .copied()
+        .filter(|v| v.is_finite() && *v >= floor)
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), v| {
+            (min.min(v), max.max(v))
+        });
+
+    if min > max {
+        return floor..floor * 10.0;
+    }
 
-    pub fn create_svg(&self, filename: impl AsRef<Path>) {
-        let path = filename.as_ref();
-        let _ = std::fs::create_dir_all(path.parent().expect("Filename is empty"));
-        let root = SVGBackend::new(path, PLOT_SIZE).into_drawing_area();
-        root.fill(&WHITE).unwrap();
-        self.render_into(&root);
+    let start = 10.0f32.powf(min.log10().floor());
+    let end = 10.0f32.powf(max.log10().ceil());
+    if end > start {
+        start..end
+    } else {
+        start..start * 10.0
     }
 }
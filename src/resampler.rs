@@ -0,0 +1,312 @@
+//! Sample rate conversion.
+
+/// Half the length of the interpolation kernel in zero crossings of the sinc.
+const KERNEL_HALF_WIDTH: f64 = 32.0;
+
+/// Cutoff frequency of the kernel relative to the lower Nyquist frequency,
+/// leaving room for the transition band of the window.
+const KERNEL_CUTOFF: f64 = 0.95;
+
+/// Length of the lowpass of [`Decimator`] and [`Interpolator`] in taps per
+/// sample of the lower rate.
+const FILTER_TAPS_PER_PHASE: usize = 64;
+
+/// Half the main lobe width of the Blackman window in cycles per sample,
+/// times the filter length. The lowpass cutoff is placed this far below the
+/// lower Nyquist frequency, so that the stopband starts there.
+const FILTER_TRANSITION: f64 = 3.0;
+
+/// Returns the samples converted from one sample rate to another.
+///
+/// Each output sample is interpolated by a Blackman-windowed sinc kernel,
+/// which also removes components above the target Nyquist frequency when
+/// converting to a lower rate. Samples outside the signal are treated as
+/// zero. The output contains the duration of the input, rounded to samples.
+pub fn resample(samples: &[f32], from_rate: f32, to_rate: f32) -> Vec<f32> {
+    assert!(
+        from_rate > 0.0 && to_rate > 0.0,
+        "Sample rates must be positive"
+    );
+
+    if samples.is_empty() {
+        return Vec::new();
+    }
+
+    let ratio = to_rate as f64 / from_rate as f64;
+    let length = (samples.len() as f64 * ratio).round() as usize;
+    let cutoff = ratio.min(1.0) * KERNEL_CUTOFF;
+    let half_width = KERNEL_HALF_WIDTH / cutoff;
+
+    (0..length)
+        .map(|n| {
+            let position = n as f64 / ratio;
+            let start = (position - half_width).ceil().max(0.0) as usize;
+            let end = ((position + half_width).floor() as usize).min(samples.len() - 1);
+
+            (start..=end)
+                .map(|k| {
+                    let offset = position - k as f64;
+                    samples[k] as f64
+                        * cutoff
+                        * sinc(cutoff * offset)
+                        * blackman(offset / half_width)
+                })
+                .sum::<f64>() as f32
+        })
+        .collect()
+}
+
+/// Decimator reducing the sample rate by an integer factor.
+///
+/// The samples are filtered by a windowed-sinc lowpass with its stopband
+/// starting at the Nyquist frequency of the lower rate, so that components
+/// above are removed instead of aliased. Only every `factor`-th filter output
+/// is computed. The state is kept between calls, so a signal can be processed
+/// in blocks of any length.
+#[derive(Debug, Clone)]
+pub struct Decimator {
+    /// Ratio of the input to the output sample rate.
+    factor: usize,
+
+    /// Taps of the lowpass at the input rate.
+    kernel: Vec<f32>,
+
+    /// History of the input samples.
+    history: History,
+
+    /// Number of input samples until the next output sample.
+    countdown: usize,
+}
+
+impl Decimator {
+    /// Returns a new instance for a factor of at least 1.
+    pub fn new(factor: usize) -> Self {
+        assert!(factor > 0, "Factor must be at least 1");
+
+        let kernel = lowpass_kernel(factor);
+        Self {
+            factor,
+            history: History::new(kernel.len()),
+            kernel,
+            countdown: 0,
+        }
+    }
+
+    /// Returns the ratio of the input to the output sample rate.
+    pub fn factor(&self) -> usize {
+        self.factor
+    }
+
+    /// Returns the delay of the lowpass in samples of the input rate.
+    pub fn latency(&self) -> usize {
+        self.kernel.len() / 2
+    }
+
+    /// Clears the state.
+    pub fn reset(&mut self) {
+        self.history.reset();
+        self.countdown = 0;
+    }
+
+    /// Decimates a block of samples and returns the number of samples
+    /// written to the output.
+    ///
+    /// The output must hold at least `input.len().div_ceil(factor)` samples.
+    /// Blocks with a length that is a multiple of the factor always produce
+    /// `input.len() / factor` samples.
+    pub fn process(&mut self, input: &[f32], output: &mut [f32]) -> usize {
+        assert!(
+            output.len() >= input.len().div_ceil(self.factor),
+            "Output of {} samples is too short",
+            output.len()
+        );
+
+        let mut count = 0;
+        for sample in input {
+            self.history.push(*sample);
+            if self.countdown == 0 {
+                output[count] = self.history.dot(&self.kernel);
+                count += 1;
+                self.countdown = self.factor;
+            }
+            self.countdown -= 1;
+        }
+
+        count
+    }
+
+    /// Filters a block of samples in-place at the input rate, without
+    /// reducing the sample rate.
+    ///
+    /// This applies the anti-aliasing lowpass only, e.g. to measure its
+    /// response with [`crate::FftAnalyzer::run`]. The state is shared with
+    /// [`Decimator::process`], so the two should not be mixed on one signal.
+    pub fn filter_block(&mut self, samples: &mut [f32]) {
+        for sample in samples.iter_mut() {
+            self.history.push(*sample);
+            *sample = self.history.dot(&self.kernel);
+        }
+    }
+}
+
+/// Interpolator increasing the sample rate by an integer factor.
+///
+/// The samples are upsampled by inserting zeros and filtered by the same
+/// lowpass as in [`Decimator`], which removes the images above the Nyquist
+/// frequency of the lower rate. Each output phase is computed from its own
+/// subset of the filter taps. The state is kept between calls.
+///
+/// An interpolator followed by a decimator with the same factor can be run
+/// as processor by [`crate::FftAnalyzer::run`] to measure the response of
+/// the conversion.
+#[derive(Debug, Clone)]
+pub struct Interpolator {
+    /// Ratio of the output to the input sample rate.
+    factor: usize,
+
+    /// History of the input samples.
+    history: History,
+
+    /// Taps of each output phase, scaled by the factor.
+    phases: Vec<Vec<f32>>,
+}
+
+impl Interpolator {
+    /// Returns a new instance for a factor of at least 1.
+    pub fn new(factor: usize) -> Self {
+        assert!(factor > 0, "Factor must be at least 1");
+
+        // The taps of each phase in the order of the history, oldest first.
+        let kernel = lowpass_kernel(factor);
+        let length = kernel.len().div_ceil(factor);
+        let phases = (0..factor)
+            .map(|phase| {
+                (0..length)
+                    .rev()
+                    .map(|k| {
+                        kernel
+                            .get(phase + k * factor)
+                            .map_or(0.0, |c| c * factor as f32)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            factor,
+            history: History::new(length),
+            phases,
+        }
+    }
+
+    /// Returns the ratio of the output to the input sample rate.
+    pub fn factor(&self) -> usize {
+        self.factor
+    }
+
+    /// Returns the delay of the lowpass in samples of the output rate.
+    pub fn latency(&self) -> usize {
+        lowpass_kernel(self.factor).len() / 2
+    }
+
+    /// Clears the state.
+    pub fn reset(&mut self) {
+        self.history.reset();
+    }
+
+    /// Interpolates a block of samples.
+    ///
+    /// The output must hold exactly `input.len() * factor` samples.
+    pub fn process(&mut self, input: &[f32], output: &mut [f32]) {
+        assert_eq!(
+            output.len(),
+            input.len() * self.factor,
+            "Output length must be the input length times the factor"
+        );
+
+        for (sample, frame) in input.iter().zip(output.chunks_mut(self.factor)) {
+            self.history.push(*sample);
+            for (out_sample, phase) in frame.iter_mut().zip(self.phases.iter()) {
+                *out_sample = self.history.dot(phase);
+            }
+        }
+    }
+}
+
+/// History of the last samples of a FIR filter.
+#[derive(Debug, Clone)]
+struct History {
+    /// Samples stored twice in a row, so that the last ones are contiguous.
+    buffer: Vec<f32>,
+
+    /// Number of samples.
+    length: usize,
+
+    /// Index of the oldest sample in the first half of the buffer.
+    position: usize,
+}
+
+impl History {
+    /// Returns a new instance holding a number of samples, initially zero.
+    fn new(length: usize) -> Self {
+        Self {
+            buffer: vec![0.0; 2 * length],
+            length,
+            position: 0,
+        }
+    }
+
+    /// Clears the history.
+    fn reset(&mut self) {
+        self.buffer.fill(0.0);
+        self.position = 0;
+    }
+
+    /// Appends a sample to the history, replacing the oldest one.
+    fn push(&mut self, sample: f32) {
+        self.buffer[self.position] = sample;
+        self.buffer[self.position + self.length] = sample;
+        self.position = (self.position + 1) % self.length;
+    }
+
+    /// Returns the dot product of the samples, oldest first, with filter taps.
+    fn dot(&self, taps: &[f32]) -> f32 {
+        let samples = &self.buffer[self.position..self.position + self.length];
+        samples.iter().zip(taps).map(|(x, c)| x * c).sum()
+    }
+}
+
+/// Returns the symmetric taps of a lowpass for a rate conversion by a factor,
+/// normalized to unity gain at DC.
+fn lowpass_kernel(factor: usize) -> Vec<f32> {
+    let half_length = FILTER_TAPS_PER_PHASE * factor / 2;
+    let length = 2 * half_length + 1;
+    // Relative to the Nyquist frequency of the higher rate.
+    let cutoff = 1.0 / factor as f64 - 2.0 * FILTER_TRANSITION / length as f64;
+
+    let kernel: Vec<f64> = (0..length)
+        .map(|n| {
+            let offset = n as f64 - half_length as f64;
+            cutoff * sinc(cutoff * offset) * blackman(offset / (half_length + 1) as f64)
+        })
+        .collect();
+    let sum: f64 = kernel.iter().sum();
+
+    kernel.iter().map(|c| (c / sum) as f32).collect()
+}
+
+/// Returns the normalized sinc function.
+fn sinc(x: f64) -> f64 {
+    if x == 0.0 {
+        1.0
+    } else {
+        let x = std::f64::consts::PI * x;
+        x.sin() / x
+    }
+}
+
+/// Returns the Blackman window for a position from -1.0 to 1.0.
+fn blackman(x: f64) -> f64 {
+    let x = std::f64::consts::PI * x;
+    (0.42 + 0.5 * x.cos() + 0.08 * (2.0 * x).cos()).max(0.0)
+}
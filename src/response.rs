@@ -0,0 +1,508 @@
+//! Frequency response data.
+
+use std::ops::Range;
+
+use realfft::num_complex::Complex;
+
+use crate::{bode, smoothing, Interpolation};
+
+/// Level in dB below the peak gain that defines the cutoff frequency.
+const CUTOFF_LEVEL_DB: f32 = -3.0;
+
+/// Highest gain in dB of an inverted response.
+const INVERSE_MAX_GAIN_DB: f32 = 20.0;
+
+/// Nominal center frequencies in Hz of the standard octave bands.
+const OCTAVE_BAND_CENTERS: [f32; 10] = [
+    31.5, 63.0, 125.0, 250.0, 500.0, 1000.0, 2000.0, 4000.0, 8000.0, 16000.0,
+];
+
+/// Bin outside a tolerance mask, see [`FrequencyResponse::check_mask`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MaskViolation {
+    /// Frequency of the bin in Hz.
+    pub freq: f32,
+
+    /// Magnitude of the bin in dB.
+    pub magnitude_db: f32,
+
+    /// Lowest allowed magnitude in dB at the frequency.
+    pub min_db: f32,
+
+    /// Highest allowed magnitude in dB at the frequency.
+    pub max_db: f32,
+}
+
+/// Frequency response with magnitude and phase per bin.
+///
+/// With the `serde` feature enabled, the response can be serialized,
+/// e.g. to JSON with `FrequencyResponse::save_json`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrequencyResponse {
+    /// Sample rate in Hz.
+    pub sample_rate: f32,
+
+    /// Frequency of each bin in Hz.
+    pub frequencies: Vec<f32>,
+
+    /// Magnitude of each bin in dB.
+    pub magnitude_db: Vec<f32>,
+
+    /// Phase of each bin in degrees.
+    pub phase_deg: Vec<f32>,
+
+    /// Frequency band in Hz in which the response is valid.
+    pub valid_band: Range<f32>,
+
+    /// Gain in dB at the lowest frequency within the valid band.
+    pub dc_gain: f32,
+
+    /// Highest gain in dB within the valid band.
+    pub peak_gain: f32,
+
+    /// Frequency of the highest gain in Hz.
+    pub peak_freq: f32,
+
+    /// Lowest frequency in Hz at which the magnitude crosses the level
+    /// 3dB below the peak gain, `None` if there is no such crossing.
+    pub cutoff: Option<f32>,
+}
+
+impl FrequencyResponse {
+    /// Returns a new response from the bins spread evenly from DC to Nyquist.
+    ///
+    /// The derived values are computed from the bins within the valid band.
+    pub fn new(
+        sample_rate: f32,
+        magnitude_db: Vec<f32>,
+        phase_deg: Vec<f32>,
+        valid_band: Range<f32>,
+    ) -> Self {
+        let bin_width = sample_rate / 2.0 / magnitude_db.len().saturating_sub(1).max(1) as f32;
+        let frequencies = (0..magnitude_db.len())
+            .map(|i| i as f32 * bin_width)
+            .collect();
+
+        let mut response = Self {
+            sample_rate,
+            frequencies,
+            magnitude_db,
+            phase_deg,
+            valid_band,
+            dc_gain: 0.0,
+            peak_gain: 0.0,
+            peak_freq: 0.0,
+            cutoff: None,
+        };
+        response.update_derived();
+
+        response
+    }
+
+    /// Writes the response including the sample rate and the frequency of
+    /// each bin as JSON file, creating its directory if needed.
+    ///
+    /// The values are written with the shortest representation that reads
+    /// back exactly, so a loaded response compares equal to the saved one.
+    #[cfg(feature = "serde")]
+    pub fn save_json(&self, filename: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        let path = filename.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+
+    /// Reads a response from a JSON file written by [`FrequencyResponse::save_json`].
+    #[cfg(feature = "serde")]
+    pub fn load_json(filename: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        let file = std::io::BufReader::new(std::fs::File::open(filename)?);
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    /// Returns the indices of the bins within the valid band.
+    pub fn valid_bins(&self) -> Range<usize> {
+        let start = self
+            .frequencies
+            .iter()
+            .position(|f| *f >= self.valid_band.start)
+            .unwrap_or(self.frequencies.len());
+        let end = self
+            .frequencies
+            .iter()
+            .rposition(|f| *f <= self.valid_band.end)
+            .map_or(start, |i| i + 1);
+
+        start..end.max(start)
+    }
+
+    /// Returns the index of the bin closest to a frequency, 0 for an empty
+    /// response.
+    pub fn bin_index(&self, freq: f32) -> usize {
+        let bin_width = self.bin_width();
+        ((freq / bin_width).round().max(0.0) as usize).min(self.frequencies.len().saturating_sub(1))
+    }
+
+    /// Returns the distance between two bins in Hz.
+    fn bin_width(&self) -> f32 {
+        self.sample_rate / 2.0 / self.frequencies.len().saturating_sub(1).max(1) as f32
+    }
+
+    /// Returns the offset in dB that aligns this response to another
+    /// one at the bin closest to a frequency.
+    pub fn match_gain_to(&self, other: &FrequencyResponse, at_freq: f32) -> f32 {
+        other.magnitude_db[other.bin_index(at_freq)] - self.magnitude_db[self.bin_index(at_freq)]
+    }
+
+    /// Adds an offset in dB to the magnitude of all bins.
+    pub fn offset_db(&mut self, db: f32) {
+        for magnitude in self.magnitude_db.iter_mut() {
+            *magnitude += db;
+        }
+        self.update_derived();
+    }
+
+    /// Returns the response of this and another response in series,
+    /// i.e. the product of their complex spectra.
+    ///
+    /// Both responses must have the same sample rate and number of bins.
+    /// The valid band is the intersection of both bands.
+    pub fn cascade(&self, other: &FrequencyResponse) -> FrequencyResponse {
+        self.combine(other, |a, b| a * b)
+    }
+
+    /// Returns the response of this and another response in parallel,
+    /// i.e. the sum of their complex spectra.
+    ///
+    /// Both responses must have the same sample rate and number of bins.
+    /// The valid band is the intersection of both bands.
+    pub fn parallel(&self, other: &FrequencyResponse) -> FrequencyResponse {
+        self.combine(other, |a, b| a + b)
+    }
+
+    /// Returns the magnitude in dB and the phase in degrees at arbitrary
+    /// frequencies, e.g. to compare responses measured at different sample rates.
+    ///
+    /// The values are interpolated linearly between the two closest bins,
+    /// the phase along the shorter way around the circle. Frequencies beyond
+    /// Nyquist return the values of the last bin.
+    pub fn resample_to_grid(&self, freqs: &[f32]) -> Vec<(f32, f32)> {
+        freqs
+            .iter()
+            .map(|freq| {
+                let interpolate = |values: &[f32], wrap_phase| {
+                    bode::interpolate(
+                        values,
+                        self.sample_rate,
+                        *freq,
+                        Interpolation::Linear,
+                        wrap_phase,
+                    )
+                };
+                (
+                    interpolate(&self.magnitude_db, false),
+                    interpolate(&self.phase_deg, true),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the inverse response for the design of a correction filter,
+    /// negating the magnitude in dB and the phase.
+    ///
+    /// The gain of the inverse is limited to +20dB, so that deep notches
+    /// and bins outside the valid band do not turn into excessive boosts.
+    pub fn invert(&self) -> FrequencyResponse {
+        FrequencyResponse::new(
+            self.sample_rate,
+            self.magnitude_db
+                .iter()
+                .map(|m| (-m).min(INVERSE_MAX_GAIN_DB))
+                .collect(),
+            self.phase_deg.iter().map(|p| -p).collect(),
+            self.valid_band.clone(),
+        )
+    }
+
+    /// Returns a copy with the magnitude smoothed over `1 / fraction` octave.
+    ///
+    /// The phase is left untouched.
+    pub fn smooth(&self, fraction: f32) -> FrequencyResponse {
+        let bin_width = self.bin_width();
+        let mut response = self.clone();
+        response.magnitude_db = smoothing::smooth(&self.magnitude_db, bin_width, fraction);
+        response.update_derived();
+
+        response
+    }
+
+    /// Returns a copy with the phase smoothed over `1 / fraction` octave.
+    ///
+    /// The phase is averaged as unit vectors to handle the wrap at ±180°.
+    /// The magnitude is left untouched.
+    pub fn smooth_phase(&self, fraction: f32) -> FrequencyResponse {
+        let bin_width = self.bin_width();
+        let mut response = self.clone();
+        response.phase_deg = smoothing::smooth_phase(&self.phase_deg, bin_width, fraction);
+
+        response
+    }
+
+    /// Returns the level in dB of the energy within each standard octave band
+    /// from 31.5Hz to 16kHz as pairs of the nominal center frequency and the level.
+    ///
+    /// The energy of the bins between the band edges is summed, using the
+    /// exact base-2 center frequencies relative to 1kHz. Only bins within the
+    /// valid band are taken into account and bands without any of them are
+    /// omitted.
+    pub fn octave_bands(&self) -> Vec<(f32, f32)> {
+        let bins = self.valid_bins();
+
+        OCTAVE_BAND_CENTERS
+            .iter()
+            .zip(-5..)
+            .filter_map(|(nominal, exponent)| {
+                let center = 1000.0 * 2.0f32.powi(exponent);
+                let edges = center / std::f32::consts::SQRT_2..center * std::f32::consts::SQRT_2;
+                let energy = bins
+                    .clone()
+                    .filter(|i| edges.contains(&self.frequencies[*i]))
+                    .map(|i| 10.0f32.powf(self.magnitude_db[i] / 10.0))
+                    .reduce(|sum, e| sum + e)?;
+                Some((*nominal, 10.0 * energy.log10()))
+            })
+            .collect()
+    }
+
+    /// Returns the frequencies in Hz at which the magnitude crosses the level
+    /// 3dB below the peak gain within the valid band, in ascending order.
+    pub fn cutoff_points(&self) -> Vec<f32> {
+        let level = self.peak_gain + CUTOFF_LEVEL_DB;
+        self.valid_bins()
+            .skip(1)
+            .filter_map(|i| {
+                let (m0, m1) = (self.magnitude_db[i - 1], self.magnitude_db[i]);
+                if (m0 - level) * (m1 - level) > 0.0 || m0 == m1 {
+                    return None;
+                }
+                let (f0, f1) = (self.frequencies[i - 1], self.frequencies[i]);
+                Some(f0 + (f1 - f0) * (level - m0) / (m1 - m0))
+            })
+            .collect()
+    }
+
+    /// Returns the slope of the magnitude in dB per octave between two
+    /// frequencies in Hz, e.g. the rolloff in the stopband of a filter.
+    ///
+    /// The slope is the least-squares fit of a line to the magnitude over the
+    /// base-2 logarithm of the frequency, using the bins within both the range
+    /// and the valid band. Returns NaN if there are fewer than 2 such bins.
+    pub fn rolloff_slope(&self, from_freq: f32, to_freq: f32) -> f32 {
+        let range = from_freq.min(to_freq)..=from_freq.max(to_freq);
+        let points: Vec<(f32, f32)> = self
+            .valid_bins()
+            .filter(|i| self.frequencies[*i] > 0.0 && range.contains(&self.frequencies[*i]))
+            .map(|i| (self.frequencies[i].log2(), self.magnitude_db[i]))
+            .collect();
+        if points.len() < 2 {
+            return f32::NAN;
+        }
+
+        let count = points.len() as f32;
+        let mean_x = points.iter().map(|(x, _)| x).sum::<f32>() / count;
+        let mean_y = points.iter().map(|(_, y)| y).sum::<f32>() / count;
+        let (covariance, variance) =
+            points
+                .iter()
+                .fold((0.0, 0.0), |(covariance, variance), (x, y)| {
+                    (
+                        covariance + (x - mean_x) * (y - mean_y),
+                        variance + (x - mean_x) * (x - mean_x),
+                    )
+                });
+
+        covariance / variance
+    }
+
+    /// Returns the bins with a magnitude outside a tolerance mask, e.g. for an
+    /// automated check of a filter against its specification.
+    ///
+    /// The mask is given as points of a frequency in Hz and the lowest and
+    /// highest allowed magnitude in dB, in ascending order of frequency. An
+    /// infinite limit allows any magnitude on that side, e.g. `f32::NEG_INFINITY`
+    /// as lowest magnitude in a stopband. Between the points, the limits are
+    /// interpolated linearly over the logarithmic frequency, or over the
+    /// linear frequency for a segment starting at 0Hz. Only bins within both
+    /// the frequency span of the mask and the valid band are checked.
+    pub fn check_mask(&self, mask: &[(f32, f32, f32)]) -> Vec<MaskViolation> {
+        let (Some(first), Some(last)) = (mask.first(), mask.last()) else {
+            return Vec::new();
+        };
+
+        self.valid_bins()
+            .filter(|i| (first.0..=last.0).contains(&self.frequencies[*i]))
+            .filter_map(|i| {
+                let freq = self.frequencies[i];
+                let segment = mask
+                    .windows(2)
+                    .find(|pair| freq <= pair[1].0)
+                    .map_or([*first, *first], |pair| [pair[0], pair[1]]);
+                let (f0, min0, max0) = segment[0];
+                let (f1, min1, max1) = segment[1];
+                let t = if f1 <= f0 {
+                    0.0
+                } else if f0 > 0.0 {
+                    (freq / f0).log2() / (f1 / f0).log2()
+                } else {
+                    (freq - f0) / (f1 - f0)
+                };
+                let interpolate = |a: f32, b: f32, permissive: fn(f32, f32) -> f32| {
+                    if a == b || !a.is_finite() || !b.is_finite() {
+                        permissive(a, b)
+                    } else {
+                        a + (b - a) * t
+                    }
+                };
+                let min_db = interpolate(min0, min1, f32::min);
+                let max_db = interpolate(max0, max1, f32::max);
+
+                let magnitude_db = self.magnitude_db[i];
+                (magnitude_db < min_db || magnitude_db > max_db).then_some(MaskViolation {
+                    freq,
+                    magnitude_db,
+                    min_db,
+                    max_db,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the minimum phase in degrees of each bin for the magnitude.
+    ///
+    /// The phase is the Hilbert transform of the natural logarithm of the
+    /// magnitude, computed via the real cepstrum. The measured phase is not
+    /// taken into account. Bins clamped to the magnitude floor, such as the
+    /// zeros of a lowpass at Nyquist, introduce an error in the phase.
+    pub fn minimum_phase(&self) -> Vec<f32> {
+        let length = 2 * self.magnitude_db.len().saturating_sub(1).max(1);
+
+        let log_magnitude: Vec<Complex<f32>> = self
+            .magnitude_db
+            .iter()
+            .map(|m| Complex::new(m * std::f32::consts::LN_10 / 20.0, 0.0))
+            .chain(std::iter::repeat(Complex::new(0.0, 0.0)))
+            .take(length / 2 + 1)
+            .collect();
+        let cepstrum = bode::ifft(&log_magnitude, length);
+
+        // Fold the anti-causal part of the cepstrum onto the causal part.
+        let folded: Vec<f32> = cepstrum
+            .iter()
+            .enumerate()
+            .map(|(i, c)| match i {
+                0 => *c,
+                i if i < length / 2 => 2.0 * c,
+                i if i == length / 2 => *c,
+                _ => 0.0,
+            })
+            .collect();
+
+        bode::fft(&folded)
+            .iter()
+            .take(self.magnitude_db.len())
+            .map(|c| c.im.to_degrees())
+            .map(|p| (p + 180.0).rem_euclid(360.0) - 180.0)
+            .collect()
+    }
+
+    /// Returns the response combining the complex spectra bin by bin.
+    fn combine(
+        &self,
+        other: &FrequencyResponse,
+        func: impl Fn(Complex<f32>, Complex<f32>) -> Complex<f32>,
+    ) -> FrequencyResponse {
+        assert_eq!(
+            self.sample_rate, other.sample_rate,
+            "Sample rates of the responses differ"
+        );
+        assert_eq!(
+            self.frequencies.len(),
+            other.frequencies.len(),
+            "Number of bins of the responses differs"
+        );
+
+        let transfer: Vec<Complex<f32>> = self
+            .spectrum()
+            .zip(other.spectrum())
+            .map(|(a, b)| func(a, b))
+            .collect();
+        let valid_band = self.valid_band.start.max(other.valid_band.start)
+            ..self.valid_band.end.min(other.valid_band.end);
+
+        FrequencyResponse::new(
+            self.sample_rate,
+            bode::magnitude_db(&transfer),
+            bode::phase_deg(&transfer),
+            valid_band,
+        )
+    }
+
+    /// Returns the complex spectrum of the bins.
+    fn spectrum(&self) -> impl Iterator<Item = Complex<f32>> + '_ {
+        self.magnitude_db
+            .iter()
+            .zip(self.phase_deg.iter())
+            .map(|(m, p)| Complex::from_polar(10.0f32.powf(m / 20.0), p.to_radians()))
+    }
+
+    /// Recomputes the derived values from the bins.
+    fn update_derived(&mut self) {
+        let bins = self.valid_bins();
+        if bins.is_empty() {
+            self.dc_gain = 0.0;
+            self.peak_gain = 0.0;
+            self.peak_freq = 0.0;
+            self.cutoff = None;
+            return;
+        }
+
+        self.dc_gain = self.magnitude_db[bins.start];
+
+        let peak = bins
+            .clone()
+            .max_by(|a, b| self.magnitude_db[*a].total_cmp(&self.magnitude_db[*b]))
+            .unwrap();
+        self.peak_gain = self.magnitude_db[peak];
+        self.peak_freq = self.frequencies[peak];
+
+        self.cutoff = self.cutoff_points().first().copied();
+    }
+}
+
+impl core::fmt::Display for FrequencyResponse {
+    /// Writes a one-line summary of the derived values, e.g.
+    /// `DC gain: 0.0dB, peak: 0.0dB at 0Hz, cutoff: 1000Hz, -3dB points: [1000Hz]`.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "DC gain: {:.1}dB, peak: {:.1}dB at {:.0}Hz, cutoff: ",
+            self.dc_gain, self.peak_gain, self.peak_freq
+        )?;
+        match self.cutoff {
+            Some(cutoff) => write!(f, "{cutoff:.0}Hz")?,
+            None => write!(f, "none")?,
+        }
+
+        write!(f, ", {CUTOFF_LEVEL_DB:.0}dB points: [")?;
+        for (i, freq) in self.cutoff_points().iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{freq:.0}Hz")?;
+        }
+        write!(f, "]")
+    }
+}
@@ -0,0 +1,109 @@
+//! Reusable analyzer session for batch measurements.
+
+use std::ops::Range;
+
+use realfft::num_complex::Complex;
+
+use crate::bode::{
+    compensate_delay, excitation_mask, fft, magnitude_db, phase_deg, transfer_function,
+    validate_output, FftAnalyzerConfig, Result,
+};
+
+/// Result of an analysis by [`AnalyzerSession::analyze`].
+#[derive(Debug, Clone)]
+pub struct SpectrumResult {
+    /// Output samples.
+    pub out_samples: Vec<f32>,
+
+    /// Magnitude of the spectrum in dB.
+    pub magnitude: Vec<f32>,
+
+    /// Phase of the spectrum in degrees.
+    pub phase: Vec<f32>,
+}
+
+/// Analyzer session with a pre-generated test signal.
+///
+/// The test signal and its spectrum are computed once on construction, each
+/// analysis only transforms the output. The session is immutable during
+/// analysis, so it can be shared between threads, e.g. via an `Arc`.
+#[derive(Debug, Clone)]
+pub struct AnalyzerSession {
+    /// Configuration the session was created with.
+    config: FftAnalyzerConfig,
+
+    /// Test signal samples.
+    in_samples: Vec<f32>,
+
+    /// Spectrum of the test signal.
+    in_spectrum: Vec<Complex<f32>>,
+
+    /// Bins excited by the test signal.
+    mask: Vec<bool>,
+
+    /// Frequency band in Hz in which the spectrum is valid.
+    valid_band: Range<f32>,
+}
+
+impl AnalyzerSession {
+    /// Returns a new session, generating the configured test signal.
+    pub fn new(config: FftAnalyzerConfig) -> Self {
+        let mut in_samples = config.test_signal_samples();
+        config.pad_to_blocks(&mut in_samples);
+        let valid_band = config.test_signal_band();
+        let in_spectrum = fft(&in_samples);
+        let mask = excitation_mask(&in_spectrum, &valid_band, config.sample_rate);
+
+        Self {
+            config,
+            in_samples,
+            in_spectrum,
+            mask,
+            valid_band,
+        }
+    }
+
+    /// Returns the configuration.
+    pub fn config(&self) -> &FftAnalyzerConfig {
+        &self.config
+    }
+
+    /// Returns the test signal samples.
+    pub fn in_samples(&self) -> &[f32] {
+        &self.in_samples
+    }
+
+    /// Returns the frequency band in Hz in which the spectrum is valid.
+    pub fn valid_band(&self) -> Range<f32> {
+        self.valid_band.clone()
+    }
+
+    /// Runs the test signal through the provided function and returns the result.
+    ///
+    /// The closure is called in the same way as for [`crate::FftAnalyzer::run`].
+    ///
+    /// Returns an error if the output contains a non-finite sample, e.g. from
+    /// an unstable filter.
+    pub fn analyze<F>(&self, mut func: F) -> Result<SpectrumResult>
+    where
+        F: FnMut(&[f32], &mut [f32]),
+    {
+        let mut out_samples = self.in_samples.clone();
+        self.config
+            .process(&self.in_samples, &mut out_samples, &mut func);
+        validate_output(&out_samples)?;
+
+        let mut transfer = transfer_function(&self.in_spectrum, &out_samples, &self.mask);
+        compensate_delay(
+            &mut transfer,
+            self.config.delay_compensation,
+            out_samples.len(),
+        );
+
+        Ok(SpectrumResult {
+            magnitude: magnitude_db(&transfer),
+            phase: phase_deg(&transfer),
+            out_samples,
+        })
+    }
+}
@@ -0,0 +1,143 @@
+//! Fractional-octave smoothing.
+//!
+//! With the `rayon` feature enabled, the bins are smoothed in parallel.
+//! Each output bin is computed independently in the same order of
+//! operations, so the results are identical to the serial version.
+
+use std::ops::RangeInclusive;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+/// Ratio of the half width of the Gaussian window to its standard deviation,
+/// beyond which the weights are negligible.
+const GAUSSIAN_EXTENT: f32 = 3.0;
+
+/// Weighting of the bins within the smoothing window.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum SmoothingKind {
+    /// Equal weights within `1 / fraction` octave around each bin.
+    #[default]
+    Rectangular,
+
+    /// Gaussian weights over the logarithmic frequency, with a full width at
+    /// half maximum of `1 / fraction` octave, as used by common room
+    /// measurement tools. The result is free of the ripple caused by
+    /// features entering and leaving the edges of a rectangular window.
+    Gaussian,
+}
+
+/// Smooths spectrum values over a window of `1 / fraction` octave around each bin.
+///
+/// `bin_width` is the frequency spacing of the bins in Hz, the first bin is at DC.
+/// Each output value is the mean of the input values within the window.
+pub fn smooth(values: &[f32], bin_width: f32, fraction: f32) -> Vec<f32> {
+    smooth_with(values, bin_width, fraction, SmoothingKind::Rectangular)
+}
+
+/// Smooths spectrum values like [`smooth`] with a weighting of the bins.
+pub fn smooth_with(values: &[f32], bin_width: f32, fraction: f32, kind: SmoothingKind) -> Vec<f32> {
+    #[cfg(feature = "rayon")]
+    {
+        (0..values.len())
+            .into_par_iter()
+            .map(|index| smooth_bin_with(values, bin_width, fraction, index, kind))
+            .collect()
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    {
+        (0..values.len())
+            .map(|index| smooth_bin_with(values, bin_width, fraction, index, kind))
+            .collect()
+    }
+}
+
+/// Smooths phase values in degrees over a window of `1 / fraction` octave around each bin.
+///
+/// The phase is averaged as unit vectors, so that values on both sides of
+/// the ±180° boundary do not cancel out. The result is wrapped to ±180°.
+pub fn smooth_phase(values: &[f32], bin_width: f32, fraction: f32) -> Vec<f32> {
+    #[cfg(feature = "rayon")]
+    {
+        (0..values.len())
+            .into_par_iter()
+            .map(|index| smooth_phase_bin(values, bin_width, fraction, index))
+            .collect()
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    {
+        (0..values.len())
+            .map(|index| smooth_phase_bin(values, bin_width, fraction, index))
+            .collect()
+    }
+}
+
+/// Returns the smoothed value of a single bin.
+fn smooth_bin(values: &[f32], bin_width: f32, fraction: f32, index: usize) -> f32 {
+    let window = &values[window(values.len(), bin_width, fraction, index)];
+    window.iter().sum::<f32>() / window.len() as f32
+}
+
+/// Returns the smoothed value of a single bin with a weighting.
+fn smooth_bin_with(
+    values: &[f32],
+    bin_width: f32,
+    fraction: f32,
+    index: usize,
+    kind: SmoothingKind,
+) -> f32 {
+    match kind {
+        SmoothingKind::Rectangular => smooth_bin(values, bin_width, fraction, index),
+        SmoothingKind::Gaussian => smooth_bin_gaussian(values, bin_width, fraction, index),
+    }
+}
+
+/// Returns the value of a single bin smoothed with Gaussian weights.
+fn smooth_bin_gaussian(values: &[f32], bin_width: f32, fraction: f32, index: usize) -> f32 {
+    if index == 0 {
+        return values[0];
+    }
+
+    // Standard deviation in octaves for a full width at half maximum of 1 / fraction.
+    let sigma = 1.0 / (fraction * 2.0 * (2.0 * std::f32::consts::LN_2).sqrt());
+    let range = window(
+        values.len(),
+        bin_width,
+        0.5 / (GAUSSIAN_EXTENT * sigma),
+        index,
+    );
+
+    let (sum, weights) = range.fold((0.0, 0.0), |(sum, weights), bin| {
+        let octaves = (bin as f32 / index as f32).log2() / sigma;
+        let weight = (-0.5 * octaves * octaves).exp();
+        (sum + weight * values[bin], weights + weight)
+    });
+    sum / weights
+}
+
+/// Returns the smoothed phase of a single bin in degrees.
+fn smooth_phase_bin(values: &[f32], bin_width: f32, fraction: f32, index: usize) -> f32 {
+    let window = &values[window(values.len(), bin_width, fraction, index)];
+    let (sin, cos) = window.iter().fold((0.0, 0.0), |(sin, cos), phase| {
+        let (s, c) = phase.to_radians().sin_cos();
+        (sin + s, cos + c)
+    });
+    f32::atan2(sin, cos).to_degrees()
+}
+
+/// Returns the range of bins within the window around a bin.
+pub(crate) fn window(
+    length: usize,
+    bin_width: f32,
+    fraction: f32,
+    index: usize,
+) -> RangeInclusive<usize> {
+    let half_width = 2.0f32.powf(0.5 / fraction);
+    let freq = index as f32 * bin_width;
+    let start = ((freq / half_width / bin_width).ceil() as usize).min(index);
+    let end = ((freq * half_width / bin_width).floor() as usize).clamp(index, length - 1);
+
+    start..=end
+}
@@ -0,0 +1,147 @@
+//! Streaming analyzer for real-time processing.
+
+use crate::bode::{excitation_mask, fft, magnitude_db, phase_deg, transfer_function};
+use crate::response::FrequencyResponse;
+
+/// Analyzer computing a rolling frequency response from blocks of
+/// input and output samples as they arrive.
+///
+/// The most recent samples are kept in ring buffers of a fixed length, which
+/// is also the length of the spectrum. The spectrum is computed on demand via
+/// [`StreamingAnalyzer::compute`] or automatically after every
+/// [`StreamingAnalyzer::update_interval`] samples.
+///
+/// The result matches an offline analysis, if the input is periodic with
+/// the buffer length and the response of the processor decays within it.
+#[derive(Debug, Clone)]
+pub struct StreamingAnalyzer {
+    /// Sample rate in Hz.
+    pub sample_rate: f32,
+
+    /// Number of samples after which the response is computed automatically,
+    /// `None` to compute it on demand only.
+    pub update_interval: Option<usize>,
+
+    /// Ring buffer with the input samples.
+    in_buffer: Vec<f32>,
+
+    /// Ring buffer with the output samples.
+    out_buffer: Vec<f32>,
+
+    /// Write position in the ring buffers.
+    position: usize,
+
+    /// Number of samples written, up to the buffer length.
+    filled: usize,
+
+    /// Number of samples written since the last computation.
+    since_update: usize,
+
+    /// Response of the last computation.
+    response: Option<FrequencyResponse>,
+}
+
+impl StreamingAnalyzer {
+    /// Returns a new analyzer with buffers of `length` samples.
+    ///
+    /// The length must be at least 2 samples.
+    pub fn new(sample_rate: f32, length: usize) -> Self {
+        assert!(
+            length >= 2,
+            "Buffer length of {length} samples is too short"
+        );
+
+        Self {
+            sample_rate,
+            update_interval: None,
+            in_buffer: vec![0.0; length],
+            out_buffer: vec![0.0; length],
+            position: 0,
+            filled: 0,
+            since_update: 0,
+            response: None,
+        }
+    }
+
+    /// Returns the length of the buffers in samples.
+    pub fn length(&self) -> usize {
+        self.in_buffer.len()
+    }
+
+    /// Returns if the buffers are completely filled, so that a response
+    /// can be computed.
+    pub fn is_filled(&self) -> bool {
+        self.filled == self.length()
+    }
+
+    /// Clears the buffers and the last response.
+    pub fn clear(&mut self) {
+        self.in_buffer.fill(0.0);
+        self.out_buffer.fill(0.0);
+        self.position = 0;
+        self.filled = 0;
+        self.since_update = 0;
+        self.response = None;
+    }
+
+    /// Appends a block of input samples and the corresponding output samples.
+    ///
+    /// Both blocks must have the same length. Once the buffers are filled,
+    /// the response is computed if the update interval has elapsed.
+    pub fn push(&mut self, in_block: &[f32], out_block: &[f32]) {
+        assert_eq!(in_block.len(), out_block.len(), "Blocks differ in length");
+
+        for (in_sample, out_sample) in in_block.iter().zip(out_block.iter()) {
+            self.in_buffer[self.position] = *in_sample;
+            self.out_buffer[self.position] = *out_sample;
+            self.position = (self.position + 1) % self.length();
+        }
+        self.filled = (self.filled + in_block.len()).min(self.length());
+        self.since_update += in_block.len();
+
+        if let Some(interval) = self.update_interval {
+            if self.since_update >= interval {
+                self.compute();
+            }
+        }
+    }
+
+    /// Computes the response from the buffered samples and returns it,
+    /// `None` if the buffers are not filled yet.
+    pub fn compute(&mut self) -> Option<&FrequencyResponse> {
+        if !self.is_filled() {
+            return None;
+        }
+
+        let in_samples = self.ordered(&self.in_buffer);
+        let out_samples = self.ordered(&self.out_buffer);
+        let band = 0.0..self.sample_rate / 2.0;
+
+        let in_spectrum = fft(&in_samples);
+        let mask = excitation_mask(&in_spectrum, &band, self.sample_rate);
+        let transfer = transfer_function(&in_spectrum, &out_samples, &mask);
+
+        self.since_update = 0;
+        self.response = Some(FrequencyResponse::new(
+            self.sample_rate,
+            magnitude_db(&transfer),
+            phase_deg(&transfer),
+            band,
+        ));
+
+        self.response.as_ref()
+    }
+
+    /// Returns the response of the last computation.
+    pub fn response(&self) -> Option<&FrequencyResponse> {
+        self.response.as_ref()
+    }
+
+    /// Returns the samples of a ring buffer from the oldest to the newest.
+    fn ordered(&self, buffer: &[f32]) -> Vec<f32> {
+        let mut samples = buffer[self.position..].to_vec();
+        samples.extend_from_slice(&buffer[..self.position]);
+
+        samples
+    }
+}
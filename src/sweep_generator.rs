@@ -1,6 +1,30 @@
 //! Sine sweep generator.
 
-#![allow(unused)]
+/// Direction of the sweep.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum SweepDirection {
+    /// From the minimum to the maximum frequency.
+    #[default]
+    Up,
+
+    /// From the maximum to the minimum frequency.
+    Down,
+}
+
+/// Progression of the frequency over time.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum SweepMode {
+    /// Frequency changes by a constant amount in Hz per sample, spending
+    /// equal time and energy per Hz.
+    #[default]
+    Linear,
+
+    /// Frequency changes by a constant ratio per sample, spending time
+    /// proportional to `1/f` and thus equal energy per octave.
+    ///
+    /// Requires a minimum frequency above zero.
+    Logarithmic,
+}
 
 /// Sweep generator.
 #[derive(Debug, Default)]
@@ -20,6 +44,9 @@ pub struct SweepGenerator {
     /// Gain.
     gain: f32,
 
+    /// Duration in seconds of the taper at the end of the sweep.
+    end_taper: f32,
+
     /// Current phase.
     phase: f32,
 
@@ -29,9 +56,18 @@ pub struct SweepGenerator {
     /// Current frequency.
     freq: f32,
 
-    /// Frequency increment.
+    /// Frequency increment for a linear sweep.
     freq_inc: f32,
 
+    /// Frequency ratio per sample for a logarithmic sweep.
+    freq_ratio: f32,
+
+    /// Progression of the frequency.
+    mode: SweepMode,
+
+    /// Sweep direction.
+    direction: SweepDirection,
+
     /// Started flag.
     started: bool,
 }
@@ -44,8 +80,23 @@ pub enum SweepError {
 
     /// Maximum frequency reached.
     MaxFreqReached,
+
+    /// Minimum frequency reached by a descending sweep.
+    MinFreqReached,
+}
+
+impl core::fmt::Display for SweepError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::NotStarted => write!(f, "Sweep was not started"),
+            Self::MaxFreqReached => write!(f, "Maximum frequency reached"),
+            Self::MinFreqReached => write!(f, "Minimum frequency reached"),
+        }
+    }
 }
 
+impl std::error::Error for SweepError {}
+
 /// Custom result type.
 pub type Result<T> = core::result::Result<T, SweepError>;
 
@@ -53,33 +104,52 @@ impl SweepGenerator {
     /// Returns a new instance.
     pub fn new(sample_rate: f32) -> Self {
         let min_freq = 20.0;
-        let max_freq = 20000.0;
-        let sweep_time = 1.0;
-        let freq_inc = (max_freq - min_freq) / (sample_rate * sweep_time);
 
-        Self {
+        let mut generator = Self {
             sample_rate,
             min_freq,
-            max_freq,
-            sweep_time,
+            max_freq: 20000.0,
+            sweep_time: 1.0,
             gain: 1.0,
             freq: min_freq,
-            freq_inc,
             ..Default::default()
-        }
+        };
+        generator.update_increments();
+
+        generator
     }
 
     /// Sets the sweep range in Hz.
     pub fn set_range(&mut self, min_freq: f32, max_freq: f32) {
         self.min_freq = min_freq;
         self.max_freq = max_freq;
-        self.freq_inc = (self.max_freq - self.min_freq) / (self.sample_rate * self.sweep_time);
+        self.update_increments();
+    }
+
+    /// Returns the minimum and maximum frequency of the sweep range in Hz.
+    pub fn range(&self) -> (f32, f32) {
+        (self.min_freq, self.max_freq)
     }
 
     /// Sets the sweep time in seconds.
     pub fn set_time(&mut self, sweep_time: f32) {
         self.sweep_time = sweep_time;
-        self.freq_inc = (self.max_freq - self.min_freq) / (self.sample_rate * self.sweep_time);
+        self.update_increments();
+    }
+
+    /// Returns the sweep time in seconds.
+    pub fn time(&self) -> f32 {
+        self.sweep_time
+    }
+
+    /// Sets the progression of the frequency, taking effect on the next start.
+    pub fn set_mode(&mut self, mode: SweepMode) {
+        self.mode = mode;
+    }
+
+    /// Sets the direction, taking effect on the next start.
+    pub fn set_direction(&mut self, direction: SweepDirection) {
+        self.direction = direction;
     }
 
     /// Sets the gain.
@@ -87,26 +157,77 @@ impl SweepGenerator {
         self.gain = gain;
     }
 
-    /// Starts the sweep.
+    /// Returns the gain.
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// Sets the duration in seconds of a raised-cosine taper at the end of
+    /// the sweep, 0 for an abrupt end.
+    ///
+    /// The taper fades the last samples out to zero at the end frequency,
+    /// which avoids the spectral artifacts of the abrupt end at the top of
+    /// the swept band.
+    pub fn set_end_taper(&mut self, time: f32) {
+        self.end_taper = time;
+    }
+
+    /// Returns the current instantaneous frequency in Hz.
+    pub fn current_frequency(&self) -> f32 {
+        self.freq
+    }
+
+    /// Returns the progress of the sweep in the range of 0.0 to 1.0.
+    pub fn progress(&self) -> f32 {
+        if self.max_freq <= self.min_freq {
+            return 1.0;
+        }
+
+        let progress = match self.mode {
+            SweepMode::Linear => (self.freq - self.min_freq) / (self.max_freq - self.min_freq),
+            SweepMode::Logarithmic => {
+                (self.freq / self.min_freq).ln() / (self.max_freq / self.min_freq).ln()
+            }
+        };
+        match self.direction {
+            SweepDirection::Up => progress.clamp(0.0, 1.0),
+            SweepDirection::Down => (1.0 - progress).clamp(0.0, 1.0),
+        }
+    }
+
+    /// Starts the sweep at the minimum frequency, or at the maximum
+    /// frequency for a descending sweep.
     pub fn start(&mut self) {
-        self.freq = self.min_freq;
+        self.freq = match self.direction {
+            SweepDirection::Up => self.min_freq,
+            SweepDirection::Down => self.max_freq,
+        };
         self.phase_inc = core::f32::consts::TAU * self.freq / self.sample_rate;
         self.started = true;
     }
 
     /// Generates a block of samples.
-    pub fn process(&mut self, buffer: &mut [f32]) -> Result<()> {
+    ///
+    /// Returns the number of samples written, which is less than the buffer
+    /// length when the end frequency is reached within the block. The call
+    /// after the last sample returns [`SweepError::MaxFreqReached`], or
+    /// [`SweepError::MinFreqReached`] for a descending sweep, regardless of
+    /// the block size.
+    pub fn process(&mut self, buffer: &mut [f32]) -> Result<usize> {
         if !self.started {
             return Err(SweepError::NotStarted);
         }
 
-        for sample in buffer.iter_mut() {
-            if self.freq > self.max_freq {
-                self.started = false;
-                return Err(SweepError::MaxFreqReached);
+        for (index, sample) in buffer.iter_mut().enumerate() {
+            if let Some(error) = self.end() {
+                if index == 0 {
+                    self.started = false;
+                    return Err(error);
+                }
+                return Ok(index);
             }
 
-            *sample = self.phase.sin() * self.gain;
+            *sample = self.phase.sin() * self.gain * self.taper_gain();
 
             self.phase += self.phase_inc;
 
@@ -116,10 +237,63 @@ impl SweepGenerator {
             }
 
             // Calculate new frequency and phase increment.
-            self.freq += self.freq_inc;
+            match (self.mode, self.direction) {
+                (SweepMode::Linear, SweepDirection::Up) => self.freq += self.freq_inc,
+                (SweepMode::Linear, SweepDirection::Down) => self.freq -= self.freq_inc,
+                (SweepMode::Logarithmic, SweepDirection::Up) => self.freq *= self.freq_ratio,
+                (SweepMode::Logarithmic, SweepDirection::Down) => self.freq /= self.freq_ratio,
+            }
             self.phase_inc = core::f32::consts::TAU * self.freq / self.sample_rate;
         }
 
-        Ok(())
+        Ok(buffer.len())
+    }
+
+    /// Generates a block of samples like [`SweepGenerator::process`], calling
+    /// `on_block` with the current instantaneous frequency in Hz beforehand.
+    ///
+    /// This allows updating the parameters of a processor along with the sweep.
+    /// The hook is not called if the sweep is not running or has reached its
+    /// end frequency.
+    pub fn process_with(
+        &mut self,
+        buffer: &mut [f32],
+        mut on_block: impl FnMut(f32),
+    ) -> Result<usize> {
+        if self.started && self.end().is_none() {
+            on_block(self.freq);
+        }
+
+        self.process(buffer)
+    }
+
+    /// Returns the error to report if the end frequency has been passed.
+    fn end(&self) -> Option<SweepError> {
+        match self.direction {
+            SweepDirection::Up if self.freq > self.max_freq => Some(SweepError::MaxFreqReached),
+            SweepDirection::Down if self.freq < self.min_freq => Some(SweepError::MinFreqReached),
+            _ => None,
+        }
+    }
+
+    /// Returns the gain of the end taper at the current frequency.
+    fn taper_gain(&self) -> f32 {
+        if self.end_taper <= 0.0 {
+            return 1.0;
+        }
+
+        let remaining = (1.0 - self.progress()) * self.sweep_time;
+        if remaining >= self.end_taper {
+            1.0
+        } else {
+            0.5 - 0.5 * (core::f32::consts::PI * remaining / self.end_taper).cos()
+        }
+    }
+
+    /// Updates the frequency increment and ratio from range and time.
+    fn update_increments(&mut self) {
+        let samples = self.sample_rate * self.sweep_time;
+        self.freq_inc = (self.max_freq - self.min_freq) / samples;
+        self.freq_ratio = (self.max_freq / self.min_freq).powf(1.0 / samples);
     }
 }
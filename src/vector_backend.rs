@@ -0,0 +1,426 @@
+//! Drawing backend for PDF and EPS files.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use plotters::prelude::DrawingBackend;
+use plotters_backend::text_anchor::{HPos, VPos};
+use plotters_backend::{
+    BackendColor, BackendCoord, BackendStyle, BackendTextStyle, DrawingErrorKind, FontTransform,
+};
+
+/// Number of segments approximating a circle.
+const CIRCLE_SEGMENTS: usize = 32;
+
+/// Vector file format.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum VectorFormat {
+    /// Portable Document Format.
+    Pdf,
+
+    /// Encapsulated PostScript.
+    Eps,
+}
+
+/// Drawing primitive in backend coordinates.
+enum Shape {
+    /// Open polyline with a color and a line width.
+    Path(Vec<BackendCoord>, BackendColor, u32),
+
+    /// Filled polygon with a color.
+    Polygon(Vec<BackendCoord>, BackendColor),
+
+    /// Text with its baseline origin, a font, a size, a color and
+    /// the rotation in degrees.
+    Text {
+        text: String,
+        pos: BackendCoord,
+        font: &'static str,
+        size: f64,
+        color: BackendColor,
+        rotation: i32,
+    },
+}
+
+/// Drawing backend collecting vector shapes and writing them as
+/// PDF or EPS file when presented.
+///
+/// Text is set in the standard PostScript fonts, so no fonts are embedded.
+/// Transparency is not supported, partially transparent colors are blended
+/// with white as the background of the plots.
+pub(crate) struct VectorBackend {
+    /// Path of the file.
+    path: PathBuf,
+
+    /// File format.
+    format: VectorFormat,
+
+    /// Size in points.
+    size: (u32, u32),
+
+    /// Shapes in drawing order.
+    shapes: Vec<Shape>,
+
+    /// Flag set when the file is written.
+    saved: bool,
+}
+
+impl VectorBackend {
+    /// Returns a new backend writing to a file with a size in points.
+    pub(crate) fn new(path: impl AsRef<Path>, format: VectorFormat, size: (u32, u32)) -> Self {
+        Self {
+            path: path.as_ref().to_owned(),
+            format,
+            size,
+            shapes: Vec::new(),
+            saved: false,
+        }
+    }
+
+    /// Returns the file contents.
+    fn document(&self) -> Vec<u8> {
+        let content = self.content();
+        let (width, height) = self.size;
+
+        match self.format {
+            VectorFormat::Eps => format!(
+                "%!PS-Adobe-3.0 EPSF-3.0\n%%BoundingBox: 0 0 {width} {height}\n\
+                 %%EndComments\n{content}showpage\n%%EOF\n"
+            )
+            .into_bytes(),
+            VectorFormat::Pdf => {
+                let fonts = self.fonts();
+                let font_resources: String = fonts
+                    .iter()
+                    .enumerate()
+                    .map(|(i, _)| format!("/F{i} {} 0 R ", 5 + i))
+                    .collect();
+                let mut objects = vec![
+                    "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+                    "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+                    format!(
+                        "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {width} {height}] \
+                         /Resources << /Font << {font_resources}>> >> /Contents 4 0 R >>"
+                    ),
+                    format!(
+                        "<< /Length {} >>\nstream\n{content}endstream",
+                        content.len()
+                    ),
+                ];
+                objects.extend(
+                    fonts
+                        .iter()
+                        .map(|font| format!("<< /Type /Font /Subtype /Type1 /BaseFont /{font} >>")),
+                );
+
+                let mut document = String::from("%PDF-1.4\n");
+                let mut offsets = Vec::new();
+                for (i, object) in objects.iter().enumerate() {
+                    offsets.push(document.len());
+                    let _ = write!(document, "{} 0 obj\n{object}\nendobj\n", i + 1);
+                }
+                let xref = document.len();
+                let _ = write!(
+                    document,
+                    "xref\n0 {}\n0000000000 65535 f \n",
+                    objects.len() + 1
+                );
+                for offset in offsets {
+                    let _ = writeln!(document, "{offset:010} 00000 n ");
+                }
+                let _ = write!(
+                    document,
+                    "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+                    objects.len() + 1
+                );
+
+                document.into_bytes()
+            }
+        }
+    }
+
+    /// Returns the distinct fonts of the texts in order of first use.
+    fn fonts(&self) -> Vec<&'static str> {
+        let mut fonts = Vec::new();
+        for shape in &self.shapes {
+            if let Shape::Text { font, .. } = shape {
+                if !fonts.contains(font) {
+                    fonts.push(*font);
+                }
+            }
+        }
+
+        fonts
+    }
+
+    /// Returns the drawing operators for all shapes.
+    fn content(&self) -> String {
+        let height = self.size.1 as i32;
+        let fonts = self.fonts();
+        let pdf = self.format == VectorFormat::Pdf;
+        let mut content = String::new();
+
+        for shape in &self.shapes {
+            match shape {
+                Shape::Path(points, color, width) => {
+                    let (r, g, b) = rgb(color);
+                    if pdf {
+                        let _ = write!(content, "{r:.3} {g:.3} {b:.3} RG {width} w ");
+                    } else {
+                        let _ = write!(
+                            content,
+                            "{r:.3} {g:.3} {b:.3} setrgbcolor {width} setlinewidth "
+                        );
+                    }
+                    write_path(&mut content, points, height, pdf);
+                    content.push_str(if pdf { "S\n" } else { "stroke\n" });
+                }
+                Shape::Polygon(points, color) => {
+                    let (r, g, b) = rgb(color);
+                    if pdf {
+                        let _ = write!(content, "{r:.3} {g:.3} {b:.3} rg ");
+                    } else {
+                        let _ = write!(content, "{r:.3} {g:.3} {b:.3} setrgbcolor ");
+                    }
+                    write_path(&mut content, points, height, pdf);
+                    content.push_str(if pdf { "h f\n" } else { "closepath fill\n" });
+                }
+                Shape::Text {
+                    text,
+                    pos,
+                    font,
+                    size,
+                    color,
+                    rotation,
+                } => {
+                    let (r, g, b) = rgb(color);
+                    let (x, y) = (pos.0, height - pos.1);
+                    let text = escape(text);
+                    if pdf {
+                        let index = fonts.iter().position(|f| f == font).unwrap_or(0);
+                        let (sin, cos) = (-*rotation as f64).to_radians().sin_cos();
+                        let _ = writeln!(
+                            content,
+                            "BT {r:.3} {g:.3} {b:.3} rg /F{index} {size:.2} Tf \
+                             {cos:.4} {sin:.4} {:.4} {cos:.4} {x} {y} Tm ({text}) Tj ET",
+                            -sin
+                        );
+                    } else {
+                        let _ = writeln!(
+                            content,
+                            "gsave {r:.3} {g:.3} {b:.3} setrgbcolor /{font} findfont \
+                             {size:.2} scalefont setfont {x} {y} translate {} rotate \
+                             0 0 moveto ({text}) show grestore",
+                            -rotation
+                        );
+                    }
+                }
+            }
+        }
+
+        content
+    }
+}
+
+impl DrawingBackend for VectorBackend {
+    type ErrorType = std::io::Error;
+
+    fn get_size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn ensure_prepared(&mut self) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        Ok(())
+    }
+
+    fn present(&mut self) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        std::fs::write(&self.path, self.document()).map_err(DrawingErrorKind::DrawingError)?;
+        self.saved = true;
+
+        Ok(())
+    }
+
+    fn draw_pixel(
+        &mut self,
+        point: BackendCoord,
+        color: BackendColor,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.draw_rect(point, (point.0 + 1, point.1 + 1), &color, true)
+    }
+
+    fn draw_line<S: BackendStyle>(
+        &mut self,
+        from: BackendCoord,
+        to: BackendCoord,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.draw_path([from, to], style)
+    }
+
+    fn draw_rect<S: BackendStyle>(
+        &mut self,
+        upper_left: BackendCoord,
+        bottom_right: BackendCoord,
+        style: &S,
+        fill: bool,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        let (x0, y0) = upper_left;
+        let (x1, y1) = bottom_right;
+        let corners = [(x0, y0), (x1, y0), (x1, y1), (x0, y1)];
+        if fill {
+            self.fill_polygon(corners, style)
+        } else {
+            self.draw_path(corners.into_iter().chain([(x0, y0)]), style)
+        }
+    }
+
+    fn draw_path<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+        &mut self,
+        path: I,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        let color = style.color();
+        let points: Vec<BackendCoord> = path.into_iter().collect();
+        if color.alpha > 0.0 && points.len() >= 2 {
+            self.shapes
+                .push(Shape::Path(points, color, style.stroke_width()));
+        }
+
+        Ok(())
+    }
+
+    fn draw_circle<S: BackendStyle>(
+        &mut self,
+        center: BackendCoord,
+        radius: u32,
+        style: &S,
+        fill: bool,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        let points: Vec<BackendCoord> = (0..=CIRCLE_SEGMENTS)
+            .map(|i| {
+                let angle = std::f64::consts::TAU * i as f64 / CIRCLE_SEGMENTS as f64;
+                (
+                    center.0 + (radius as f64 * angle.cos()).round() as i32,
+                    center.1 + (radius as f64 * angle.sin()).round() as i32,
+                )
+            })
+            .collect();
+        if fill {
+            self.fill_polygon(points, style)
+        } else {
+            self.draw_path(points, style)
+        }
+    }
+
+    fn fill_polygon<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+        &mut self,
+        vert: I,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        let color = style.color();
+        let points: Vec<BackendCoord> = vert.into_iter().collect();
+        if color.alpha > 0.0 && points.len() >= 3 {
+            self.shapes.push(Shape::Polygon(points, color));
+        }
+
+        Ok(())
+    }
+
+    fn draw_text<TStyle: BackendTextStyle>(
+        &mut self,
+        text: &str,
+        style: &TStyle,
+        pos: BackendCoord,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        let color = style.color();
+        if color.alpha == 0.0 {
+            return Ok(());
+        }
+
+        // Anchors are resolved as by the SVG backend, using the layout
+        // of the font for the width.
+        let size = style.size() / 1.24;
+        let ((min_x, _), (max_x, _)) = style
+            .layout_box(text)
+            .map_err(|e| DrawingErrorKind::FontError(Box::new(e)))?;
+        let width = (max_x - min_x) as f64;
+        let dx = match style.anchor().h_pos {
+            HPos::Left => 0.0,
+            HPos::Center => -width / 2.0,
+            HPos::Right => -width,
+        };
+        let dy = match style.anchor().v_pos {
+            VPos::Top => 0.76 * size,
+            VPos::Center => 0.25 * size,
+            VPos::Bottom => -0.25 * size,
+        };
+        let rotation = match style.transform() {
+            FontTransform::None => 0,
+            FontTransform::Rotate90 => 90,
+            FontTransform::Rotate180 => 180,
+            FontTransform::Rotate270 => 270,
+        };
+        let (sin, cos) = (rotation as f64).to_radians().sin_cos();
+        let offset = (dx * cos - dy * sin, dx * sin + dy * cos);
+
+        self.shapes.push(Shape::Text {
+            text: text.to_string(),
+            pos: (
+                pos.0 + offset.0.round() as i32,
+                pos.1 + offset.1.round() as i32,
+            ),
+            font: match style.family().as_str() {
+                "serif" => "Times-Roman",
+                "monospace" => "Courier",
+                _ => "Helvetica",
+            },
+            size,
+            color,
+            rotation,
+        });
+
+        Ok(())
+    }
+}
+
+impl Drop for VectorBackend {
+    fn drop(&mut self) {
+        if !self.saved {
+            // Fallback if the drawing was not presented. Drop must not panic,
+            // so a failed write is ignored.
+            let _ = self.present();
+        }
+    }
+}
+
+/// Returns the color components in the range of 0.0 to 1.0, blended with white.
+fn rgb(color: &BackendColor) -> (f64, f64, f64) {
+    let alpha = color.alpha.clamp(0.0, 1.0);
+    let blend = |c: u8| c as f64 / 255.0 * alpha + 1.0 - alpha;
+    (blend(color.rgb.0), blend(color.rgb.1), blend(color.rgb.2))
+}
+
+/// Writes the operators of a path, flipping the Y axis.
+fn write_path(content: &mut String, points: &[BackendCoord], height: i32, pdf: bool) {
+    let (move_to, line_to) = if pdf {
+        ("m", "l")
+    } else {
+        ("moveto", "lineto")
+    };
+    for (i, (x, y)) in points.iter().enumerate() {
+        let op = if i == 0 { move_to } else { line_to };
+        let _ = write!(content, "{x} {} {op} ", height - y);
+    }
+}
+
+/// Returns the text as string literal content, replacing characters
+/// outside of ASCII by `?`.
+fn escape(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '(' | ')' | '\\' => format!("\\{c}"),
+            c if c.is_ascii() && !c.is_ascii_control() => c.to_string(),
+            _ => "?".to_string(),
+        })
+        .collect()
+}
@@ -0,0 +1,65 @@
+//! Reader for WAV files
+
+use std::fs::File;
+
+/// Reads all channels of a WAV file.
+///
+/// Returns the sample rate in Hz and the samples of each channel, converted
+/// to `f32` in the range of -1.0 to 1.0. Integer formats with 8, 16 and 24
+/// bits and the 32-bit float format are supported.
+pub fn read_all_channels(
+    filename: impl AsRef<std::path::Path>,
+) -> std::io::Result<(u32, Vec<Vec<f32>>)> {
+    let mut file = File::open(filename)?;
+    let (header, data) = wav::read(&mut file)?;
+
+    let samples: Vec<f32> = match data {
+        wav::BitDepth::Eight(data) => data
+            .iter()
+            .map(|sample| (*sample as f32 - 128.0) / 128.0)
+            .collect(),
+        wav::BitDepth::Sixteen(data) => {
+            data.iter().map(|sample| *sample as f32 / 32768.0).collect()
+        }
+        wav::BitDepth::TwentyFour(data) => data
+            .iter()
+            .map(|sample| *sample as f32 / 8388608.0)
+            .collect(),
+        wav::BitDepth::ThirtyTwoFloat(data) => data,
+        wav::BitDepth::Empty => Vec::new(),
+    };
+
+    let channel_count = header.channel_count.max(1) as usize;
+    let channels = (0..channel_count)
+        .map(|channel| {
+            samples
+                .iter()
+                .skip(channel)
+                .step_by(channel_count)
+                .copied()
+                .collect()
+        })
+        .collect();
+
+    Ok((header.sampling_rate, channels))
+}
+
+/// Reads a single channel of a WAV file, e.g. the reference or the
+/// measurement of a multichannel recording.
+///
+/// Returns the sample rate in Hz and the samples as for [`read_all_channels`],
+/// or an error if the file has no channel with the index.
+pub fn read_channel(
+    filename: impl AsRef<std::path::Path>,
+    channel: usize,
+) -> std::io::Result<(u32, Vec<f32>)> {
+    let (sample_rate, mut channels) = read_all_channels(filename)?;
+    if channel >= channels.len() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("No channel {channel} in {} channels", channels.len()),
+        ));
+    }
+
+    Ok((sample_rate, channels.swap_remove(channel)))
+}
@@ -8,13 +8,52 @@ pub fn write(
     filename: impl AsRef<std::path::Path> + core::fmt::Display,
     sample_rate: u32,
     data: &[f32],
+) -> std::io::Result<()> {
+    write_interleaved(filename, sample_rate, 1, Vec::from(data))
+}
+
+/// Writes one slice of sample data per channel as WAV file in 32-bit float format.
+///
+/// At least one channel must be given and all channels must have the same length.
+pub fn write_channels(
+    filename: impl AsRef<std::path::Path> + core::fmt::Display,
+    sample_rate: u32,
+    channels: &[&[f32]],
+) -> std::io::Result<()> {
+    let Some(length) = channels.first().map(|channel| channel.len()) else {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "No channels",
+        ));
+    };
+    if channels.iter().any(|channel| channel.len() != length) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "Channels differ in length",
+        ));
+    }
+
+    let data: Vec<f32> = (0..length)
+        .flat_map(|index| channels.iter().map(move |channel| channel[index]))
+        .collect();
+
+    write_interleaved(filename, sample_rate, channels.len() as u16, data)
+}
+
+/// Writes interleaved sample data of a number of channels as WAV file in
+/// 32-bit float format, creating its directory if needed.
+fn write_interleaved(
+    filename: impl AsRef<std::path::Path> + core::fmt::Display,
+    sample_rate: u32,
+    channel_count: u16,
+    data: Vec<f32>,
 ) -> std::io::Result<()> {
     let path = format!("{filename}");
     let path = Path::new(path.as_str());
     let parent = path.parent().unwrap();
     std::fs::create_dir_all(parent).ok();
     let mut file = File::create(path)?;
-    let header = wav::Header::new(wav::WAV_FORMAT_IEEE_FLOAT, 1, sample_rate, 32);
-    wav::write(header, &wav::BitDepth::from(Vec::from(data)), &mut file)?;
+    let header = wav::Header::new(wav::WAV_FORMAT_IEEE_FLOAT, channel_count, sample_rate, 32);
+    wav::write(header, &wav::BitDepth::from(data), &mut file)?;
     Ok(())
 }
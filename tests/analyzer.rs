@@ -0,0 +1,1003 @@
+//! Tests for the analyzer.
+
+use dsp_analyze::*;
+
+/// Sample rate in Hz.
+const SAMPLE_RATE: f32 = 48000.0;
+
+/// Block size in samples.
+const BLOCK_SIZE: usize = 16;
+
+#[test]
+fn constructors() {
+    let analyzer = FftAnalyzer::with_sample_rate(44100.0);
+    assert_eq!(analyzer.config.sample_rate, 44100.0);
+    assert_eq!(
+        analyzer.config.block_size,
+        FftAnalyzerConfig::default().block_size
+    );
+
+    let mut analyzer = FftAnalyzer::with_block_size(BLOCK_SIZE);
+    assert_eq!(analyzer.config.block_size, BLOCK_SIZE);
+    assert_eq!(analyzer.config.sample_rate, SAMPLE_RATE);
+    analyzer
+        .run(|in_samples, _| assert_eq!(in_samples.len(), BLOCK_SIZE))
+        .unwrap();
+}
+
+#[test]
+fn frequency_resolution() {
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        test_signal: TestSignal::Sweep,
+        ..Default::default()
+    });
+    let length = analyzer.fft_length();
+    analyzer.run(|_, _| {}).unwrap();
+    assert_eq!(analyzer.fft_length(), length);
+    assert_eq!(analyzer.fft_length(), analyzer.in_samples.len());
+
+    let bins = analyzer.spectrum_magnitude.len();
+    assert_eq!(bins, analyzer.fft_length() / 2 + 1);
+    let nyquist = analyzer.frequency_resolution() * (bins - 1) as f32;
+    assert!((nyquist - SAMPLE_RATE / 2.0).abs() < 1.0, "{nyquist}Hz");
+
+    analyzer
+        .run_with_signal(vec![1.0; 4800], |_, _| {})
+        .unwrap();
+    assert_eq!(analyzer.frequency_resolution(), 10.0);
+}
+
+#[test]
+fn run_with_signal() {
+    let mut input = vec![0.0; SAMPLE_RATE as usize];
+    input[0] = 1.0;
+    input[1] = 0.3;
+
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    analyzer
+        .run_with_signal(input, |_, out_samples| {
+            for sample in out_samples.iter_mut() {
+                *sample *= 0.5;
+            }
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude("Custom signal", "out/analyzer/custom_signal_mag.svg")
+        .unwrap();
+
+    for magnitude in analyzer.spectrum_magnitude.iter() {
+        assert!((magnitude + 6.02).abs() < 0.01);
+    }
+}
+
+#[test]
+fn run_with_empty_signal() {
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig::default());
+    let result = analyzer.run_with_signal(Vec::new(), |_, _| {});
+
+    assert!(matches!(result, Err(AnalyzerError::EmptySignal)));
+}
+
+#[test]
+fn non_finite_output() {
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+
+    // One-pole filter with its pole outside the unit circle.
+    let mut state = 0.0f32;
+    let result = analyzer.run(|_, out_samples| {
+        for sample in out_samples.iter_mut() {
+            state = *sample + 2.0 * state;
+            *sample = state;
+        }
+    });
+
+    // The impulse response 2^n exceeds the range of f32 after 128 samples.
+    match result {
+        Err(AnalyzerError::NonFiniteOutput(index, value)) => {
+            assert_eq!(index, 128);
+            assert_eq!(value, f32::INFINITY);
+        }
+        result => panic!("Unexpected result {result:?}"),
+    }
+    assert!(result.unwrap_err().to_string().contains("128"));
+    assert!(analyzer.spectrum_magnitude.is_empty());
+}
+
+#[test]
+fn non_finite_output_multiple_runs() {
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    let unstable = || {
+        let mut state = 0.0f32;
+        move |_: &[f32], out_samples: &mut [f32]| {
+            for sample in out_samples.iter_mut() {
+                state = *sample + 2.0 * state;
+                *sample = state;
+            }
+        }
+    };
+    let check = |result: Result<(), AnalyzerError>| match result {
+        Err(AnalyzerError::NonFiniteOutput(index, value)) => {
+            assert_eq!(index, 128);
+            assert_eq!(value, f32::INFINITY);
+        }
+        result => panic!("Unexpected result {result:?}"),
+    };
+
+    let funcs: Vec<Processor> = vec![Box::new(|_, _| {}), Box::new(unstable())];
+    check(analyzer.run_many(funcs).map(|_| ()));
+
+    let stages: Vec<Processor> = vec![Box::new(unstable()), Box::new(|_, _| {})];
+    check(analyzer.run_stages(stages).map(|_| ()));
+    assert!(analyzer.out_samples[128].is_infinite());
+    assert!(analyzer.spectrum_magnitude.is_empty());
+
+    check(analyzer.run_with_hold(2, unstable()).map(|_| ()));
+    assert!(analyzer.out_samples[128].is_infinite());
+    assert!(analyzer.spectrum_magnitude.is_empty());
+}
+
+#[test]
+fn run_many() {
+    let gains = [1.0, 0.5, 0.25];
+
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    let funcs: Vec<Processor> = gains
+        .iter()
+        .map(|&gain| {
+            Box::new(move |_: &[f32], out_samples: &mut [f32]| {
+                for sample in out_samples.iter_mut() {
+                    *sample *= gain;
+                }
+            }) as Processor
+        })
+        .collect();
+    let results = analyzer.run_many(funcs).unwrap();
+    assert_eq!(results.len(), gains.len());
+
+    for (&gain, response) in gains.iter().zip(results.iter()) {
+        analyzer
+            .run(|_, out_samples| {
+                for sample in out_samples.iter_mut() {
+                    *sample *= gain;
+                }
+            })
+            .unwrap();
+        assert_eq!(response, &analyzer.response());
+    }
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn par_run_many() {
+    let coeffs = [0.1, 0.5, 0.9];
+
+    // One-pole lowpass filters with their state moved into the closures.
+    let one_pole = |coeff: f32| {
+        let mut state = 0.0f32;
+        move |_: &[f32], out_samples: &mut [f32]| {
+            for sample in out_samples.iter_mut() {
+                state += coeff * (*sample - state);
+                *sample = state;
+            }
+        }
+    };
+
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    let funcs: Vec<Processor> = coeffs
+        .iter()
+        .map(|&coeff| Box::new(one_pole(coeff)) as Processor)
+        .collect();
+    let serial = analyzer.run_many(funcs).unwrap();
+    let funcs: Vec<SendProcessor> = coeffs
+        .iter()
+        .map(|&coeff| Box::new(one_pole(coeff)) as SendProcessor)
+        .collect();
+    let parallel = analyzer.par_run_many(funcs).unwrap();
+    assert_eq!(parallel, serial);
+
+    let funcs: Vec<SendProcessor> = vec![
+        Box::new(one_pole(0.5)),
+        Box::new(|_: &[f32], out_samples: &mut [f32]| out_samples[7] = f32::NAN),
+    ];
+    match analyzer.par_run_many(funcs) {
+        Err(AnalyzerError::NonFiniteOutput(index, _)) => assert_eq!(index, 7),
+        result => panic!("Unexpected result {result:?}"),
+    }
+}
+
+#[test]
+fn sweep_valid_band() {
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        test_signal: TestSignal::Sweep,
+        ..Default::default()
+    });
+    analyzer
+        .run(|_, out_samples| {
+            for sample in out_samples.iter_mut() {
+                *sample *= 0.5;
+            }
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude("Sweep", "out/analyzer/sweep_mag.svg")
+        .unwrap();
+    analyzer
+        .plot_phase("Sweep", "out/analyzer/sweep_phase.svg")
+        .unwrap();
+
+    assert_eq!(analyzer.valid_band, 1.0..20000.0);
+
+    let bin_width = SAMPLE_RATE / 2.0 / (analyzer.spectrum_magnitude.len() - 1) as f32;
+    for (i, (magnitude, phase)) in analyzer
+        .spectrum_magnitude
+        .iter()
+        .zip(analyzer.spectrum_phase.iter())
+        .enumerate()
+    {
+        assert!(magnitude.is_finite() && phase.is_finite());
+        let freq = i as f32 * bin_width;
+        if freq > 20000.0 {
+            assert_eq!(*magnitude, -100.0);
+        } else if freq > 20.0 && freq < 19000.0 {
+            assert!((magnitude + 6.02).abs() < 0.01, "{freq}Hz: {magnitude}dB");
+        }
+    }
+}
+
+#[test]
+fn complex_single_sideband() {
+    let mut analyzer = ComplexFftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+
+    // Real processors have a response symmetric around DC.
+    analyzer
+        .run(|_, out_samples| {
+            for sample in out_samples.iter_mut() {
+                *sample *= 0.5;
+            }
+        })
+        .unwrap();
+    assert_eq!(analyzer.spectrum_magnitude.len(), SAMPLE_RATE as usize);
+    assert_eq!(analyzer.frequencies()[0], -SAMPLE_RATE / 2.0);
+    assert_eq!(analyzer.frequencies()[SAMPLE_RATE as usize / 2], 0.0);
+    for freq in [1000.0, 6000.0, 20000.0] {
+        assert!((analyzer.magnitude_at(freq) + 6.02).abs() < 0.01);
+        assert!((analyzer.magnitude_at(-freq) + 6.02).abs() < 0.01);
+    }
+
+    // One-pole lowpass shifted to 6kHz, passing positive frequencies only.
+    let pole = Complex::from_polar(0.9, std::f32::consts::TAU * 6000.0 / SAMPLE_RATE);
+    let mut state = Complex::new(0.0, 0.0);
+    analyzer
+        .run(|_, out_samples| {
+            for sample in out_samples.iter_mut() {
+                state = *sample * 0.1 + pole * state;
+                *sample = state;
+            }
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude("Complex resonator 6kHz", "out/analyzer/complex_mag.svg")
+        .unwrap();
+    analyzer
+        .plot_phase("Complex resonator 6kHz", "out/analyzer/complex_phase.svg")
+        .unwrap();
+    let svg = std::fs::read_to_string("out/analyzer/complex_mag.svg").unwrap();
+    assert!(svg.contains("Magnitude"));
+
+    let upper = analyzer.magnitude_at(6000.0);
+    let lower = analyzer.magnitude_at(-6000.0);
+    assert!(upper.abs() < 0.01, "{upper}dB");
+    assert!((lower + 22.6).abs() < 0.1, "{lower}dB");
+    assert!(analyzer.phase_at(6000.0).abs() < 0.01);
+}
+
+#[test]
+fn time_domain() {
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        test_signal: TestSignal::Sweep,
+        ..Default::default()
+    });
+    analyzer
+        .run(|_, out_samples| {
+            for sample in out_samples.iter_mut() {
+                *sample *= 0.5;
+            }
+        })
+        .unwrap();
+
+    // The sweep lasts one second, padded to full blocks.
+    let samples: Vec<(f32, f32, f32)> = analyzer.time_domain().collect();
+    assert_eq!(samples.len(), analyzer.in_samples.len());
+    assert_eq!(samples[0].0, 0.0);
+    let end = samples.last().unwrap().0;
+    assert!(
+        (end - 1.0).abs() < BLOCK_SIZE as f32 / SAMPLE_RATE,
+        "{end}s"
+    );
+    assert!(samples.windows(2).all(|pair| pair[1].0 > pair[0].0));
+    assert!(samples
+        .iter()
+        .all(|(_, input, output)| *output == 0.5 * input));
+
+    let filename = "out/analyzer/time_domain.csv";
+    let _ = std::fs::remove_file(filename);
+    analyzer
+        .run_with_signal(vec![1.0, 0.5, 0.25, 0.0], |_, _| {})
+        .unwrap();
+    analyzer.save_time_domain_csv(filename);
+    let csv = std::fs::read_to_string(filename).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "time,input,output");
+    assert_eq!(lines.len(), analyzer.in_samples.len() + 1);
+    assert_eq!(lines[2], format!("{},0.5,0.5", 1.0 / SAMPLE_RATE));
+}
+
+#[test]
+fn session_threads() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<AnalyzerSession>();
+
+    let config = FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        test_signal: TestSignal::Sweep,
+        ..Default::default()
+    };
+    let session = std::sync::Arc::new(AnalyzerSession::new(config.clone()));
+    let gains = [1.0, 0.5, 0.25, 0.125];
+
+    let results: Vec<SpectrumResult> = std::thread::scope(|scope| {
+        let handles: Vec<_> = gains
+            .iter()
+            .map(|&gain| {
+                let session = session.clone();
+                scope.spawn(move || {
+                    session
+                        .analyze(|_, out_samples| {
+                            for sample in out_samples.iter_mut() {
+                                *sample *= gain;
+                            }
+                        })
+                        .unwrap()
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+
+    let mut analyzer = FftAnalyzer::new(config);
+    for (&gain, result) in gains.iter().zip(results.iter()) {
+        analyzer
+            .run(|_, out_samples| {
+                for sample in out_samples.iter_mut() {
+                    *sample *= gain;
+                }
+            })
+            .unwrap();
+        assert_eq!(result.magnitude, analyzer.spectrum_magnitude);
+        assert_eq!(result.phase, analyzer.spectrum_phase);
+        assert_eq!(result.out_samples, analyzer.out_samples);
+    }
+    assert_eq!(session.valid_band(), analyzer.valid_band);
+
+    let result = session.analyze(|_, out_samples| out_samples[3] = f32::NAN);
+    match result {
+        Err(AnalyzerError::NonFiniteOutput(index, value)) => {
+            assert_eq!(index, 3);
+            assert!(value.is_nan());
+        }
+        result => panic!("Unexpected result {result:?}"),
+    }
+}
+
+#[test]
+fn delay_compensation() {
+    for compensation in [DelayCompensation::Samples(10.0), DelayCompensation::Auto] {
+        let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+            block_size: BLOCK_SIZE,
+            delay_compensation: compensation,
+            ..Default::default()
+        });
+        let mut delay_line = std::collections::VecDeque::from(vec![0.0; 10]);
+        analyzer
+            .run(|_, out_samples| {
+                for sample in out_samples.iter_mut() {
+                    delay_line.push_back(*sample * 0.5);
+                    *sample = delay_line.pop_front().unwrap();
+                }
+            })
+            .unwrap();
+        analyzer
+            .plot_phase("Delay compensation", "out/analyzer/delay_phase.svg")
+            .unwrap();
+
+        for phase in analyzer.spectrum_phase.iter() {
+            assert!(phase.abs() < 0.1, "{compensation:?}: {phase}°");
+        }
+    }
+}
+
+#[test]
+fn real_edge_bins() {
+    // Symmetric FIR with a gain of 0.5 at DC and -1.5 at Nyquist.
+    let taps = [-0.25, 1.0, -0.25];
+
+    for test_signal in [TestSignal::Impulse, TestSignal::Mls { order: 15 }] {
+        let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+            block_size: BLOCK_SIZE,
+            test_signal,
+            ..Default::default()
+        });
+        let mut history = [0.0; 3];
+        analyzer
+            .run(|in_samples, out_samples| {
+                for (input, output) in in_samples.iter().zip(out_samples.iter_mut()) {
+                    history = [*input, history[0], history[1]];
+                    *output = taps.iter().zip(history.iter()).map(|(a, b)| a * b).sum();
+                }
+            })
+            .unwrap();
+
+        let nyquist = analyzer.spectrum_phase.len() - 1;
+        assert_eq!(analyzer.spectrum_phase[0], 0.0);
+        assert_eq!(analyzer.spectrum_phase[nyquist].abs(), 180.0);
+    }
+}
+
+#[test]
+fn trim_leading_silence() {
+    let config = FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        test_signal: TestSignal::Mls { order: 15 },
+        ..Default::default()
+    };
+    let lowpass = || {
+        let mut state = 0.0;
+        move |_: &[f32], out_samples: &mut [f32]| {
+            for sample in out_samples.iter_mut() {
+                state += 0.2 * (*sample - state);
+                *sample = state;
+            }
+        }
+    };
+
+    let mut original = FftAnalyzer::new(config.clone());
+    original.run(lowpass()).unwrap();
+
+    let silence = 10 * BLOCK_SIZE;
+    let mut recording = vec![0.0; silence];
+    recording.extend_from_slice(&original.in_samples);
+    let mut analyzer = FftAnalyzer::new(config);
+    analyzer.run_with_signal(recording, lowpass()).unwrap();
+
+    assert_eq!(analyzer.trim_leading_silence(-60.0).unwrap(), silence);
+    assert_eq!(analyzer.in_samples, original.in_samples);
+    assert_eq!(analyzer.out_samples.len(), original.out_samples.len());
+    for freq in [100.0, 1000.0, 10000.0] {
+        assert!((analyzer.magnitude_at(freq) - original.magnitude_at(freq)).abs() < 0.01);
+        assert!((analyzer.phase_at(freq) - original.phase_at(freq)).abs() < 0.1);
+    }
+
+    // Nothing left to trim.
+    assert_eq!(analyzer.trim_leading_silence(-60.0).unwrap(), 0);
+    assert!(analyzer.trim_leading_silence(10.0).is_err());
+    assert_eq!(analyzer.in_samples.len(), original.in_samples.len());
+}
+
+#[test]
+fn shade_invalid_band() {
+    let plot = |shade_invalid_band, filename| {
+        let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+            block_size: BLOCK_SIZE,
+            test_signal: TestSignal::Sweep,
+            shade_invalid_band,
+            ..Default::default()
+        });
+        analyzer.run(|_, _| {}).unwrap();
+        analyzer
+            .plot_magnitude("Sweep 1Hz-20kHz", filename)
+            .unwrap();
+        std::fs::read_to_string(filename).unwrap()
+    };
+
+    let svg = plot(false, "out/analyzer/sweep_unshaded_mag.svg");
+    assert!(!svg.contains("#A0A0A0"));
+
+    // The regions below 1Hz and above 20kHz are shaded, the latter is wider
+    // on the axis up to 48kHz.
+    let svg = plot(true, "out/analyzer/sweep_shaded_mag.svg");
+    let widths: Vec<u32> = svg
+        .lines()
+        .filter(|line| line.starts_with("<rect") && line.contains("#A0A0A0"))
+        .map(|line| {
+            let width = &line[line.find("width=\"").unwrap() + 7..];
+            width[..width.find('"').unwrap()].parse().unwrap()
+        })
+        .collect();
+    assert_eq!(widths.len(), 2, "{widths:?}");
+    assert!(widths[0] > 0 && widths[1] > widths[0], "{widths:?}");
+}
+
+#[test]
+fn full_blocks() {
+    let block_size = 100;
+
+    for allow_short_blocks in [false, true] {
+        let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+            block_size,
+            allow_short_blocks,
+            test_signal: TestSignal::Mls { order: 12 },
+            ..Default::default()
+        });
+        let mut block_sizes = Vec::new();
+        analyzer
+            .run(|in_samples, out_samples| {
+                assert_eq!(in_samples.len(), out_samples.len());
+                block_sizes.push(out_samples.len());
+            })
+            .unwrap();
+
+        let short_blocks = block_sizes.iter().filter(|&&len| len != block_size).count();
+        if allow_short_blocks {
+            assert_ne!(analyzer.in_samples.len() % block_size, 0);
+            assert_eq!(short_blocks, 1);
+        } else {
+            assert_eq!(analyzer.in_samples.len() % block_size, 0);
+            assert_eq!(short_blocks, 0);
+        }
+    }
+}
+
+#[test]
+fn title_font() {
+    let filename = "out/analyzer/title_font_mag.svg";
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        title_font: ("serif".to_string(), 24),
+        ..Default::default()
+    });
+    analyzer.run(|_, _| {}).unwrap();
+    analyzer
+        .plot_magnitude("High shelf first order 1kHz Q=0.707", filename)
+        .unwrap();
+
+    let svg = std::fs::read_to_string(filename).unwrap();
+    assert!(svg.contains("font-family=\"serif\""));
+}
+
+#[test]
+fn magnitude_scale() {
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        magnitude_scale: MagnitudeScale::Linear,
+        ..Default::default()
+    });
+    analyzer.run(|_, _| {}).unwrap();
+    analyzer
+        .plot_magnitude("Bypass linear", "out/analyzer/bypass_linear_mag.svg")
+        .unwrap();
+
+    for magnitude in analyzer.magnitude() {
+        assert!((magnitude - 1.0).abs() < 1e-4, "{magnitude}");
+    }
+
+    analyzer.config.magnitude_scale = MagnitudeScale::DbRelative(6.0);
+    for (relative, magnitude) in analyzer
+        .magnitude()
+        .iter()
+        .zip(analyzer.spectrum_magnitude.iter())
+    {
+        assert!((relative - magnitude + 6.0).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn double_precision() {
+    // Two-pole resonator at 1kHz with a Q of about 65.
+    let radius = 0.999f64;
+    let theta = std::f64::consts::TAU * 1000.0 / SAMPLE_RATE as f64;
+    let (a1, a2) = (2.0 * radius * theta.cos(), -radius * radius);
+
+    // Returns the maximum deviation from the exact magnitude in dB.
+    let max_error = |magnitude: &[f64]| {
+        let bin_width = SAMPLE_RATE as f64 / 2.0 / (magnitude.len() - 1) as f64;
+        magnitude
+            .iter()
+            .enumerate()
+            .skip(20)
+            .take(20000)
+            .map(|(i, measured)| {
+                let w = std::f64::consts::TAU * i as f64 * bin_width / SAMPLE_RATE as f64;
+                let re = 1.0 - a1 * w.cos() - a2 * (2.0 * w).cos();
+                let im = a1 * w.sin() + a2 * (2.0 * w).sin();
+                let expected = -10.0 * (re * re + im * im).log10();
+                (measured - expected).abs()
+            })
+            .fold(0.0, f64::max)
+    };
+
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    let (mut y1, mut y2) = (0.0f32, 0.0f32);
+    analyzer
+        .run(|_, out_samples| {
+            for sample in out_samples.iter_mut() {
+                let y = *sample + a1 as f32 * y1 + a2 as f32 * y2;
+                (y2, y1) = (y1, y);
+                *sample = y;
+            }
+        })
+        .unwrap();
+    let magnitude: Vec<f64> = analyzer
+        .spectrum_magnitude
+        .iter()
+        .map(|v| *v as f64)
+        .collect();
+    let error_32 = max_error(&magnitude);
+
+    let mut analyzer = FftAnalyzer64::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    let (mut y1, mut y2) = (0.0f64, 0.0f64);
+    analyzer
+        .run(|_, out_samples| {
+            for sample in out_samples.iter_mut() {
+                let y = *sample + a1 * y1 + a2 * y2;
+                (y2, y1) = (y1, y);
+                *sample = y;
+            }
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude("Resonator 1kHz f64", "out/analyzer/resonator64_mag.svg")
+        .unwrap();
+    let error_64 = max_error(&analyzer.spectrum_magnitude);
+
+    assert!(error_64 < error_32 / 100.0);
+}
+
+#[test]
+fn excitation_noise() {
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        excitation_noise_db: Some(-40.0),
+        ..Default::default()
+    });
+
+    // The noise is part of the excitation, so a bypass is still flat.
+    analyzer.run(|_, _| {}).unwrap();
+    let noise_peak = analyzer.in_samples[1..]
+        .iter()
+        .fold(0.0f32, |peak, v| peak.max(v.abs()));
+    assert!(noise_peak > 0.009 && noise_peak <= 0.01, "{noise_peak}");
+    for magnitude in analyzer.spectrum_magnitude.iter() {
+        assert!(magnitude.abs() < 0.01);
+    }
+
+    // A gate below the impulse level removes the noise, which shows up
+    // as deviation from the flat response.
+    analyzer
+        .run(|_, out_samples| {
+            for sample in out_samples.iter_mut() {
+                if sample.abs() < 0.05 {
+                    *sample = 0.0;
+                }
+            }
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude("Noise gate", "out/analyzer/noise_gate_mag.svg")
+        .unwrap();
+    let deviation = analyzer
+        .spectrum_magnitude
+        .iter()
+        .fold(0.0f32, |max, v| max.max(v.abs()));
+    assert!(deviation > 3.0, "{deviation}dB");
+}
+
+#[test]
+fn fft_normalization() {
+    // Sine of amplitude 0.5 at 1kHz, on bin 1000 of 48000 samples.
+    let sine: Vec<f32> = (0..SAMPLE_RATE as usize)
+        .map(|i| 0.5 * (std::f32::consts::TAU * 1000.0 * i as f32 / SAMPLE_RATE).sin())
+        .collect();
+    let length = sine.len() as f32;
+
+    for (normalization, expected) in [
+        (FftNormalization::Unitary, 0.25 * length.sqrt()),
+        (FftNormalization::Forward, 0.25),
+        (FftNormalization::None, 0.25 * length),
+    ] {
+        let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+            block_size: BLOCK_SIZE,
+            fft_normalization: normalization,
+            ..Default::default()
+        });
+        analyzer.run_with_signal(sine.clone(), |_, _| {}).unwrap();
+
+        for spectrum in [analyzer.input_spectrum(), analyzer.output_spectrum()] {
+            let magnitude = spectrum[1000];
+            assert!(
+                (magnitude / expected - 1.0).abs() < 1e-3,
+                "{normalization:?}: {magnitude} vs {expected}"
+            );
+        }
+
+        // The transfer function does not depend on the convention.
+        assert!(analyzer.spectrum_magnitude[1000].abs() < 0.01);
+    }
+}
+
+#[test]
+fn psd_welch() {
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    let mut generator = NoiseGenerator::new(1);
+    analyzer
+        .run(|_, out_samples| {
+            generator.process(out_samples);
+        })
+        .unwrap();
+
+    // Uniform noise within ±1.0 has a power of 1/3, spread over Nyquist.
+    let expected = 10.0 * (1.0 / 3.0 / (SAMPLE_RATE / 2.0)).log10();
+    let variance = |segment_len| {
+        let psd = analyzer.psd_welch(segment_len, segment_len / 2);
+        assert_eq!(psd.len(), segment_len / 2 + 1);
+
+        let bins = &psd[1..psd.len() - 1];
+        let mean = bins.iter().sum::<f32>() / bins.len() as f32;
+        assert!(
+            (mean - expected).abs() < 1.0,
+            "{mean}dB/Hz vs {expected}dB/Hz"
+        );
+
+        bins.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / bins.len() as f32
+    };
+
+    let few_segments = variance(8192);
+    let many_segments = variance(512);
+    assert!(
+        many_segments < few_segments / 4.0,
+        "{many_segments} vs {few_segments}"
+    );
+}
+
+#[test]
+fn magnitude_difference() {
+    let run = |gain: f32| {
+        let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+            block_size: BLOCK_SIZE,
+            ..Default::default()
+        });
+        analyzer
+            .run(|_, out_samples| {
+                for sample in out_samples.iter_mut() {
+                    *sample *= gain;
+                }
+            })
+            .unwrap();
+        analyzer
+    };
+
+    let analyzer = run(0.5);
+    analyzer
+        .plot_magnitude_difference(
+            &run(0.5),
+            "Difference to itself",
+            "out/analyzer/difference_self_mag.svg",
+        )
+        .unwrap();
+    assert!(analyzer
+        .magnitude_difference(&run(0.5))
+        .iter()
+        .all(|difference| *difference == 0.0));
+
+    for difference in analyzer.magnitude_difference(&run(1.0)) {
+        assert!((difference + 6.02).abs() < 0.01);
+    }
+}
+
+#[test]
+fn spectrogram() {
+    const STEP: usize = 6000;
+    const FFT_SIZE: usize = 1024;
+
+    // Sine centered on a bin, rising by 10dB every step from -70dB to 0dB.
+    let freq = 20.0 * SAMPLE_RATE / FFT_SIZE as f32;
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    let mut index = 0;
+    analyzer
+        .run(|_, out_samples| {
+            for sample in out_samples.iter_mut() {
+                let level = -70.0 + 10.0 * (index / STEP) as f32;
+                let phase = std::f32::consts::TAU * freq * index as f32 / SAMPLE_RATE;
+                *sample = 10.0f32.powf(level / 20.0) * phase.sin();
+                index += 1;
+            }
+        })
+        .unwrap();
+    assert_eq!(analyzer.out_samples.len(), 8 * STEP);
+
+    let frames = analyzer.spectrogram(FFT_SIZE, FFT_SIZE);
+    assert_eq!(frames.len(), (8 * STEP - FFT_SIZE) / FFT_SIZE + 1);
+    assert_eq!(frames[0].len(), FFT_SIZE / 2 + 1);
+    let levels: Vec<f32> = (0..8)
+        .map(|step| frames[(step * STEP + STEP / 2) / FFT_SIZE][20])
+        .collect();
+    for (step, level) in levels.iter().enumerate() {
+        let expected = -70.0 + 10.0 * step as f32;
+        assert!((level - expected).abs() < 0.1, "{level}dB vs {expected}dB");
+    }
+
+    // The lightness of the colors rises with the level.
+    let range = analyzer.config.colormap_range_db.clone();
+    for colormap in [Colormap::Viridis, Colormap::Magma, Colormap::Grayscale] {
+        let luminance: Vec<f32> = levels
+            .iter()
+            .map(|level| {
+                let color = colormap.color_db(*level, &range);
+                0.2126 * color.0 as f32 + 0.7152 * color.1 as f32 + 0.0722 * color.2 as f32
+            })
+            .collect();
+        assert!(
+            luminance.windows(2).all(|pair| pair[1] > pair[0]),
+            "{colormap:?}: {luminance:?}"
+        );
+    }
+
+    let filename = "out/analyzer/spectrogram.png";
+    let _ = std::fs::remove_file(filename);
+    analyzer.config.colormap = Colormap::Magma;
+    analyzer
+        .plot_spectrogram(FFT_SIZE, FFT_SIZE / 2, "Rising sine", filename)
+        .unwrap();
+    assert!(std::fs::metadata(filename).unwrap().len() > 0);
+}
+
+#[test]
+fn render_panels() {
+    use dsp_analyze::plotters::prelude::*;
+
+    let filename = "out/analyzer/panels.svg";
+    let _ = std::fs::remove_file(filename);
+
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    analyzer
+        .run(|_, out_samples| {
+            for sample in out_samples.iter_mut() {
+                *sample *= 0.5;
+            }
+        })
+        .unwrap();
+
+    {
+        let root = SVGBackend::new(filename, (600, 800)).into_drawing_area();
+        root.fill(&WHITE).unwrap();
+        let panels = root.split_evenly((2, 1));
+        analyzer.render_magnitude("Magnitude", &panels[0]);
+        analyzer.render_phase("Phase", &panels[1]);
+        root.present().unwrap();
+    }
+
+    let svg = std::fs::read_to_string(filename).unwrap();
+    assert!(svg.contains("Magnitude") && svg.contains("Phase"));
+}
+
+#[test]
+fn run_with_hold() {
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+
+    // Gain modulated randomly between 0.8 and 1.0 for each block.
+    let mut noise = NoiseGenerator::new(1);
+    noise.set_gain(0.1);
+    let (max_hold, min_hold) = analyzer
+        .run_with_hold(8, |_, out_samples| {
+            let gain = 0.9 + noise.next_sample();
+            for sample in out_samples.iter_mut() {
+                *sample *= gain;
+            }
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude_hold(
+            &max_hold,
+            &min_hold,
+            "Random gain modulation",
+            "out/analyzer/hold_mag.svg",
+        )
+        .unwrap();
+
+    let magnitude = analyzer.magnitude();
+    let resolution = analyzer.frequency_resolution();
+    for (bin, ((max, min), last)) in max_hold.iter().zip(&min_hold).zip(&magnitude).enumerate() {
+        assert!(min <= last && last <= max);
+        let freq = bin as f32 * resolution;
+        if (20.0..20000.0).contains(&freq) {
+            assert!(max - min > 0.01, "{freq}Hz: {min}dB..{max}dB");
+            assert!(*max < 0.5 && *min > -3.0, "{freq}Hz: {min}dB..{max}dB");
+        }
+    }
+}
+
+#[test]
+fn energy_decay_relief() {
+    const FFT_SIZE: usize = 1024;
+    const HOP: usize = 480;
+
+    // Two decaying sines centered on bins, with a decay time to -60dB of
+    // 500ms at the lower and 200ms at the higher frequency.
+    let bin_width = SAMPLE_RATE / FFT_SIZE as f32;
+    let bands = [(20, 0.5), (100, 0.2)];
+    let ir: Vec<f32> = (0..SAMPLE_RATE as usize)
+        .map(|i| {
+            let time = i as f32 / SAMPLE_RATE;
+            bands
+                .iter()
+                .map(|(bin, decay_time)| {
+                    let phase = std::f32::consts::TAU * *bin as f32 * bin_width * time;
+                    10.0f32.powf(-3.0 * time / decay_time) * phase.sin()
+                })
+                .sum()
+        })
+        .collect();
+
+    let frames = edr(&ir, FFT_SIZE, HOP);
+    assert_eq!(frames.len(), (ir.len() - FFT_SIZE) / HOP + 1);
+    assert_eq!(frames[0].len(), FFT_SIZE / 2 + 1);
+    assert!(frames[0][20].abs() < 0.01);
+
+    // The energy decays 60dB within the decay time, measured over 100ms.
+    let frame_time = HOP as f32 / SAMPLE_RATE;
+    for (bin, decay_time) in bands {
+        let slope = (frames[15][bin] - frames[5][bin]) / (10.0 * frame_time);
+        let expected = -60.0 / decay_time;
+        assert!(
+            (slope - expected).abs() < 0.01 * expected.abs(),
+            "{bin}: {slope}dB/s vs {expected}dB/s"
+        );
+        assert!(frames.windows(2).all(|pair| pair[1][bin] <= pair[0][bin]));
+    }
+
+    let filename = "out/analyzer/edr.png";
+    let _ = std::fs::remove_file(filename);
+    let analyzer = FftAnalyzer::from_impulse_response(&ir, SAMPLE_RATE).unwrap();
+    analyzer
+        .plot_edr(FFT_SIZE, HOP, "Decaying sines", filename)
+        .unwrap();
+    assert!(std::fs::metadata(filename).unwrap().len() > 0);
+}
@@ -1,9 +1,20 @@
 //! Biquad IIR filters.
 
 use core::f32::consts::PI;
+use core::ops::RangeInclusive;
 
+use dsp_analyze::FrequencyResponse;
 use micromath::F32Ext;
 
+/// Q values tried for each band by [`auto_eq`].
+const AUTO_EQ_Q: [f32; 9] = [0.5, 0.7, 1.0, 1.4, 2.0, 3.0, 4.0, 6.0, 8.0];
+
+/// Ratios of the remaining error tried as gain of each band by [`auto_eq`].
+const AUTO_EQ_GAIN_RATIOS: [f32; 4] = [0.5, 0.75, 1.0, 1.25];
+
+/// Highest absolute gain in dB of a band placed by [`auto_eq`].
+const AUTO_EQ_MAX_GAIN_DB: f32 = 12.0;
+
 /// Filter parameters.
 #[derive(Debug, Default, Clone, PartialEq)]
 pub enum FilterParams {
@@ -66,6 +77,9 @@ pub enum FilterParams {
 
         /// Gain in dB.
         gain: f32,
+
+        /// Q value, 0.707 for a shelf without overshoot.
+        q: f32,
     },
 
     /// High shelf mode.
@@ -75,6 +89,9 @@ pub enum FilterParams {
 
         /// Gain in dB.
         gain: f32,
+
+        /// Q value, 0.707 for a shelf without overshoot.
+        q: f32,
     },
 
     /// Allpass mode.
@@ -92,6 +109,12 @@ pub enum FilterParams {
         freq: f32,
     },
 
+    /// One-pole highpass mode, complementary to the one-pole lowpass.
+    Highpass1p {
+        /// Cutoff frequency in Hz.
+        freq: f32,
+    },
+
     /// First order lowpass mode.
     Lowpass1p1z {
         /// Cutoff frequency in Hz.
@@ -127,6 +150,158 @@ pub enum FilterParams {
         /// Center frequency in Hz.
         freq: f32,
     },
+
+    /// Differentiator mode, the difference of consecutive samples.
+    ///
+    /// The magnitude rises with 6dB per octave and crosses 0dB at a sixth
+    /// of the sample rate.
+    Differentiator,
+
+    /// Leaky integrator mode, the inverse of the differentiator for a leak of 0.
+    ///
+    /// The magnitude falls with 6dB per octave above the corner frequency
+    /// of about `leak * sample_rate / (2 * PI)`, with a gain of `1 / leak`
+    /// below it.
+    Integrator {
+        /// Fraction of the accumulated value lost per sample, from 0 to 1.
+        /// 0 gives an ideal integrator, which is only marginally stable.
+        leak: f32,
+    },
+}
+
+impl FilterParams {
+    /// Returns a copy with the parameters clamped to the given ranges.
+    ///
+    /// Infinite values are clamped to the nearest bound. If any parameter is NaN
+    /// or a used range has NaN or reversed bounds, [`FilterParams::Bypass`] is
+    /// returned, so that the coefficient calculation never sees a NaN.
+    pub fn clamp(
+        &self,
+        freq_range: RangeInclusive<f32>,
+        q_range: RangeInclusive<f32>,
+        gain_range: RangeInclusive<f32>,
+    ) -> Self {
+        self.try_clamp(&freq_range, &q_range, &gain_range)
+            .unwrap_or_default()
+    }
+
+    /// Returns peak mode parameters with the bandwidth given in octaves
+    /// instead of a Q value.
+    pub fn peak_bw(freq: f32, gain: f32, bandwidth_octaves: f32) -> Self {
+        Self::Peak {
+            freq,
+            q: bandwidth_to_q(bandwidth_octaves),
+            gain,
+        }
+    }
+
+    /// Returns notch mode parameters with the bandwidth given in octaves
+    /// instead of a Q value.
+    pub fn notch_bw(freq: f32, bandwidth_octaves: f32) -> Self {
+        Self::Notch {
+            freq,
+            q: bandwidth_to_q(bandwidth_octaves),
+        }
+    }
+
+    /// Clamps the parameters, returns `None` if this is not possible.
+    fn try_clamp(
+        &self,
+        freq_range: &RangeInclusive<f32>,
+        q_range: &RangeInclusive<f32>,
+        gain_range: &RangeInclusive<f32>,
+    ) -> Option<Self> {
+        let freq = |value: f32| clamp_value(value, freq_range);
+        let q = |value: f32| clamp_value(value, q_range);
+        let gain = |value: f32| clamp_value(value, gain_range);
+
+        let params = match *self {
+            Self::Bypass => Self::Bypass,
+            Self::Lowpass { freq: f, q: v } => Self::Lowpass {
+                freq: freq(f)?,
+                q: q(v)?,
+            },
+            Self::Highpass { freq: f, q: v } => Self::Highpass {
+                freq: freq(f)?,
+                q: q(v)?,
+            },
+            Self::Bandpass { freq: f, q: v } => Self::Bandpass {
+                freq: freq(f)?,
+                q: q(v)?,
+            },
+            Self::Notch { freq: f, q: v } => Self::Notch {
+                freq: freq(f)?,
+                q: q(v)?,
+            },
+            Self::Peak {
+                freq: f,
+                q: v,
+                gain: g,
+            } => Self::Peak {
+                freq: freq(f)?,
+                q: q(v)?,
+                gain: gain(g)?,
+            },
+            Self::LowShelf {
+                freq: f,
+                gain: g,
+                q: v,
+            } => Self::LowShelf {
+                freq: freq(f)?,
+                gain: gain(g)?,
+                q: q(v)?,
+            },
+            Self::HighShelf {
+                freq: f,
+                gain: g,
+                q: v,
+            } => Self::HighShelf {
+                freq: freq(f)?,
+                gain: gain(g)?,
+                q: q(v)?,
+            },
+            Self::Allpass { freq: f, q: v } => Self::Allpass {
+                freq: freq(f)?,
+                q: q(v)?,
+            },
+            Self::Lowpass1p { freq: f } => Self::Lowpass1p { freq: freq(f)? },
+            Self::Highpass1p { freq: f } => Self::Highpass1p { freq: freq(f)? },
+            Self::Lowpass1p1z { freq: f } => Self::Lowpass1p1z { freq: freq(f)? },
+            Self::Highpass1p1z { freq: f } => Self::Highpass1p1z { freq: freq(f)? },
+            Self::LowShelf1st { freq: f, gain: g } => Self::LowShelf1st {
+                freq: freq(f)?,
+                gain: gain(g)?,
+            },
+            Self::HighShelf1st { freq: f, gain: g } => Self::HighShelf1st {
+                freq: freq(f)?,
+                gain: gain(g)?,
+            },
+            Self::Allpass1st { freq: f } => Self::Allpass1st { freq: freq(f)? },
+            Self::Differentiator => Self::Differentiator,
+            Self::Integrator { leak } => Self::Integrator {
+                leak: clamp_value(leak, &(0.0..=1.0))?,
+            },
+        };
+
+        Some(params)
+    }
+}
+
+/// Converts a bandwidth in octaves to the equivalent Q value.
+pub fn bandwidth_to_q(bandwidth_octaves: f32) -> f32 {
+    let ratio = 2.0.powf(bandwidth_octaves);
+    ratio.sqrt() / (ratio - 1.0)
+}
+
+/// Clamps a value to a range, returns `None` if the value or the range bounds are NaN
+/// or the bounds are reversed.
+fn clamp_value(value: f32, range: &RangeInclusive<f32>) -> Option<f32> {
+    let (min, max) = (*range.start(), *range.end());
+    if value.is_nan() || min.is_nan() || max.is_nan() || min > max {
+        return None;
+    }
+
+    Some(value.clamp(min, max))
 }
 
 /// Normalized filter coefficients.
@@ -241,49 +416,49 @@ impl BiquadFilterCoefficients {
                     }
                 }
             }
-            FilterParams::LowShelf { freq, gain } => {
+            FilterParams::LowShelf { freq, gain, q } => {
                 let k = (PI * freq * sample_time).tan();
                 let v = 10.0.powf(gain.abs() / 20.0);
                 if gain >= 0.0 {
-                    let norm = 1.0 / (1.0 + 2.0.sqrt() * k + k * k);
+                    let norm = 1.0 / (1.0 + 1.0 / q * k + k * k);
                     Self {
-                        a0: (1.0 + (2.0 * v).sqrt() * k + v * k * k) * norm,
+                        a0: (1.0 + v.sqrt() / q * k + v * k * k) * norm,
                         a1: 2.0 * (v * k * k - 1.0) * norm,
-                        a2: (1.0 - (2.0 * v).sqrt() * k + v * k * k) * norm,
+                        a2: (1.0 - v.sqrt() / q * k + v * k * k) * norm,
                         b1: 2.0 * (k * k - 1.0) * norm,
-                        b2: (1.0 - 2.0.sqrt() * k + k * k) * norm,
+                        b2: (1.0 - 1.0 / q * k + k * k) * norm,
                     }
                 } else {
-                    let norm = 1.0 / (1.0 + (2.0 * v).sqrt() * k + v * k * k);
+                    let norm = 1.0 / (1.0 + v.sqrt() / q * k + v * k * k);
                     Self {
-                        a0: (1.0 + 2.0.sqrt() * k + k * k) * norm,
+                        a0: (1.0 + 1.0 / q * k + k * k) * norm,
                         a1: 2.0 * (k * k - 1.0) * norm,
-                        a2: (1.0 - 2.0.sqrt() * k + k * k) * norm,
+                        a2: (1.0 - 1.0 / q * k + k * k) * norm,
                         b1: 2.0 * (v * k * k - 1.0) * norm,
-                        b2: (1.0 - (2.0 * v).sqrt() * k + v * k * k) * norm,
+                        b2: (1.0 - v.sqrt() / q * k + v * k * k) * norm,
                     }
                 }
             }
-            FilterParams::HighShelf { freq, gain } => {
+            FilterParams::HighShelf { freq, gain, q } => {
                 let k = (PI * freq * sample_time).tan();
                 let v = 10.0.powf(gain.abs() / 20.0);
                 if gain >= 0.0 {
-                    let norm = 1.0 / (1.0 + 2.0.sqrt() * k + k * k);
+                    let norm = 1.0 / (1.0 + 1.0 / q * k + k * k);
                     Self {
-                        a0: (v + (2.0 * v).sqrt() * k + k * k) * norm,
+                        a0: (v + v.sqrt() / q * k + k * k) * norm,
                         a1: 2.0 * (k * k - v) * norm,
-                        a2: (v - (2.0 * v).sqrt() * k + k * k) * norm,
+                        a2: (v - v.sqrt() / q * k + k * k) * norm,
                         b1: 2.0 * (k * k - 1.0) * norm,
-                        b2: (1.0 - 2.0.sqrt() * k + k * k) * norm,
+                        b2: (1.0 - 1.0 / q * k + k * k) * norm,
                     }
                 } else {
-                    let norm = 1.0 / (v + (2.0 * v).sqrt() * k + k * k);
+                    let norm = 1.0 / (v + v.sqrt() / q * k + k * k);
                     Self {
-                        a0: (1.0 + 2.0.sqrt() * k + k * k) * norm,
+                        a0: (1.0 + 1.0 / q * k + k * k) * norm,
                         a1: 2.0 * (k * k - 1.0) * norm,
-                        a2: (1.0 - 2.0.sqrt() * k + k * k) * norm,
+                        a2: (1.0 - 1.0 / q * k + k * k) * norm,
                         b1: 2.0 * (k * k - v) * norm,
-                        b2: (v - (2.0 * v).sqrt() * k + k * k) * norm,
+                        b2: (v - v.sqrt() / q * k + k * k) * norm,
                     }
                 }
             }
@@ -311,6 +486,17 @@ impl BiquadFilterCoefficients {
                     b2: 0.0,
                 }
             }
+            FilterParams::Highpass1p { freq } => {
+                // Input minus the output of the one-pole lowpass.
+                let b1 = (-2.0 * PI * freq * sample_time).exp();
+                Self {
+                    a0: b1,
+                    a1: -b1,
+                    a2: 0.0,
+                    b1: -b1,
+                    b2: 0.0,
+                }
+            }
             FilterParams::Lowpass1p1z { freq } => {
                 let k = (PI * freq * sample_time).tan();
                 let norm = 1.0 / (1.0 / k + 1.0);
@@ -383,15 +569,356 @@ impl BiquadFilterCoefficients {
                 let k = (PI * freq * sample_time).tan();
                 let a0 = (1.0 - k) / (1.0 + k);
                 Self {
-                    a0: a0,
+                    a0,
                     a1: -1.0,
                     a2: 0.0,
                     b1: -a0,
                     b2: 0.0,
                 }
             }
+            FilterParams::Differentiator => Self {
+                a0: 1.0,
+                a1: -1.0,
+                a2: 0.0,
+                b1: 0.0,
+                b2: 0.0,
+            },
+            FilterParams::Integrator { leak } => Self {
+                a0: 1.0,
+                a1: 0.0,
+                a2: 0.0,
+                b1: leak - 1.0,
+                b2: 0.0,
+            },
+        }
+    }
+
+    /// Calculates the coefficients from the filter parameters using the
+    /// formulas of the RBJ Audio EQ Cookbook.
+    ///
+    /// `sample_time` is `1.0 / sample_rate`. The shelves use the Q of the
+    /// parameters.
+    ///
+    /// See [`BiquadFilterCoefficients::from_params_rbj_with_slope`] for details.
+    pub fn from_params_rbj(params: FilterParams, sample_time: f32) -> BiquadFilterCoefficients {
+        Self::rbj(params, sample_time, None)
+    }
+
+    /// Calculates the coefficients from the filter parameters using the
+    /// formulas of the RBJ Audio EQ Cookbook.
+    ///
+    /// `sample_time` is `1.0 / sample_rate`. `shelf_slope` is the shelf slope
+    /// parameter `S` of the cookbook, with 1.0 being the steepest slope
+    /// without overshoot. It replaces the Q of the shelves.
+    ///
+    /// The second order lowpass, highpass, bandpass, notch and allpass modes
+    /// result in the same coefficients as [`BiquadFilterCoefficients::from_params`].
+    /// The peak mode differs in the meaning of Q: the cookbook splits the gain
+    /// between numerator and denominator, while [`BiquadFilterCoefficients::from_params`]
+    /// keeps the Q of the denominator for boosts and of the numerator for cuts.
+    /// The shelves differ in the meaning of the frequency: the cookbook places it
+    /// at half of the shelf gain in dB, while [`BiquadFilterCoefficients::from_params`]
+    /// places it at the corner 3dB below the full shelf gain.
+    /// The first order modes are not part of the cookbook and are calculated
+    /// by [`BiquadFilterCoefficients::from_params`].
+    pub fn from_params_rbj_with_slope(
+        params: FilterParams,
+        sample_time: f32,
+        shelf_slope: f32,
+    ) -> BiquadFilterCoefficients {
+        Self::rbj(params, sample_time, Some(shelf_slope))
+    }
+
+    /// Calculates the cookbook coefficients, with the shelves using either
+    /// the slope if given or the Q of the parameters.
+    fn rbj(
+        params: FilterParams,
+        sample_time: f32,
+        shelf_slope: Option<f32>,
+    ) -> BiquadFilterCoefficients {
+        let omega = |freq: f32| 2.0 * PI * freq * sample_time;
+        let shelf_alpha = |sin: f32, a: f32, q: f32| match shelf_slope {
+            Some(slope) => sin / 2.0 * ((a + 1.0 / a) * (1.0 / slope - 1.0) + 2.0).sqrt(),
+            None => sin / (2.0 * q),
+        };
+
+        // Cookbook naming: `b` is the numerator and `a` the denominator.
+        let (b0, b1, b2, a0, a1, a2) = match params {
+            FilterParams::Lowpass { freq, q } => {
+                let (sin, cos) = omega(freq).sin_cos();
+                let alpha = sin / (2.0 * q);
+                let b1 = 1.0 - cos;
+                (b1 / 2.0, b1, b1 / 2.0, 1.0 + alpha, -2.0 * cos, 1.0 - alpha)
+            }
+            FilterParams::Highpass { freq, q } => {
+                let (sin, cos) = omega(freq).sin_cos();
+                let alpha = sin / (2.0 * q);
+                let b1 = -(1.0 + cos);
+                (
+                    -b1 / 2.0,
+                    b1,
+                    -b1 / 2.0,
+                    1.0 + alpha,
+                    -2.0 * cos,
+                    1.0 - alpha,
+                )
+            }
+            FilterParams::Bandpass { freq, q } => {
+                let (sin, cos) = omega(freq).sin_cos();
+                let alpha = sin / (2.0 * q);
+                (alpha, 0.0, -alpha, 1.0 + alpha, -2.0 * cos, 1.0 - alpha)
+            }
+            FilterParams::Notch { freq, q } => {
+                let (sin, cos) = omega(freq).sin_cos();
+                let alpha = sin / (2.0 * q);
+                (1.0, -2.0 * cos, 1.0, 1.0 + alpha, -2.0 * cos, 1.0 - alpha)
+            }
+            FilterParams::Allpass { freq, q } => {
+                let (sin, cos) = omega(freq).sin_cos();
+                let alpha = sin / (2.0 * q);
+                (
+                    1.0 - alpha,
+                    -2.0 * cos,
+                    1.0 + alpha,
+                    1.0 + alpha,
+                    -2.0 * cos,
+                    1.0 - alpha,
+                )
+            }
+            FilterParams::Peak { freq, q, gain } => {
+                let (sin, cos) = omega(freq).sin_cos();
+                let alpha = sin / (2.0 * q);
+                let a = 10.0f32.powf(gain / 40.0);
+                (
+                    1.0 + alpha * a,
+                    -2.0 * cos,
+                    1.0 - alpha * a,
+                    1.0 + alpha / a,
+                    -2.0 * cos,
+                    1.0 - alpha / a,
+                )
+            }
+            FilterParams::LowShelf { freq, gain, q } => {
+                let (sin, cos) = omega(freq).sin_cos();
+                let a = 10.0f32.powf(gain / 40.0);
+                let alpha = shelf_alpha(sin, a, q);
+                let beta = 2.0 * a.sqrt() * alpha;
+                (
+                    a * ((a + 1.0) - (a - 1.0) * cos + beta),
+                    2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+                    a * ((a + 1.0) - (a - 1.0) * cos - beta),
+                    (a + 1.0) + (a - 1.0) * cos + beta,
+                    -2.0 * ((a - 1.0) + (a + 1.0) * cos),
+                    (a + 1.0) + (a - 1.0) * cos - beta,
+                )
+            }
+            FilterParams::HighShelf { freq, gain, q } => {
+                let (sin, cos) = omega(freq).sin_cos();
+                let a = 10.0f32.powf(gain / 40.0);
+                let alpha = shelf_alpha(sin, a, q);
+                let beta = 2.0 * a.sqrt() * alpha;
+                (
+                    a * ((a + 1.0) + (a - 1.0) * cos + beta),
+                    -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+                    a * ((a + 1.0) + (a - 1.0) * cos - beta),
+                    (a + 1.0) - (a - 1.0) * cos + beta,
+                    2.0 * ((a - 1.0) - (a + 1.0) * cos),
+                    (a + 1.0) - (a - 1.0) * cos - beta,
+                )
+            }
+            params => return Self::from_params(params, sample_time),
+        };
+
+        Self {
+            a0: b0 / a0,
+            a1: b1 / a0,
+            a2: b2 / a0,
+            b1: a1 / a0,
+            b2: a2 / a0,
         }
     }
+
+    /// Evaluates the transfer function at a frequency.
+    ///
+    /// Returns the linear magnitude and the phase in radians.
+    /// `sample_time` is `1.0 / sample_rate`.
+    pub fn frequency_response(&self, freq: f32, sample_time: f32) -> (f32, f32) {
+        let omega = 2.0 * PI * freq * sample_time;
+        let (sin1, cos1) = (-omega).sin_cos();
+        let (sin2, cos2) = (-2.0 * omega).sin_cos();
+
+        let num_re = self.a0 + self.a1 * cos1 + self.a2 * cos2;
+        let num_im = self.a1 * sin1 + self.a2 * sin2;
+        let den_re = 1.0 + self.b1 * cos1 + self.b2 * cos2;
+        let den_im = self.b1 * sin1 + self.b2 * sin2;
+
+        let magnitude =
+            (num_re * num_re + num_im * num_im).sqrt() / (den_re * den_re + den_im * den_im).sqrt();
+        let phase = num_im.atan2(num_re) - den_im.atan2(den_re);
+
+        (magnitude, phase)
+    }
+
+    /// Returns the phase in radians at ascending frequencies, unwrapped to a
+    /// continuous curve across the frequencies.
+    pub fn phase_response(&self, freqs: &[f32], sample_time: f32) -> Vec<f32> {
+        let mut phases: Vec<f32> = freqs
+            .iter()
+            .map(|freq| self.frequency_response(*freq, sample_time).1)
+            .collect();
+        unwrap_phase(&mut phases);
+
+        phases
+    }
+
+    /// Returns the coefficients in the order a0, a1, a2, b1, b2.
+    pub fn to_array(&self) -> [f32; 5] {
+        [self.a0, self.a1, self.a2, self.b1, self.b2]
+    }
+
+    /// Returns the coefficients as fixed-point values with `fractional_bits`
+    /// of up to 31, in the order of [`BiquadFilterCoefficients::to_array`].
+    ///
+    /// Coefficients outside of the representable range are saturated,
+    /// see [`BiquadFilterCoefficients::q_format_saturation`].
+    pub fn to_q_format(&self, fractional_bits: u32) -> [i32; 5] {
+        let scale = q_format_scale(fractional_bits);
+        self.to_array().map(|c| {
+            (c as f64 * scale)
+                .round()
+                .clamp(i32::MIN as f64, i32::MAX as f64) as i32
+        })
+    }
+
+    /// Returns for each coefficient if it is saturated by
+    /// [`BiquadFilterCoefficients::to_q_format`].
+    pub fn q_format_saturation(&self, fractional_bits: u32) -> [bool; 5] {
+        let scale = q_format_scale(fractional_bits);
+        self.to_array().map(|c| {
+            let value = (c as f64 * scale).round();
+            value < i32::MIN as f64 || value > i32::MAX as f64
+        })
+    }
+
+    /// Returns the coefficients from fixed-point values as returned by
+    /// [`BiquadFilterCoefficients::to_q_format`].
+    pub fn from_q_format(values: [i32; 5], fractional_bits: u32) -> BiquadFilterCoefficients {
+        let scale = q_format_scale(fractional_bits);
+        let [a0, a1, a2, b1, b2] = values.map(|v| (v as f64 / scale) as f32);
+
+        Self { a0, a1, a2, b1, b2 }
+    }
+
+    /// Returns a C declaration of a `float` array named `name` with the
+    /// coefficients in the order of [`BiquadFilterCoefficients::to_array`].
+    ///
+    /// The values are written with the shortest representation that reads
+    /// back exactly, preceded by a comment with the difference equation.
+    pub fn to_c_header(&self, name: &str) -> String {
+        let values: Vec<String> = self.to_array().iter().map(|c| format!("{c:?}f")).collect();
+
+        format!(
+            "/* y[n] = a0*x[n] + a1*x[n-1] + a2*x[n-2] - b1*y[n-1] - b2*y[n-2] */\n\
+             /* {{ a0, a1, a2, b1, b2 }} */\n\
+             static const float {name}[5] = {{ {} }};\n",
+            values.join(", ")
+        )
+    }
+}
+
+/// Returns the factor converting a value to a fixed-point format.
+fn q_format_scale(fractional_bits: u32) -> f64 {
+    assert!(
+        fractional_bits <= 31,
+        "{fractional_bits} fractional bits exceed 31"
+    );
+
+    (1u64 << fractional_bits) as f64
+}
+
+/// Removes jumps of more than π between consecutive phase values in radians.
+pub fn unwrap_phase(phases: &mut [f32]) {
+    for i in 1..phases.len() {
+        let delta = (phases[i] - phases[i - 1] + PI).rem_euclid(2.0 * PI) - PI;
+        phases[i] = phases[i - 1] + delta;
+    }
+}
+
+/// Returns the magnitude in dB of a filter at a frequency.
+pub fn magnitude_db_at(params: &FilterParams, freq: f32, sample_rate: f32) -> f32 {
+    let sample_time = 1.0 / sample_rate;
+    let coeffs = BiquadFilterCoefficients::from_params(params.clone(), sample_time);
+    let (magnitude, _) = coeffs.frequency_response(freq, sample_time);
+
+    20.0 * magnitude.log10()
+}
+
+/// Returns up to `num_bands` peak filters that bring a measured response
+/// close to a target curve, given as frequencies in Hz and levels in dB.
+///
+/// The bands are placed greedily: each one is centered on the target
+/// frequency with the largest remaining error, with the Q and gain from a
+/// set of candidates that minimize the RMS error over all target frequencies.
+/// The candidates are evaluated with [`magnitude_db_at`]. Placing stops
+/// early if no candidate reduces the error.
+pub fn auto_eq(
+    measured: &FrequencyResponse,
+    target: &[(f32, f32)],
+    num_bands: usize,
+) -> Vec<FilterParams> {
+    let freqs: Vec<f32> = target.iter().map(|(freq, _)| *freq).collect();
+    let mut residual: Vec<f32> = measured
+        .resample_to_grid(&freqs)
+        .iter()
+        .zip(target.iter())
+        .map(|((magnitude, _), (_, level))| level - magnitude)
+        .collect();
+    let rms =
+        |values: &[f32]| (values.iter().map(|v| v * v).sum::<f32>() / values.len() as f32).sqrt();
+
+    let mut bands = Vec::new();
+    while bands.len() < num_bands {
+        let Some((index, error)) = residual
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.abs().total_cmp(&b.1.abs()))
+        else {
+            break;
+        };
+        let freq = freqs[index];
+        let gain = error.clamp(-AUTO_EQ_MAX_GAIN_DB, AUTO_EQ_MAX_GAIN_DB);
+
+        let best = AUTO_EQ_Q
+            .iter()
+            .flat_map(|q| {
+                AUTO_EQ_GAIN_RATIOS.map(|ratio| FilterParams::Peak {
+                    freq,
+                    q: *q,
+                    gain: gain * ratio,
+                })
+            })
+            .map(|params| {
+                let remaining: Vec<f32> = residual
+                    .iter()
+                    .zip(freqs.iter())
+                    .map(|(error, freq)| {
+                        error - magnitude_db_at(&params, *freq, measured.sample_rate)
+                    })
+                    .collect();
+                (rms(&remaining), params, remaining)
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0));
+
+        match best {
+            Some((error, params, remaining)) if error < rms(&residual) => {
+                residual = remaining;
+                bands.push(params);
+            }
+            _ => break,
+        }
+    }
+
+    bands
 }
 
 /// Biquad IIR filter in direct form 1.
@@ -513,3 +1040,169 @@ impl BiquadFilter2 {
         }
     }
 }
+
+/// Lanes of [`BiquadFilter2x4`].
+type Lanes = wide::f32x4;
+
+/// Four independent biquad filters in transposed direct form 2, processing
+/// four channels in parallel.
+///
+/// The recursion of a single filter is serial, so the channels are mapped to
+/// the lanes of a vector instead, which are processed with SIMD instructions.
+#[derive(Debug, Default, Clone)]
+pub struct BiquadFilter2x4 {
+    /// Time per sample, `1.0 / sample_rate`.
+    sample_time: f32,
+
+    /// Coefficients a0, a1, a2, b1 and b2 of all lanes.
+    coeffs: [Lanes; 5],
+
+    /// Sample memory.
+    states: [Lanes; 2],
+}
+
+impl BiquadFilter2x4 {
+    /// Returns a new instance with all lanes in bypass mode.
+    pub fn new(sample_rate: f32) -> Self {
+        let mut filter = Self {
+            sample_time: 1.0 / sample_rate,
+            ..Default::default()
+        };
+        for lane in 0..4 {
+            filter.set_coefficients(lane, BiquadFilterCoefficients::default());
+        }
+
+        filter
+    }
+
+    /// Sets the coefficients of a lane according to the parameters.
+    pub fn set_params(&mut self, lane: usize, params: FilterParams) {
+        let coeffs = BiquadFilterCoefficients::from_params(params, self.sample_time);
+        self.set_coefficients(lane, coeffs);
+    }
+
+    /// Sets the coefficients of a lane.
+    pub fn set_coefficients(&mut self, lane: usize, coeffs: BiquadFilterCoefficients) {
+        for (lanes, value) in self.coeffs.iter_mut().zip(coeffs.to_array()) {
+            let mut values = lanes.to_array();
+            values[lane] = value;
+            *lanes = Lanes::from(values);
+        }
+    }
+
+    /// Processes a single sample of each channel.
+    pub fn process_sample(&mut self, samples: [f32; 4]) -> [f32; 4] {
+        let [a0, a1, a2, b1, b2] = self.coeffs;
+        let sample = Lanes::from(samples);
+        let out_sample = self.states[0] + a0 * sample;
+
+        self.states[0] = self.states[1] + a1 * sample - b1 * out_sample;
+        self.states[1] = a2 * sample - b2 * out_sample;
+
+        out_sample.to_array()
+    }
+
+    /// Processes a block of samples of each channel in-place.
+    ///
+    /// All channels must have the same length.
+    pub fn process_block(&mut self, channels: [&mut [f32]; 4]) {
+        let length = channels[0].len();
+        assert!(
+            channels.iter().all(|channel| channel.len() == length),
+            "Channels differ in length"
+        );
+
+        let [c0, c1, c2, c3] = channels;
+        for n in 0..length {
+            [c0[n], c1[n], c2[n], c3[n]] = self.process_sample([c0[n], c1[n], c2[n], c3[n]]);
+        }
+    }
+}
+
+/// Cascade of biquad filters in transposed direct form 2.
+///
+/// First-order sections are supported, so odd-order filters can be built
+/// from one first-order and several second-order sections.
+#[derive(Debug, Default, Clone)]
+pub struct BiquadCascade {
+    /// Time per sample, `1.0 / sample_rate`.
+    sample_time: f32,
+
+    /// Filter sections in processing order.
+    sections: Vec<BiquadFilter2>,
+}
+
+impl BiquadCascade {
+    /// Returns a new instance without sections.
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            sample_time: 1.0 / sample_rate,
+            ..Default::default()
+        }
+    }
+
+    /// Appends a section with the coefficients according to the parameters.
+    pub fn push(&mut self, params: FilterParams) {
+        let mut section = BiquadFilter2::new(1.0 / self.sample_time);
+        section.set_params(params);
+        self.sections.push(section);
+    }
+
+    /// Removes all sections.
+    pub fn clear(&mut self) {
+        self.sections.clear();
+    }
+
+    /// Returns the number of sections.
+    pub fn len(&self) -> usize {
+        self.sections.len()
+    }
+
+    /// Returns `true` if the cascade has no sections.
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+
+    /// Evaluates the transfer function of the cascade at a frequency.
+    ///
+    /// Returns the linear magnitude as product of the section magnitudes and
+    /// the phase in radians as sum of the section phases.
+    pub fn frequency_response(&self, freq: f32) -> (f32, f32) {
+        self.sections
+            .iter()
+            .map(|section| section.coeffs.frequency_response(freq, self.sample_time))
+            .fold((1.0, 0.0), |(magnitude, phase), (m, p)| {
+                (magnitude * m, phase + p)
+            })
+    }
+
+    /// Returns the phase of the cascade in radians at ascending frequencies.
+    ///
+    /// The phase of each section is unwrapped across the frequencies before
+    /// summing, so the result is continuous even if the total phase exceeds ±π.
+    pub fn phase_response(&self, freqs: &[f32]) -> Vec<f32> {
+        self.sections
+            .iter()
+            .map(|section| section.coeffs.phase_response(freqs, self.sample_time))
+            .fold(vec![0.0; freqs.len()], |mut phases, section_phases| {
+                for (phase, section_phase) in phases.iter_mut().zip(section_phases) {
+                    *phase += section_phase;
+                }
+                phases
+            })
+    }
+
+    /// Processes a single sample.
+    pub fn process_sample(&mut self, sample: f32) -> f32 {
+        self.sections
+            .iter_mut()
+            .fold(sample, |sample, section| section.process_sample(sample))
+    }
+
+    /// Processes a block of samples in-place.
+    pub fn process_block(&mut self, samples: &mut [f32]) {
+        for section in self.sections.iter_mut() {
+            section.process_block(samples);
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! Comb filters.
+
+/// Comb filter mode.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum CombMode {
+    /// Feedforward mode, `y[n] = x[n] + g * x[n - d]`.
+    #[default]
+    Feedforward,
+
+    /// Feedback mode, `y[n] = x[n] + g * y[n - d]`.
+    Feedback,
+}
+
+/// Comb filter with a fixed delay length.
+#[derive(Debug, Clone)]
+pub struct CombFilter {
+    /// Filter mode.
+    mode: CombMode,
+
+    /// Feedforward or feedback coefficient.
+    gain: f32,
+
+    /// Delay line, holds either input or output samples depending on the mode.
+    delay_line: Vec<f32>,
+
+    /// Current read/write position in the delay line.
+    position: usize,
+}
+
+impl CombFilter {
+    /// Returns a new instance.
+    ///
+    /// `delay_samples` must be at least 1.
+    pub fn new(mode: CombMode, delay_samples: usize, gain: f32) -> Self {
+        assert!(delay_samples > 0, "Delay must be at least one sample");
+
+        Self {
+            mode,
+            gain,
+            delay_line: vec![0.0; delay_samples],
+            position: 0,
+        }
+    }
+
+    /// Clears the delay line.
+    pub fn reset(&mut self) {
+        self.delay_line.fill(0.0);
+        self.position = 0;
+    }
+
+    /// Sets the feedforward or feedback coefficient.
+    pub fn set_gain(&mut self, gain: f32) {
+        self.gain = gain;
+    }
+
+    /// Processes a single sample.
+    pub fn process_sample(&mut self, sample: f32) -> f32 {
+        let delayed = self.delay_line[self.position];
+        let out_sample = sample + self.gain * delayed;
+
+        self.delay_line[self.position] = match self.mode {
+            CombMode::Feedforward => sample,
+            CombMode::Feedback => out_sample,
+        };
+        self.position = (self.position + 1) % self.delay_line.len();
+
+        out_sample
+    }
+
+    /// Processes a block of samples in-place.
+    pub fn process_block(&mut self, samples: &mut [f32]) {
+        for sample in samples.iter_mut() {
+            *sample = self.process_sample(*sample);
+        }
+    }
+}
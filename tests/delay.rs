@@ -0,0 +1,75 @@
+//! Fractional delay.
+
+use std::collections::VecDeque;
+
+/// Delay by a fractional number of samples.
+///
+/// The delay is split into an integer part, realized by a delay line, and
+/// a fractional part between 0.5 and 1.5 samples, realized by a first-order
+/// Thiran allpass, `y[n] = a * x[n] + x[n - 1] - a * y[n - 1]` with
+/// `a = (1 - d) / (1 + d)`. The group delay is exact at low frequencies
+/// and decreases towards Nyquist.
+#[derive(Debug, Clone)]
+pub struct FractionalDelay {
+    /// Delay line for the integer part.
+    delay_line: VecDeque<f32>,
+
+    /// Allpass coefficient.
+    coeff: f32,
+
+    /// Previous allpass input sample.
+    in_state: f32,
+
+    /// Previous allpass output sample.
+    out_state: f32,
+}
+
+impl FractionalDelay {
+    /// Returns a new instance with a delay in samples.
+    ///
+    /// `delay` must be at least 0.5 samples.
+    pub fn new(delay: f32) -> Self {
+        assert!(delay >= 0.5, "Delay must be at least 0.5 samples");
+
+        let integer = (delay - 0.5).floor() as usize;
+        let fraction = delay - integer as f32;
+
+        Self {
+            delay_line: VecDeque::from(vec![0.0; integer]),
+            coeff: (1.0 - fraction) / (1.0 + fraction),
+            in_state: 0.0,
+            out_state: 0.0,
+        }
+    }
+
+    /// Clears the delay line and the allpass state.
+    pub fn reset(&mut self) {
+        self.delay_line.iter_mut().for_each(|sample| *sample = 0.0);
+        self.in_state = 0.0;
+        self.out_state = 0.0;
+    }
+
+    /// Processes a single sample.
+    pub fn process_sample(&mut self, sample: f32) -> f32 {
+        let sample = match self.delay_line.pop_front() {
+            Some(delayed) => {
+                self.delay_line.push_back(sample);
+                delayed
+            }
+            None => sample,
+        };
+
+        let out_sample = self.coeff * sample + self.in_state - self.coeff * self.out_state;
+        self.in_state = sample;
+        self.out_state = out_sample;
+
+        out_sample
+    }
+
+    /// Processes a block of samples in-place.
+    pub fn process_block(&mut self, samples: &mut [f32]) {
+        for sample in samples.iter_mut() {
+            *sample = self.process_sample(*sample);
+        }
+    }
+}
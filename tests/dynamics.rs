@@ -0,0 +1,115 @@
+//! Tests for dynamics processors.
+
+pub mod envelope;
+
+use dsp_analyze::*;
+use envelope::*;
+
+/// Sample rate in Hz.
+const SAMPLE_RATE: f32 = 48000.0;
+
+/// Block size in samples.
+const BLOCK_SIZE: usize = 16;
+
+#[test]
+fn crest_factor() {
+    // Sine of 100 periods at 1kHz.
+    let sine: Vec<f32> = (0..4800)
+        .map(|i| (std::f32::consts::TAU * 1000.0 * i as f32 / SAMPLE_RATE).sin())
+        .collect();
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    analyzer.run_with_signal(sine.clone(), |_, _| {}).unwrap();
+    assert!((analyzer.input_crest_factor_db() - 3.01).abs() < 0.01);
+    assert!((analyzer.crest_factor_db() - 3.01).abs() < 0.01);
+    assert!((analyzer.output_rms() - 0.5f32.sqrt()).abs() < 1e-4);
+
+    // Hard limiter at half of the peak.
+    analyzer
+        .run_with_signal(sine, |_, out_samples| {
+            for sample in out_samples.iter_mut() {
+                *sample = sample.clamp(-0.5, 0.5);
+            }
+        })
+        .unwrap();
+    assert!((analyzer.input_crest_factor_db() - 3.01).abs() < 0.01);
+    assert!(
+        analyzer.crest_factor_db() < 1.5,
+        "{}dB",
+        analyzer.crest_factor_db()
+    );
+}
+
+#[test]
+fn envelope_peak() {
+    let attack_time = 0.01;
+    let release_time = 0.1;
+    let mut follower =
+        EnvelopeFollower::new(SAMPLE_RATE, EnvelopeMode::Peak, attack_time, release_time);
+
+    // Step on for 0.5s, then off for 0.5s.
+    let step = step_signal(SAMPLE_RATE as usize / 2, SAMPLE_RATE as usize);
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    analyzer
+        .run_with_signal(step, |_, out_samples| {
+            follower.process_block(out_samples);
+        })
+        .unwrap();
+    analyzer.save_output("out/dynamics/envelope_peak.wav");
+    let envelope = &analyzer.out_samples;
+
+    let attack = envelope[(attack_time * SAMPLE_RATE) as usize - 1];
+    assert!((attack - (1.0 - (-1.0f32).exp())).abs() < 0.01, "{attack}");
+    assert!((envelope[SAMPLE_RATE as usize / 2 - 1] - 1.0).abs() < 1e-4);
+
+    let release = envelope[SAMPLE_RATE as usize / 2 + (release_time * SAMPLE_RATE) as usize - 1];
+    assert!((release - (-1.0f32).exp()).abs() < 0.01, "{release}");
+}
+
+#[test]
+fn envelope_rms() {
+    let mut follower = EnvelopeFollower::new(SAMPLE_RATE, EnvelopeMode::Rms, 0.01, 0.1);
+
+    // Alternating signal with an RMS of 0.5.
+    let mut samples: Vec<f32> = (0..SAMPLE_RATE as usize / 2)
+        .map(|i| if i % 2 == 0 { 0.5 } else { -0.5 })
+        .collect();
+    follower.process_block(&mut samples);
+
+    assert!((samples.last().unwrap() - 0.5).abs() < 1e-3);
+}
+
+#[test]
+fn hilbert_envelope_am() {
+    // 1kHz carrier modulated at 10Hz by 50%, whole periods over one second.
+    let modulation =
+        |i: usize| 1.0 + 0.5 * (std::f32::consts::TAU * 10.0 * i as f32 / SAMPLE_RATE).sin();
+    let signal: Vec<f32> = (0..SAMPLE_RATE as usize)
+        .map(|i| modulation(i) * (std::f32::consts::TAU * 1000.0 * i as f32 / SAMPLE_RATE).sin())
+        .collect();
+
+    let envelope = hilbert_envelope(&signal);
+    assert_eq!(envelope.len(), signal.len());
+    for (i, level) in envelope.iter().enumerate() {
+        assert!(
+            (level - modulation(i)).abs() < 1e-3,
+            "{i}: {level} vs {}",
+            modulation(i)
+        );
+    }
+
+    assert!(hilbert_envelope(&[]).is_empty());
+    assert_eq!(hilbert_envelope(&[-0.5]), vec![0.5]);
+}
+
+/// Returns a signal of `length` samples, which is 1.0 for the first `on` samples.
+fn step_signal(on: usize, length: usize) -> Vec<f32> {
+    (0..length)
+        .map(|i| if i < on { 1.0 } else { 0.0 })
+        .collect()
+}
@@ -0,0 +1,102 @@
+//! Envelope follower for dynamics processors.
+
+/// Detection mode of the envelope follower.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum EnvelopeMode {
+    /// Follows the absolute value of the signal.
+    #[default]
+    Peak,
+
+    /// Follows the root mean square of the signal.
+    Rms,
+}
+
+/// Envelope follower with separate attack and release time constants.
+#[derive(Debug, Clone)]
+pub struct EnvelopeFollower {
+    /// Sample rate in Hz.
+    sample_rate: f32,
+
+    /// Detection mode.
+    mode: EnvelopeMode,
+
+    /// Smoothing coefficient for a rising envelope.
+    attack_coeff: f32,
+
+    /// Smoothing coefficient for a falling envelope.
+    release_coeff: f32,
+
+    /// Current state, squared in RMS mode.
+    state: f32,
+}
+
+impl EnvelopeFollower {
+    /// Returns a new instance with attack and release time constants in seconds.
+    pub fn new(sample_rate: f32, mode: EnvelopeMode, attack_time: f32, release_time: f32) -> Self {
+        Self {
+            sample_rate,
+            mode,
+            attack_coeff: time_to_coeff(attack_time, sample_rate),
+            release_coeff: time_to_coeff(release_time, sample_rate),
+            state: 0.0,
+        }
+    }
+
+    /// Sets the detection mode.
+    pub fn set_mode(&mut self, mode: EnvelopeMode) {
+        self.mode = mode;
+    }
+
+    /// Sets the attack time constant in seconds.
+    pub fn set_attack(&mut self, attack_time: f32) {
+        self.attack_coeff = time_to_coeff(attack_time, self.sample_rate);
+    }
+
+    /// Sets the release time constant in seconds.
+    pub fn set_release(&mut self, release_time: f32) {
+        self.release_coeff = time_to_coeff(release_time, self.sample_rate);
+    }
+
+    /// Resets the envelope to zero.
+    pub fn reset(&mut self) {
+        self.state = 0.0;
+    }
+
+    /// Processes a single sample and returns the envelope.
+    pub fn process_sample(&mut self, sample: f32) -> f32 {
+        let input = match self.mode {
+            EnvelopeMode::Peak => sample.abs(),
+            EnvelopeMode::Rms => sample * sample,
+        };
+
+        let coeff = if input > self.state {
+            self.attack_coeff
+        } else {
+            self.release_coeff
+        };
+        self.state = input + coeff * (self.state - input);
+
+        match self.mode {
+            EnvelopeMode::Peak => self.state,
+            EnvelopeMode::Rms => self.state.sqrt(),
+        }
+    }
+
+    /// Processes a block of samples in-place, replacing them by the envelope.
+    pub fn process_block(&mut self, samples: &mut [f32]) {
+        for sample in samples.iter_mut() {
+            *sample = self.process_sample(*sample);
+        }
+    }
+}
+
+/// Returns the one-pole smoothing coefficient for a time constant in seconds.
+///
+/// After the time constant, the envelope has covered `1 - 1/e` of a step.
+fn time_to_coeff(time: f32, sample_rate: f32) -> f32 {
+    if time <= 0.0 {
+        return 0.0;
+    }
+
+    (-1.0 / (time * sample_rate)).exp()
+}
@@ -1,9 +1,15 @@
 //! Tests for filters.
 
 pub mod biquad;
+pub mod comb;
+pub mod delay;
+pub mod fir;
 
 use biquad::*;
+use comb::*;
+use delay::*;
 use dsp_analyze::*;
+use fir::*;
 
 /// Sample rate in Hz.
 const SAMPLE_RATE: f32 = 48000.0;
@@ -23,14 +29,402 @@ fn lowpass() {
         block_size: BLOCK_SIZE,
         ..Default::default()
     });
-    analyzer.run(|_, out_samples| {
-        filter.process_block(out_samples);
-    });
-    analyzer.plot_magnitude("Lowpass 1kHz", "out/filters/lowpass_1k_mag.svg");
-    analyzer.plot_phase("Lowpass 1kHz", "out/filters/lowpass_1k_phase.svg");
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude("Lowpass 1kHz", "out/filters/lowpass_1k_mag.svg")
+        .unwrap();
+    analyzer
+        .plot_phase("Lowpass 1kHz", "out/filters/lowpass_1k_phase.svg")
+        .unwrap();
     analyzer.save_output("out/filters/lowpass_1k.wav");
 }
 
+#[test]
+fn lowpass_response() {
+    let mut filter = BiquadFilter2::new(SAMPLE_RATE);
+    filter.set_params(FilterParams::Lowpass {
+        freq: 1000.0,
+        q: core::f32::consts::FRAC_1_SQRT_2,
+    });
+
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+    let response = analyzer.response();
+
+    assert_eq!(response.frequencies.len(), response.magnitude_db.len());
+    assert_eq!(response.frequencies[1000], 1000.0);
+    assert!(response.dc_gain.abs() < 0.01);
+    assert!(response.peak_gain.abs() < 0.01);
+    assert!((response.cutoff.unwrap() - 1000.0).abs() < 2.0);
+}
+
+#[test]
+fn analyze_all() {
+    let out_dir = "out/filters/batch";
+    let _ = std::fs::remove_dir_all(out_dir);
+
+    let configs = [
+        (
+            String::from("lowpass_1k"),
+            FilterParams::Lowpass {
+                freq: 1000.0,
+                q: core::f32::consts::FRAC_1_SQRT_2,
+            },
+        ),
+        (
+            String::from("highpass_1k"),
+            FilterParams::Highpass {
+                freq: 1000.0,
+                q: core::f32::consts::FRAC_1_SQRT_2,
+            },
+        ),
+        (
+            String::from("peak_1k"),
+            FilterParams::Peak {
+                freq: 1000.0,
+                q: 1.0,
+                gain: 6.0,
+            },
+        ),
+    ];
+
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    let responses = analyzer
+        .analyze_all(&configs, out_dir, |params| {
+            let mut filter = BiquadFilter2::new(SAMPLE_RATE);
+            filter.set_params(params.clone());
+            move |_: &[f32], out_samples: &mut [f32]| filter.process_block(out_samples)
+        })
+        .unwrap();
+
+    assert_eq!(responses.len(), 3);
+    assert!(responses[0].dc_gain.abs() < 0.01);
+    assert!(responses[1].dc_gain < -60.0);
+    assert!((responses[2].peak_gain - 6.0).abs() < 0.1);
+
+    for (name, _) in configs.iter() {
+        for file in ["magnitude.svg", "phase.svg"] {
+            assert!(std::fs::metadata(format!("{out_dir}/{name}/{file}")).is_ok());
+        }
+    }
+
+    let index = std::fs::read_to_string(format!("{out_dir}/index.txt")).unwrap();
+    assert_eq!(index.lines().count(), 3);
+    assert!(index.starts_with("lowpass_1k: DC gain:"));
+}
+
+#[test]
+fn analyze_all_write_error() {
+    let out_dir = "out/filters/batch_error";
+    let _ = std::fs::remove_dir_all(out_dir);
+    std::fs::create_dir_all(out_dir).unwrap();
+
+    // A file in place of the directory of the second configuration.
+    std::fs::write(format!("{out_dir}/highpass_1k"), "").unwrap();
+
+    let configs = [
+        (
+            String::from("lowpass_1k"),
+            FilterParams::Lowpass {
+                freq: 1000.0,
+                q: core::f32::consts::FRAC_1_SQRT_2,
+            },
+        ),
+        (
+            String::from("highpass_1k"),
+            FilterParams::Highpass {
+                freq: 1000.0,
+                q: core::f32::consts::FRAC_1_SQRT_2,
+            },
+        ),
+    ];
+
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    let result = analyzer.analyze_all(&configs, out_dir, |params| {
+        let mut filter = BiquadFilter2::new(SAMPLE_RATE);
+        filter.set_params(params.clone());
+        move |_: &[f32], out_samples: &mut [f32]| filter.process_block(out_samples)
+    });
+    match result {
+        Err(AnalyzerError::Io(_)) => {}
+        result => panic!("Unexpected result {result:?}"),
+    }
+    assert!(std::fs::metadata(format!("{out_dir}/lowpass_1k/magnitude.svg")).is_ok());
+    assert!(std::fs::metadata(format!("{out_dir}/index.txt")).is_err());
+}
+
+/// Returns the parameters of four different filters at `freq`.
+fn four_filters(freq: f32) -> [FilterParams; 4] {
+    [
+        FilterParams::Lowpass { freq, q: 0.7 },
+        FilterParams::Highpass { freq, q: 2.0 },
+        FilterParams::Peak {
+            freq,
+            q: 1.0,
+            gain: 6.0,
+        },
+        FilterParams::Notch { freq, q: 10.0 },
+    ]
+}
+
+/// Returns four channels of independent noise.
+fn four_channels(length: usize) -> [Vec<f32>; 4] {
+    core::array::from_fn(|channel| {
+        let mut generator = NoiseGenerator::new(channel as u64 + 1);
+        let mut samples = vec![0.0; length];
+        generator.process(&mut samples);
+        samples
+    })
+}
+
+#[test]
+fn biquad_2x4() {
+    let params = four_filters(1000.0);
+    let mut filter = BiquadFilter2x4::new(SAMPLE_RATE);
+    for (lane, params) in params.iter().enumerate() {
+        filter.set_params(lane, params.clone());
+    }
+
+    let mut channels = four_channels(4800);
+    let expected: Vec<Vec<f32>> = channels
+        .iter()
+        .zip(params.iter())
+        .map(|(samples, params)| {
+            let mut single = BiquadFilter2::new(SAMPLE_RATE);
+            single.set_params(params.clone());
+            let mut samples = samples.clone();
+            single.process_block(&mut samples);
+            samples
+        })
+        .collect();
+
+    let [c0, c1, c2, c3] = &mut channels;
+    filter.process_block([c0, c1, c2, c3]);
+
+    for (channel, expected) in channels.iter().zip(expected.iter()) {
+        for (sample, expected) in channel.iter().zip(expected.iter()) {
+            assert!((sample - expected).abs() < 1e-5, "{sample} vs {expected}");
+        }
+    }
+}
+
+#[test]
+fn analyze_ref() {
+    let params = FilterParams::Peak {
+        freq: 1000.0,
+        q: 2.0,
+        gain: 6.0,
+    };
+    let mut filter = BiquadFilter2::new(SAMPLE_RATE);
+    filter.set_params(params.clone());
+
+    // Two periods of an MLS, attenuated by the playback chain. The second
+    // period of the capture is in steady state.
+    let sequence = mls(15);
+    let mut reference: Vec<f32> = sequence
+        .iter()
+        .chain(sequence.iter())
+        .map(|sample| sample * 0.5)
+        .collect();
+    let mut measured = reference.clone();
+    filter.process_block(&mut measured);
+    let reference = reference.split_off(sequence.len());
+    let measured = measured.split_off(sequence.len());
+
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig::default());
+    analyzer.analyze_ref(&reference, &measured).unwrap();
+    assert_eq!(analyzer.in_samples, reference);
+    for freq in [100.0, 1000.0, 3000.0, 10000.0] {
+        let magnitude = analyzer.magnitude_at(freq);
+        let expected = magnitude_db_at(&params, freq, SAMPLE_RATE);
+        assert!(
+            (magnitude - expected).abs() < 0.05,
+            "{freq}Hz: {magnitude}dB vs {expected}dB"
+        );
+    }
+
+    assert!(matches!(
+        analyzer.analyze_ref(&reference, &measured[1..]),
+        Err(AnalyzerError::LengthMismatch(_, _))
+    ));
+    assert_eq!(analyzer.out_samples, measured);
+}
+
+#[test]
+fn magnitude_error() {
+    let params = FilterParams::Peak {
+        freq: 1000.0,
+        q: 2.0,
+        gain: 9.0,
+    };
+    let mut filter = BiquadFilter2::new(SAMPLE_RATE);
+    filter.set_params(params.clone());
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    analyzer
+        .run(|_, out_samples| filter.process_block(out_samples))
+        .unwrap();
+
+    let bin_width = SAMPLE_RATE / 2.0 / (analyzer.spectrum_magnitude.len() - 1) as f32;
+    let ideal: Vec<f32> = (0..analyzer.spectrum_magnitude.len())
+        .map(|bin| magnitude_db_at(&params, bin as f32 * bin_width, SAMPLE_RATE))
+        .collect();
+    analyzer
+        .plot_magnitude_error(&ideal, "Peak 1kHz error", "out/filters/peak_1k_error.svg")
+        .unwrap();
+
+    let error = analyzer.magnitude_error(&ideal);
+    assert_eq!(error.len(), ideal.len());
+    for (bin, error) in error.iter().enumerate() {
+        assert!(
+            error.abs() < 0.01,
+            "{}Hz: {error}dB",
+            bin as f32 * bin_width
+        );
+    }
+
+    // A gain of 1dB too much shows up across the band.
+    let ideal: Vec<f32> = ideal.iter().map(|level| level - 1.0).collect();
+    assert!(analyzer
+        .magnitude_error(&ideal)
+        .iter()
+        .all(|error| (error - 1.0).abs() < 0.01));
+}
+
+#[test]
+fn magnitude_error_null() {
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        test_signal: TestSignal::Sweep,
+        ..Default::default()
+    });
+    analyzer
+        .run(|_, out_samples| out_samples.fill(0.0))
+        .unwrap();
+
+    // The muted output is a null in every bin, which must not be hidden.
+    let ideal = vec![0.0; analyzer.spectrum_magnitude.len()];
+    let error = analyzer.magnitude_error(&ideal);
+    let resolution = analyzer.frequency_resolution();
+    for (bin, error) in error.iter().enumerate() {
+        let freq = bin as f32 * resolution;
+        if (100.0..10000.0).contains(&freq) {
+            assert!(*error < -99.0, "{freq}Hz: {error}dB");
+        } else if freq < analyzer.valid_band.start || freq > analyzer.valid_band.end {
+            assert_eq!(*error, 0.0, "{freq}Hz");
+        }
+    }
+}
+
+#[test]
+fn probe_frequency() {
+    let params = FilterParams::Lowpass {
+        freq: 1000.0,
+        q: core::f32::consts::FRAC_1_SQRT_2,
+    };
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+
+    let mut filter = BiquadFilter2::new(SAMPLE_RATE);
+    filter.set_params(params.clone());
+    analyzer
+        .run(|_, out_samples| filter.process_block(out_samples))
+        .unwrap();
+    let expected: Vec<(f32, f32)> = [100.0, 1000.0, 5000.0]
+        .iter()
+        .map(|freq| (analyzer.magnitude_at(*freq), analyzer.phase_at(*freq)))
+        .collect();
+
+    // Bin-aligned frequencies match the full FFT.
+    for (freq, (magnitude, phase)) in [100.0, 1000.0, 5000.0].iter().zip(expected) {
+        let mut filter = BiquadFilter2::new(SAMPLE_RATE);
+        filter.set_params(params.clone());
+        let (probed_magnitude, probed_phase) = analyzer
+            .probe_frequency(*freq, |_, out_samples| filter.process_block(out_samples))
+            .unwrap();
+        assert!(
+            (probed_magnitude - magnitude).abs() < 0.01,
+            "{freq}Hz: {probed_magnitude}dB vs {magnitude}dB"
+        );
+        assert!(
+            (probed_phase - phase).abs() < 0.1,
+            "{freq}Hz: {probed_phase}° vs {phase}°"
+        );
+    }
+
+    // Frequencies between bins match the coefficients.
+    let mut filter = BiquadFilter2::new(SAMPLE_RATE);
+    filter.set_params(params.clone());
+    let (magnitude, _) = analyzer
+        .probe_frequency(1234.5, |_, out_samples| filter.process_block(out_samples))
+        .unwrap();
+    let expected = magnitude_db_at(&params, 1234.5, SAMPLE_RATE);
+    assert!(
+        (magnitude - expected).abs() < 0.01,
+        "{magnitude}dB vs {expected}dB"
+    );
+    assert!(analyzer.spectrum_magnitude.is_empty());
+}
+
+#[test]
+fn interpolation() {
+    for interpolation in [Interpolation::Linear, Interpolation::Log] {
+        let mut filter = BiquadFilter2::new(SAMPLE_RATE);
+        filter.set_params(FilterParams::Lowpass {
+            freq: 1000.0,
+            q: 0.7,
+        });
+
+        let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+            block_size: BLOCK_SIZE,
+            interpolation,
+            ..Default::default()
+        });
+        analyzer
+            .run(|_, out_samples| {
+                filter.process_block(out_samples);
+            })
+            .unwrap();
+
+        for bin in [0, 1, 500, 1000, 24000] {
+            let freq = bin as f32;
+            assert_eq!(
+                analyzer.magnitude_at(freq),
+                analyzer.spectrum_magnitude[bin]
+            );
+            assert_eq!(analyzer.phase_at(freq), analyzer.spectrum_phase[bin]);
+        }
+
+        let (m0, m1) = (
+            analyzer.spectrum_magnitude[1000],
+            analyzer.spectrum_magnitude[1001],
+        );
+        let magnitude = analyzer.magnitude_at(1000.5);
+        assert!(magnitude < m0 && magnitude > m1);
+    }
+}
+
 #[test]
 fn highpass() {
     let mut filter = BiquadFilter2::new(SAMPLE_RATE);
@@ -43,11 +437,17 @@ fn highpass() {
         block_size: BLOCK_SIZE,
         ..Default::default()
     });
-    analyzer.run(|_, out_samples| {
-        filter.process_block(out_samples);
-    });
-    analyzer.plot_magnitude("Highpass 1kHz", "out/filters/highpass_1k_mag.svg");
-    analyzer.plot_phase("Highpass 1kHz", "out/filters/highpass_1k_phase.svg");
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude("Highpass 1kHz", "out/filters/highpass_1k_mag.svg")
+        .unwrap();
+    analyzer
+        .plot_phase("Highpass 1kHz", "out/filters/highpass_1k_phase.svg")
+        .unwrap();
     analyzer.save_output("out/filters/highpass_1k.wav");
 }
 
@@ -63,14 +463,111 @@ fn bandpass() {
         block_size: BLOCK_SIZE,
         ..Default::default()
     });
-    analyzer.run(|_, out_samples| {
-        filter.process_block(out_samples);
-    });
-    analyzer.plot_magnitude("Bandpass 1kHz", "out/filters/bandpass_1k_mag.svg");
-    analyzer.plot_phase("Bandpass 1kHz", "out/filters/bandpass_1k_phase.svg");
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude("Bandpass 1kHz", "out/filters/bandpass_1k_mag.svg")
+        .unwrap();
+    analyzer
+        .plot_phase("Bandpass 1kHz", "out/filters/bandpass_1k_phase.svg")
+        .unwrap();
     analyzer.save_output("out/filters/bandpass_1k.wav");
 }
 
+#[test]
+fn bandpass_ringing() {
+    // Output RMS in the 10ms after a burst of 5 cycles at 1kHz.
+    let ringing = |q| {
+        let mut filter = BiquadFilter2::new(SAMPLE_RATE);
+        filter.set_params(FilterParams::Bandpass { freq: 1000.0, q });
+
+        let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+            block_size: BLOCK_SIZE,
+            test_signal: TestSignal::ToneBurst {
+                freq: 1000.0,
+                cycles: 5,
+            },
+            ..Default::default()
+        });
+        analyzer
+            .run(|_, out_samples| {
+                filter.process_block(out_samples);
+            })
+            .unwrap();
+        analyzer
+            .plot_output_time(
+                &format!("Bandpass 1kHz Q {q} burst"),
+                format!("out/filters/bandpass_1k_q{q}_burst.svg"),
+            )
+            .unwrap();
+
+        // The burst ends after 240 samples.
+        assert!(analyzer.in_samples[240..].iter().all(|v| *v == 0.0));
+        let tail = &analyzer.out_samples[240..720];
+        (tail.iter().map(|v| v * v).sum::<f32>() / tail.len() as f32).sqrt()
+    };
+
+    let high_q = ringing(20.0);
+    let low_q = ringing(0.7);
+    assert!(high_q > 0.1, "{high_q}");
+    assert!(high_q > low_q * 10.0, "{high_q} vs {low_q}");
+}
+
+#[test]
+fn bandpass_decay() {
+    // Slices 1ms apart with bins of 46.875Hz, 1kHz is within bin 21.
+    let decay = |q| {
+        let mut filter = BiquadFilter2::new(SAMPLE_RATE);
+        filter.set_params(FilterParams::Bandpass { freq: 1000.0, q });
+
+        let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+            block_size: BLOCK_SIZE,
+            ..Default::default()
+        });
+        analyzer
+            .run(|_, out_samples| {
+                filter.process_block(out_samples);
+            })
+            .unwrap();
+        analyzer
+            .plot_waterfall(
+                &format!("Bandpass 1kHz Q {q}"),
+                format!("out/filters/bandpass_1k_q{q}_waterfall.png"),
+            )
+            .unwrap();
+
+        let slices = analyzer.cumulative_spectral_decay(1024, 48, 21);
+        assert_eq!(slices.len(), 21);
+        assert_eq!(slices[0].len(), 513);
+        slices
+    };
+
+    // The ridge stays at the resonance, decaying exponentially with a
+    // time constant of Q / (pi * f).
+    let high_q = decay(50.0);
+    let ridge = high_q[20]
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .unwrap()
+        .0;
+    assert_eq!(ridge, 21);
+
+    let expected = 20.0 * std::f32::consts::E.log10() * 0.02 * std::f32::consts::PI * 1000.0 / 50.0;
+    let high_q_decay = high_q[0][21] - high_q[20][21];
+    assert!(
+        (high_q_decay - expected).abs() < 1.0,
+        "{high_q_decay}dB vs {expected}dB"
+    );
+
+    let low_q = decay(2.0);
+    let low_q_decay = low_q[0][21] - low_q[20][21];
+    assert!(low_q_decay > 60.0, "{low_q_decay}dB");
+}
+
 #[test]
 fn peak() {
     let mut filter = BiquadFilter2::new(SAMPLE_RATE);
@@ -84,51 +581,165 @@ fn peak() {
         block_size: BLOCK_SIZE,
         ..Default::default()
     });
-    analyzer.run(|_, out_samples| {
-        filter.process_block(out_samples);
-    });
-    analyzer.plot_magnitude("Peak 1kHz", "out/filters/peak_1k_mag.svg");
-    analyzer.plot_phase("Peak 1kHz", "out/filters/peak_1k_phase.svg");
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude("Peak 1kHz", "out/filters/peak_1k_mag.svg")
+        .unwrap();
+    analyzer
+        .plot_phase("Peak 1kHz", "out/filters/peak_1k_phase.svg")
+        .unwrap();
     analyzer.save_output("out/filters/peak_1k.wav");
 }
 
+#[test]
+fn peak_bandwidth() {
+    assert!(
+        (bandwidth_to_q(1.0) - core::f32::consts::SQRT_2).abs() < 1e-3,
+        "{}",
+        bandwidth_to_q(1.0)
+    );
+
+    let mut filter = BiquadFilter2::new(SAMPLE_RATE);
+    filter.set_params(FilterParams::peak_bw(1000.0, 20.0, 1.0));
+
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude("Peak 1kHz 1 octave", "out/filters/peak_1k_bw1_mag.svg")
+        .unwrap();
+
+    let (lower, upper) = band_edges(&analyzer.spectrum_magnitude, 1000, 20.0 - 3.01);
+    assert!((upper / lower - 2.0).abs() < 0.02, "{lower}Hz..{upper}Hz");
+}
+
+#[test]
+fn notch_bandwidth() {
+    let mut filter = BiquadFilter2::new(SAMPLE_RATE);
+    filter.set_params(FilterParams::notch_bw(1000.0, 1.0));
+
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude("Notch 1kHz 1 octave", "out/filters/notch_1k_bw1_mag.svg")
+        .unwrap();
+
+    let (lower, upper) = band_edges(&analyzer.spectrum_magnitude, 1000, -3.01);
+    assert!((upper / lower - 2.0).abs() < 0.02, "{lower}Hz..{upper}Hz");
+}
+
+/// Returns the frequencies around a center bin at which a magnitude
+/// spectrum with 1Hz bins crosses a level.
+fn band_edges(magnitude: &[f32], center: usize, level: f32) -> (f32, f32) {
+    let above = magnitude[center] > level;
+    let crosses = |i: &usize| (magnitude[*i] > level) != above;
+    let lower = (1..center).rev().find(crosses).unwrap();
+    let upper = (center..magnitude.len()).find(crosses).unwrap();
+
+    (lower as f32 + 0.5, upper as f32 - 0.5)
+}
+
 #[test]
 fn lowshelf() {
     let mut filter = BiquadFilter2::new(SAMPLE_RATE);
     filter.set_params(FilterParams::LowShelf {
         freq: 1000.0,
         gain: 20.0,
+        q: 0.707,
     });
 
     let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
         block_size: BLOCK_SIZE,
         ..Default::default()
     });
-    analyzer.run(|_, out_samples| {
-        filter.process_block(out_samples);
-    });
-    analyzer.plot_magnitude("Low shelf 1kHz", "out/filters/lowshelf_1k_mag.svg");
-    analyzer.plot_phase("Low shelf 1kHz", "out/filters/lowshelf_1k_phase.svg");
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude("Low shelf 1kHz", "out/filters/lowshelf_1k_mag.svg")
+        .unwrap();
+    analyzer
+        .plot_phase("Low shelf 1kHz", "out/filters/lowshelf_1k_phase.svg")
+        .unwrap();
     analyzer.save_output("out/filters/lowshelf_1k.wav");
 }
 
+#[test]
+fn lowshelf_q() {
+    let measure = |q| {
+        let mut filter = BiquadFilter2::new(SAMPLE_RATE);
+        filter.set_params(FilterParams::LowShelf {
+            freq: 1000.0,
+            gain: 12.0,
+            q,
+        });
+
+        let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+            block_size: BLOCK_SIZE,
+            ..Default::default()
+        });
+        analyzer
+            .run(|_, out_samples| {
+                filter.process_block(out_samples);
+            })
+            .unwrap();
+        analyzer
+    };
+
+    let analyzer = measure(0.707);
+    assert!(analyzer.response().peak_gain < 12.01);
+
+    // A higher Q overshoots the shelf gain at the transition.
+    let analyzer = measure(2.0);
+    analyzer
+        .plot_magnitude("Low shelf 1kHz Q=2", "out/filters/lowshelf_1k_q2_mag.svg")
+        .unwrap();
+    assert!(analyzer.response().peak_gain > 13.0);
+}
+
 #[test]
 fn highshelf() {
     let mut filter = BiquadFilter2::new(SAMPLE_RATE);
     filter.set_params(FilterParams::HighShelf {
         freq: 1000.0,
         gain: 20.0,
+        q: 0.707,
     });
 
     let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
         block_size: BLOCK_SIZE,
         ..Default::default()
     });
-    analyzer.run(|_, out_samples| {
-        filter.process_block(out_samples);
-    });
-    analyzer.plot_magnitude("High shelf 1kHz", "out/filters/highshelf_1k_mag.svg");
-    analyzer.plot_phase("High shelf 1kHz", "out/filters/highshelf_1k_phase.svg");
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude("High shelf 1kHz", "out/filters/highshelf_1k_mag.svg")
+        .unwrap();
+    analyzer
+        .plot_phase("High shelf 1kHz", "out/filters/highshelf_1k_phase.svg")
+        .unwrap();
     analyzer.save_output("out/filters/highshelf_1k.wav");
 }
 
@@ -144,14 +755,74 @@ fn notch() {
         block_size: BLOCK_SIZE,
         ..Default::default()
     });
-    analyzer.run(|_, out_samples| {
-        filter.process_block(out_samples);
-    });
-    analyzer.plot_magnitude("Notch 1kHz", "out/filters/notch_1k_mag.svg");
-    analyzer.plot_phase("Notch 1kHz", "out/filters/notch_1k_phase.svg");
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude("Notch 1kHz", "out/filters/notch_1k_mag.svg")
+        .unwrap();
+    analyzer
+        .plot_phase("Notch 1kHz", "out/filters/notch_1k_phase.svg")
+        .unwrap();
     analyzer.save_output("out/filters/notch_1k.wav");
 }
 
+#[test]
+fn notch_magnitude_range() {
+    let mut filter = BiquadFilter2::new(SAMPLE_RATE);
+    filter.set_params(FilterParams::Notch {
+        freq: 1000.0,
+        q: 10.0,
+    });
+
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude("Notch 1kHz Q=10", "out/filters/notch_1k_q10_mag.svg")
+        .unwrap();
+
+    let range = analyzer.magnitude_range();
+    assert!(range.start.is_finite() && range.end.is_finite());
+    assert!(range.start >= -120.0);
+    assert!(range.end > range.start);
+    assert_eq!(range.start % 6.0, 0.0);
+    assert_eq!(range.end % 6.0, 0.0);
+}
+
+#[test]
+fn measure_notch() {
+    let mut filter = BiquadFilter2::new(SAMPLE_RATE);
+    filter.set_params(FilterParams::Notch {
+        freq: 1000.0,
+        q: 5.0,
+    });
+
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+
+    let notch = analyzer.measure_notch().unwrap();
+    assert!((notch.freq - 1000.0).abs() < 1.0, "{notch:?}");
+    assert!(notch.depth > 60.0, "{notch:?}");
+    let bandwidth = notch.bandwidth.unwrap();
+    assert!((bandwidth - 200.0).abs() < 5.0, "{notch:?}");
+}
+
 #[test]
 fn allpass() {
     let mut filter = BiquadFilter2::new(SAMPLE_RATE);
@@ -164,11 +835,17 @@ fn allpass() {
         block_size: BLOCK_SIZE,
         ..Default::default()
     });
-    analyzer.run(|_, out_samples| {
-        filter.process_block(out_samples);
-    });
-    analyzer.plot_magnitude("Allpass 1kHz", "out/filters/allpass_1k_mag.svg");
-    analyzer.plot_phase("Allpass 1kHz", "out/filters/allpass_1k_phase.svg");
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude("Allpass 1kHz", "out/filters/allpass_1k_mag.svg")
+        .unwrap();
+    analyzer
+        .plot_phase("Allpass 1kHz", "out/filters/allpass_1k_phase.svg")
+        .unwrap();
     analyzer.save_output("out/filters/allpass_1k.wav");
 }
 
@@ -181,17 +858,72 @@ fn lowpass1p() {
         block_size: BLOCK_SIZE,
         ..Default::default()
     });
-    analyzer.run(|_, out_samples| {
-        filter.process_block(out_samples);
-    });
-    analyzer.plot_magnitude("Lowpass one-pole 1kHz", "out/filters/lowpass1p_1k_mag.svg");
-    analyzer.plot_phase(
-        "Lowpass one-pole 1kHz",
-        "out/filters/lowpass1p_1k_phase.svg",
-    );
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude("Lowpass one-pole 1kHz", "out/filters/lowpass1p_1k_mag.svg")
+        .unwrap();
+    analyzer
+        .plot_phase(
+            "Lowpass one-pole 1kHz",
+            "out/filters/lowpass1p_1k_phase.svg",
+        )
+        .unwrap();
     analyzer.save_output("out/filters/lowpass1p_1k.wav");
 }
 
+#[test]
+fn highpass1p() {
+    let mut lowpass = BiquadFilter2::new(SAMPLE_RATE);
+    lowpass.set_params(FilterParams::Lowpass1p { freq: 1000.0 });
+    let mut highpass = BiquadFilter2::new(SAMPLE_RATE);
+    highpass.set_params(FilterParams::Highpass1p { freq: 1000.0 });
+
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    analyzer
+        .run(|_, out_samples| {
+            highpass.process_block(out_samples);
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude(
+            "Highpass one-pole 1kHz",
+            "out/filters/highpass1p_1k_mag.svg",
+        )
+        .unwrap();
+    analyzer
+        .plot_phase(
+            "Highpass one-pole 1kHz",
+            "out/filters/highpass1p_1k_phase.svg",
+        )
+        .unwrap();
+    analyzer.save_output("out/filters/highpass1p_1k.wav");
+    assert!(analyzer.magnitude_at(20000.0).abs() < 1.0);
+
+    // Both filters in parallel add up to the input.
+    let mut highpass = BiquadFilter2::new(SAMPLE_RATE);
+    highpass.set_params(FilterParams::Highpass1p { freq: 1000.0 });
+    analyzer
+        .run(|in_samples, out_samples| {
+            let mut high = in_samples.to_vec();
+            highpass.process_block(&mut high);
+            lowpass.process_block(out_samples);
+            for (out_sample, high_sample) in out_samples.iter_mut().zip(high) {
+                *out_sample += high_sample;
+            }
+        })
+        .unwrap();
+    for magnitude in analyzer.spectrum_magnitude.iter() {
+        assert!(magnitude.abs() < 0.01, "{magnitude}dB");
+    }
+}
+
 #[test]
 fn lowpass1p1z() {
     let mut filter = BiquadFilter2::new(SAMPLE_RATE);
@@ -201,17 +933,23 @@ fn lowpass1p1z() {
         block_size: BLOCK_SIZE,
         ..Default::default()
     });
-    analyzer.run(|_, out_samples| {
-        filter.process_block(out_samples);
-    });
-    analyzer.plot_magnitude(
-        "Lowpass first order 1kHz",
-        "out/filters/lowpass1p1z_1k_mag.svg",
-    );
-    analyzer.plot_phase(
-        "Lowpass first order 1kHz",
-        "out/filters/lowpass1p1z_1k_phase.svg",
-    );
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude(
+            "Lowpass first order 1kHz",
+            "out/filters/lowpass1p1z_1k_mag.svg",
+        )
+        .unwrap();
+    analyzer
+        .plot_phase(
+            "Lowpass first order 1kHz",
+            "out/filters/lowpass1p1z_1k_phase.svg",
+        )
+        .unwrap();
     analyzer.save_output("out/filters/lowpass1p1z_1k.wav");
 }
 
@@ -224,17 +962,23 @@ fn highpass1p1z() {
         block_size: BLOCK_SIZE,
         ..Default::default()
     });
-    analyzer.run(|_, out_samples| {
-        filter.process_block(out_samples);
-    });
-    analyzer.plot_magnitude(
-        "Highpass first order 1kHz",
-        "out/filters/highpass1p1z_1k_mag.svg",
-    );
-    analyzer.plot_phase(
-        "Highpass first order 1kHz",
-        "out/filters/highpass1p1z_1k_phase.svg",
-    );
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude(
+            "Highpass first order 1kHz",
+            "out/filters/highpass1p1z_1k_mag.svg",
+        )
+        .unwrap();
+    analyzer
+        .plot_phase(
+            "Highpass first order 1kHz",
+            "out/filters/highpass1p1z_1k_phase.svg",
+        )
+        .unwrap();
     analyzer.save_output("out/filters/highpass1p1z_1k.wav");
 }
 
@@ -250,17 +994,23 @@ fn lowshelf1st() {
         block_size: BLOCK_SIZE,
         ..Default::default()
     });
-    analyzer.run(|_, out_samples| {
-        filter.process_block(out_samples);
-    });
-    analyzer.plot_magnitude(
-        "Low shelf first order 1kHz",
-        "out/filters/lowshelf1st_1k_mag.svg",
-    );
-    analyzer.plot_phase(
-        "Low shelf first order 1kHz",
-        "out/filters/lowshelf1st_1k_phase.svg",
-    );
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude(
+            "Low shelf first order 1kHz",
+            "out/filters/lowshelf1st_1k_mag.svg",
+        )
+        .unwrap();
+    analyzer
+        .plot_phase(
+            "Low shelf first order 1kHz",
+            "out/filters/lowshelf1st_1k_phase.svg",
+        )
+        .unwrap();
     analyzer.save_output("out/filters/lowshelf1st_1k.wav");
 }
 
@@ -276,17 +1026,23 @@ fn highshelf1st() {
         block_size: BLOCK_SIZE,
         ..Default::default()
     });
-    analyzer.run(|_, out_samples| {
-        filter.process_block(out_samples);
-    });
-    analyzer.plot_magnitude(
-        "High shelf first order 1kHz",
-        "out/filters/highshelf1st_1k_mag.svg",
-    );
-    analyzer.plot_phase(
-        "High shelf first order 1kHz",
-        "out/filters/highshelf1st_1k_phase.svg",
-    );
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude(
+            "High shelf first order 1kHz",
+            "out/filters/highshelf1st_1k_mag.svg",
+        )
+        .unwrap();
+    analyzer
+        .plot_phase(
+            "High shelf first order 1kHz",
+            "out/filters/highshelf1st_1k_phase.svg",
+        )
+        .unwrap();
     analyzer.save_output("out/filters/highshelf1st_1k.wav");
 }
 
@@ -299,16 +1055,707 @@ fn allpass1st() {
         block_size: BLOCK_SIZE,
         ..Default::default()
     });
-    analyzer.run(|_, out_samples| {
-        filter.process_block(out_samples);
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude(
+            "Allpass first order 1kHz",
+            "out/filters/allpass1st_1k_mag.svg",
+        )
+        .unwrap();
+    analyzer
+        .plot_phase(
+            "Allpass first order 1kHz",
+            "out/filters/allpass1st_1k_phase.svg",
+        )
+        .unwrap();
+    analyzer.save_output("out/filters/allpass1st_1k.wav");
+}
+
+#[test]
+fn differentiator() {
+    let mut filter = BiquadFilter2::new(SAMPLE_RATE);
+    filter.set_params(FilterParams::Differentiator);
+
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
     });
-    analyzer.plot_magnitude(
-        "Allpass first order 1kHz",
-        "out/filters/allpass1st_1k_mag.svg",
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude("Differentiator", "out/filters/differentiator_mag.svg")
+        .unwrap();
+    analyzer
+        .plot_phase("Differentiator", "out/filters/differentiator_phase.svg")
+        .unwrap();
+
+    // Rises with 6dB per octave from 20Hz to 5kHz, bending down towards Nyquist.
+    let mut freq = 20.0;
+    while freq < 5000.0 {
+        let slope = analyzer.magnitude_at(freq * 2.0) - analyzer.magnitude_at(freq);
+        assert!((slope - 6.02).abs() < 0.15, "{freq}Hz: {slope}dB");
+        freq *= 2.0;
+    }
+    assert!(analyzer.magnitude_at(SAMPLE_RATE / 6.0).abs() < 0.01);
+    assert!((analyzer.magnitude_at(SAMPLE_RATE / 2.0) - 6.02).abs() < 0.01);
+}
+
+#[test]
+fn integrator() {
+    let leak = 0.001;
+    let mut filter = BiquadFilter2::new(SAMPLE_RATE);
+    filter.set_params(FilterParams::Integrator { leak });
+
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        test_signal: TestSignal::Mls { order: 16 },
+        pre_roll: 0.5,
+        ..Default::default()
+    });
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude("Leaky integrator", "out/filters/integrator_mag.svg")
+        .unwrap();
+    analyzer
+        .plot_phase("Leaky integrator", "out/filters/integrator_phase.svg")
+        .unwrap();
+
+    // Falls with 6dB per octave above the corner frequency of about 7.6Hz.
+    let mut freq = 160.0;
+    while freq < 5000.0 {
+        let slope = analyzer.magnitude_at(freq * 2.0) - analyzer.magnitude_at(freq);
+        assert!((slope + 6.02).abs() < 0.15, "{freq}Hz: {slope}dB");
+        freq *= 2.0;
+    }
+    let dc_gain = 20.0 * (1.0 / leak).log10();
+    assert!((analyzer.spectrum_magnitude[0] - dc_gain).abs() < 0.1);
+
+    // Out-of-range leaks are clamped.
+    let params = FilterParams::Integrator { leak: -1.0 };
+    assert_eq!(
+        params.clamp(20.0..=20000.0, 0.1..=10.0, -24.0..=24.0),
+        FilterParams::Integrator { leak: 0.0 }
     );
-    analyzer.plot_phase(
-        "Allpass first order 1kHz",
-        "out/filters/allpass1st_1k_phase.svg",
+}
+
+#[test]
+fn butterworth3() {
+    let freq = 100.0;
+    let mut filter = BiquadCascade::new(SAMPLE_RATE);
+    filter.push(FilterParams::Lowpass1p1z { freq });
+    filter.push(FilterParams::Lowpass { freq, q: 1.0 });
+    assert_eq!(filter.len(), 2);
+
+    let analytic_db = |f: f32| 20.0 * filter.frequency_response(f).0.log10();
+    let analytic_slope = analytic_db(3200.0) - analytic_db(1600.0);
+    assert!(
+        (analytic_slope + 18.0).abs() < 0.5,
+        "{analytic_slope}dB/oct"
     );
-    analyzer.save_output("out/filters/allpass1st_1k.wav");
+    assert!((analytic_db(freq) + 3.01).abs() < 0.05);
+
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude(
+            "Butterworth 3rd order lowpass 100Hz",
+            "out/filters/butterworth3_100_mag.svg",
+        )
+        .unwrap();
+    analyzer
+        .plot_phase(
+            "Butterworth 3rd order lowpass 100Hz",
+            "out/filters/butterworth3_100_phase.svg",
+        )
+        .unwrap();
+
+    let measured_slope = analyzer.spectrum_magnitude[3200] - analyzer.spectrum_magnitude[1600];
+    assert!(
+        (measured_slope + 18.0).abs() < 0.5,
+        "{measured_slope}dB/oct"
+    );
+}
+
+#[test]
+fn rbj_coefficients() {
+    let sample_time = 1.0 / SAMPLE_RATE;
+    let params = [
+        FilterParams::Lowpass {
+            freq: 1000.0,
+            q: 0.7,
+        },
+        FilterParams::Highpass {
+            freq: 1000.0,
+            q: 0.7,
+        },
+        FilterParams::Bandpass {
+            freq: 1000.0,
+            q: 2.0,
+        },
+        FilterParams::Notch {
+            freq: 1000.0,
+            q: 2.0,
+        },
+        FilterParams::Allpass {
+            freq: 1000.0,
+            q: 0.7,
+        },
+    ];
+
+    for params in params {
+        let coeffs = BiquadFilterCoefficients::from_params(params.clone(), sample_time);
+        let rbj = BiquadFilterCoefficients::from_params_rbj(params.clone(), sample_time);
+        for freq in [10.0, 100.0, 700.0, 2000.0, 20000.0] {
+            let (magnitude, _) = coeffs.frequency_response(freq, sample_time);
+            let (rbj_magnitude, _) = rbj.frequency_response(freq, sample_time);
+            let difference = 20.0 * (rbj_magnitude / magnitude).log10();
+            assert!(difference.abs() < 0.01, "{params:?} at {freq}Hz");
+        }
+    }
+
+    // The shelf frequency is at half of the gain for the cookbook
+    // and at the 3dB corner for the default formulas.
+    let shelf = FilterParams::LowShelf {
+        freq: 1000.0,
+        gain: 20.0,
+        q: 0.707,
+    };
+    let coeffs = BiquadFilterCoefficients::from_params(shelf.clone(), sample_time);
+    let rbj = BiquadFilterCoefficients::from_params_rbj(shelf, sample_time);
+    let (magnitude, _) = coeffs.frequency_response(1000.0, sample_time);
+    let (rbj_magnitude, _) = rbj.frequency_response(1000.0, sample_time);
+    assert!((20.0 * rbj_magnitude.log10() - 10.0).abs() < 0.05);
+    assert!((20.0 * magnitude.log10() - 17.0).abs() < 1.0);
+}
+
+#[test]
+fn q_format() {
+    let sample_time = 1.0 / SAMPLE_RATE;
+    let coeffs = BiquadFilterCoefficients::from_params(
+        FilterParams::Lowpass {
+            freq: 1000.0,
+            q: 0.707,
+        },
+        sample_time,
+    );
+
+    let fixed = coeffs.to_q_format(29);
+    assert_eq!(coeffs.q_format_saturation(29), [false; 5]);
+    let restored = BiquadFilterCoefficients::from_q_format(fixed, 29);
+    for (original, restored) in coeffs.to_array().iter().zip(restored.to_array()) {
+        assert!(
+            (original - restored).abs() <= 1.0 / (1u32 << 29) as f32 + original.abs() * 1e-7,
+            "{original} vs {restored}"
+        );
+    }
+
+    // Q31 covers -1.0 to 1.0, b1 is close to -2.0.
+    let saturation = coeffs.q_format_saturation(31);
+    assert_eq!(saturation, [false, false, false, true, false]);
+    assert_eq!(coeffs.to_q_format(31)[3], i32::MIN);
+}
+
+#[test]
+fn c_header() {
+    let coeffs = BiquadFilterCoefficients::from_params(
+        FilterParams::Lowpass {
+            freq: 1000.0,
+            q: 0.707,
+        },
+        1.0 / SAMPLE_RATE,
+    );
+
+    let header = coeffs.to_c_header("lowpass_1k");
+    assert!(header.contains("static const float lowpass_1k[5] = { "));
+    assert!(header.trim_end().ends_with(" };"));
+    assert!(header.contains("- b1*y[n-1]"));
+
+    let start = header.find("= {").unwrap() + 3;
+    let end = header.rfind('}').unwrap();
+    let values: Vec<f32> = header[start..end]
+        .split(',')
+        .map(|value| value.trim().strip_suffix('f').unwrap().parse().unwrap())
+        .collect();
+    assert_eq!(values, coeffs.to_array());
+}
+
+#[test]
+fn clamp_nan() {
+    let params = FilterParams::Lowpass {
+        freq: f32::NAN,
+        q: 0.7,
+    };
+    let params = params.clamp(20.0..=20000.0, 0.1..=10.0, -24.0..=24.0);
+    assert_eq!(params, FilterParams::Bypass);
+
+    let mut filter = BiquadFilter2::new(SAMPLE_RATE);
+    filter.set_params(params);
+
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+
+    assert!(analyzer.out_samples.iter().all(|sample| sample.is_finite()));
+}
+
+#[test]
+fn clamp_infinite() {
+    let params = FilterParams::Peak {
+        freq: f32::INFINITY,
+        q: 0.7,
+        gain: f32::NEG_INFINITY,
+    };
+    let params = params.clamp(20.0..=20000.0, 0.1..=10.0, -24.0..=24.0);
+
+    assert_eq!(
+        params,
+        FilterParams::Peak {
+            freq: 20000.0,
+            q: 0.7,
+            gain: -24.0,
+        }
+    );
+}
+
+#[test]
+fn magnitude_at_frequency() {
+    let lowpass = FilterParams::Lowpass {
+        freq: 1000.0,
+        q: core::f32::consts::FRAC_1_SQRT_2,
+    };
+    assert!((magnitude_db_at(&lowpass, 1000.0, SAMPLE_RATE) + 3.01).abs() < 0.01);
+    assert!(magnitude_db_at(&lowpass, 10.0, SAMPLE_RATE).abs() < 0.01);
+
+    let peak = FilterParams::Peak {
+        freq: 1000.0,
+        q: 0.7,
+        gain: 12.0,
+    };
+    assert!((magnitude_db_at(&peak, 1000.0, SAMPLE_RATE) - 12.0).abs() < 0.01);
+
+    let bypass = FilterParams::Bypass;
+    assert_eq!(magnitude_db_at(&bypass, 5000.0, SAMPLE_RATE), 0.0);
+}
+
+#[test]
+fn comb_feedforward() {
+    let delay_samples = 48;
+    let mut filter = CombFilter::new(CombMode::Feedforward, delay_samples, 0.5);
+
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude("Comb feedforward 1kHz", "out/filters/comb_ff_1k_mag.svg")
+        .unwrap();
+    analyzer
+        .plot_phase("Comb feedforward 1kHz", "out/filters/comb_ff_1k_phase.svg")
+        .unwrap();
+    analyzer.save_output("out/filters/comb_ff_1k.wav");
+
+    let spacing = SAMPLE_RATE / delay_samples as f32;
+    assert_comb_spacing(&analyzer.spectrum_magnitude, spacing);
+}
+
+#[test]
+fn comb_feedback() {
+    let delay_samples = 48;
+    let mut filter = CombFilter::new(CombMode::Feedback, delay_samples, 0.5);
+
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude("Comb feedback 1kHz", "out/filters/comb_fb_1k_mag.svg")
+        .unwrap();
+    analyzer
+        .plot_phase("Comb feedback 1kHz", "out/filters/comb_fb_1k_phase.svg")
+        .unwrap();
+    analyzer.save_output("out/filters/comb_fb_1k.wav");
+
+    let spacing = SAMPLE_RATE / delay_samples as f32;
+    assert_comb_spacing(&analyzer.spectrum_magnitude, spacing);
+}
+
+#[test]
+fn pre_roll() {
+    let params = FilterParams::Bandpass {
+        freq: 1000.0,
+        q: 50.0,
+    };
+
+    // Largest deviation from the analytic response from 100Hz to 10kHz.
+    let deviation = |pre_roll| {
+        let mut filter = BiquadFilter2::new(SAMPLE_RATE);
+        filter.set_params(params.clone());
+        let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+            block_size: BLOCK_SIZE,
+            test_signal: TestSignal::Mls { order: 15 },
+            pre_roll,
+            ..Default::default()
+        });
+        analyzer
+            .run(|_, out_samples| {
+                filter.process_block(out_samples);
+            })
+            .unwrap();
+        assert_eq!(
+            analyzer.in_samples.len(),
+            mls(15).len().next_multiple_of(BLOCK_SIZE)
+        );
+
+        (100..10000)
+            .step_by(10)
+            .map(|freq| {
+                let expected = magnitude_db_at(&params, freq as f32, SAMPLE_RATE);
+                (analyzer.magnitude_at(freq as f32) - expected).abs()
+            })
+            .fold(0.0f32, f32::max)
+    };
+
+    // The ringing of the resonance at the start is missing from the end of
+    // the period without a pre-roll.
+    let transient = deviation(0.0);
+    let settled = deviation(0.2);
+    assert!(transient > 0.5, "{transient}dB");
+    assert!(settled < 0.05, "{settled}dB");
+}
+
+#[test]
+fn fir_lowpass() {
+    let taps = fir_lowpass_windowed(63, 4000.0, SAMPLE_RATE, FirWindow::Blackman);
+    let mut filter = FirFilter::new(taps);
+    let delay = filter.linear_phase_delay().unwrap();
+    assert_eq!(delay, 31.0);
+
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude("FIR lowpass 4kHz", "out/filters/fir_lowpass_4k_mag.svg")
+        .unwrap();
+    analyzer
+        .plot_phase("FIR lowpass 4kHz", "out/filters/fir_lowpass_4k_phase.svg")
+        .unwrap();
+
+    assert!(analyzer.magnitude_at(100.0).abs() < 0.01);
+    assert!((analyzer.magnitude_at(4000.0) + 6.02).abs() < 0.1);
+    assert!(analyzer.magnitude_at(8000.0) < -60.0);
+
+    // Constant group delay in the passband, from the phase difference of
+    // adjacent 1Hz bins.
+    for freq in (10..3000).step_by(10) {
+        let step = analyzer.phase_at(freq as f32 + 1.0) - analyzer.phase_at(freq as f32);
+        let step = (step + 540.0) % 360.0 - 180.0;
+        let group_delay = -step / 360.0 * SAMPLE_RATE;
+        assert!(
+            (group_delay - delay).abs() < 0.01,
+            "{freq}Hz: {group_delay}"
+        );
+    }
+
+    // With the delay compensated, the phase is zero in the passband.
+    filter.reset();
+    analyzer.config.delay_compensation = DelayCompensation::Samples(delay);
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+    for freq in (10..3000).step_by(10) {
+        assert!(analyzer.phase_at(freq as f32).abs() < 0.01);
+    }
+}
+
+/// Asserts that the local minima and maxima of a magnitude spectrum
+/// with 1Hz bins are spaced `spacing` Hz apart.
+fn assert_comb_spacing(magnitude: &[f32], spacing: f32) {
+    let band = &magnitude[..(spacing * 10.0) as usize];
+    let minima: Vec<usize> = (1..band.len() - 1)
+        .filter(|&i| band[i] < band[i - 1] && band[i] < band[i + 1])
+        .collect();
+    let maxima: Vec<usize> = (1..band.len() - 1)
+        .filter(|&i| band[i] > band[i - 1] && band[i] > band[i + 1])
+        .collect();
+
+    assert!(minima.len() >= 5);
+    assert!(maxima.len() >= 5);
+    for pair in minima.windows(2).chain(maxima.windows(2)) {
+        assert!(((pair[1] - pair[0]) as f32 - spacing).abs() <= 1.0);
+    }
+}
+
+#[test]
+fn from_impulse_response() {
+    let params = FilterParams::Peak {
+        freq: 1000.0,
+        q: 0.7,
+        gain: 6.0,
+    };
+    let mut filter = BiquadFilter2::new(SAMPLE_RATE);
+    filter.set_params(params.clone());
+
+    let mut ir = vec![0.0; SAMPLE_RATE as usize];
+    ir[0] = 1.0;
+    filter.process_block(&mut ir);
+
+    let analyzer = FftAnalyzer::from_impulse_response(&ir, SAMPLE_RATE).unwrap();
+    analyzer
+        .plot_magnitude(
+            "Peak 1kHz from impulse response",
+            "out/filters/peak_ir_1k_mag.svg",
+        )
+        .unwrap();
+
+    for freq in [20.0, 100.0, 1000.0, 5000.0, 20000.0] {
+        let expected = magnitude_db_at(&params, freq, SAMPLE_RATE);
+        let magnitude = analyzer.magnitude_at(freq);
+        assert!(
+            (magnitude - expected).abs() < 0.01,
+            "{freq}Hz: {magnitude}dB"
+        );
+    }
+}
+
+#[test]
+fn from_impulse_response_invalid() {
+    match FftAnalyzer::from_impulse_response(&[], SAMPLE_RATE) {
+        Err(AnalyzerError::EmptySignal) => {}
+        result => panic!("Unexpected result {result:?}"),
+    }
+
+    let mut ir = vec![0.0; 64];
+    ir[0] = 1.0;
+    ir[5] = f32::NAN;
+    match FftAnalyzer::from_impulse_response(&ir, SAMPLE_RATE) {
+        Err(AnalyzerError::NonFiniteOutput(index, value)) => {
+            assert_eq!(index, 5);
+            assert!(value.is_nan());
+        }
+        result => panic!("Unexpected result {result:?}"),
+    }
+}
+
+#[test]
+fn allpass_continuous_phase() {
+    let mut cascade = BiquadCascade::new(SAMPLE_RATE);
+    cascade.push(FilterParams::Allpass {
+        freq: 1000.0,
+        q: 0.7,
+    });
+
+    let freqs: Vec<f32> = (0..=2400).map(|i| i as f32 * 10.0).collect();
+    let phases: Vec<f32> = cascade
+        .phase_response(&freqs)
+        .iter()
+        .map(|phase| phase.to_degrees())
+        .collect();
+
+    assert!(phases[0].abs() < 0.1);
+    assert!((phases[phases.len() - 1] + 360.0).abs() < 0.1);
+    for pair in phases.windows(2) {
+        assert!(
+            pair[1] <= pair[0] + 1e-3 && pair[0] - pair[1] < 5.0,
+            "{pair:?}"
+        );
+    }
+}
+
+#[test]
+fn lowpass_linear_log() {
+    let mut filter = BiquadFilter2::new(SAMPLE_RATE);
+    filter.set_params(FilterParams::Lowpass {
+        freq: 1000.0,
+        q: 0.7,
+    });
+
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        magnitude_scale: MagnitudeScale::LinearLog,
+        ..Default::default()
+    });
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude(
+            "Lowpass 1kHz linear magnitude",
+            "out/filters/lowpass_1k_linear_log_mag.svg",
+        )
+        .unwrap();
+
+    let range = analyzer.magnitude_range();
+    assert!(range.start > 0.0 && range.end > range.start);
+    assert!(range.end >= 1.0);
+}
+
+#[test]
+fn clipping() {
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        test_signal: TestSignal::Sweep,
+        ..Default::default()
+    });
+    analyzer.run(|_, _| {}).unwrap();
+    assert!(analyzer.output_peak() <= 1.0);
+    assert!(!analyzer.clipped());
+    assert_eq!(analyzer.clipped_samples(), 0);
+
+    let mut filter = BiquadFilter2::new(SAMPLE_RATE);
+    filter.set_params(FilterParams::Peak {
+        freq: 1000.0,
+        q: 0.7,
+        gain: 20.0,
+    });
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+    assert!(analyzer.output_peak() > 9.0);
+    assert!(analyzer.clipped());
+    assert!(analyzer.clipped_samples() > 0);
+}
+
+#[test]
+fn excitation_level() {
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        test_signal: TestSignal::Sweep,
+        ..Default::default()
+    });
+    let mut filter = BiquadFilter2::new(SAMPLE_RATE);
+    filter.set_params(FilterParams::Peak {
+        freq: 1000.0,
+        q: 0.7,
+        gain: 12.0,
+    });
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+    assert!(analyzer.clipped());
+    let reference = analyzer.magnitude_at(1000.0);
+
+    // 1dB more headroom than the gain for the ripple of the filtered sweep.
+    analyzer.config.excitation_level_db = -13.0;
+    let mut filter = BiquadFilter2::new(SAMPLE_RATE);
+    filter.set_params(FilterParams::Peak {
+        freq: 1000.0,
+        q: 0.7,
+        gain: 12.0,
+    });
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+    let peak = analyzer
+        .in_samples
+        .iter()
+        .fold(0.0f32, |peak, v| peak.max(v.abs()));
+    let peak_db = 20.0 * peak.log10();
+    assert!((peak_db + 13.0).abs() < 0.1, "{peak_db}dB");
+    assert!(!analyzer.clipped(), "{}", analyzer.output_peak());
+    assert!((analyzer.magnitude_at(1000.0) - reference).abs() < 1e-3);
+}
+
+#[test]
+fn run_stages() {
+    let params = FilterParams::Lowpass {
+        freq: 1000.0,
+        q: 0.7,
+    };
+    let mut first = BiquadFilter2::new(SAMPLE_RATE);
+    first.set_params(params.clone());
+    let mut second = BiquadFilter2::new(SAMPLE_RATE);
+    second.set_params(params.clone());
+
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    let stages: Vec<Processor> = vec![
+        Box::new(|_, out_samples| first.process_block(out_samples)),
+        Box::new(|_, out_samples| second.process_block(out_samples)),
+    ];
+    let responses = analyzer.run_stages(stages).unwrap();
+    analyzer
+        .plot_magnitude("Two lowpass stages 1kHz", "out/filters/stages_1k_mag.svg")
+        .unwrap();
+    assert_eq!(responses.len(), 2);
+
+    let expected = magnitude_db_at(&params, 4000.0, SAMPLE_RATE);
+    let bin = responses[0].bin_index(4000.0);
+    assert!((responses[0].magnitude_db[bin] - expected).abs() < 0.01);
+    assert!((responses[1].magnitude_db[bin] - 2.0 * expected).abs() < 0.01);
+    assert_eq!(analyzer.spectrum_magnitude, responses[1].magnitude_db);
+}
+
+#[test]
+fn fractional_delay() {
+    for delay in [0.7, 2.3, 10.5] {
+        let mut filter = FractionalDelay::new(delay);
+
+        let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+            block_size: BLOCK_SIZE,
+            ..Default::default()
+        });
+        analyzer
+            .run(|_, out_samples| {
+                filter.process_block(out_samples);
+            })
+            .unwrap();
+
+        // Group delay from the phase slope between 100Hz and 101Hz.
+        let slope = analyzer.spectrum_phase[101] - analyzer.spectrum_phase[100];
+        let slope = (slope + 180.0).rem_euclid(360.0) - 180.0;
+        let group_delay = -slope / 360.0 * SAMPLE_RATE;
+        assert!((group_delay - delay).abs() < 0.01, "{delay}: {group_delay}");
+    }
 }
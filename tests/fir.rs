@@ -0,0 +1,164 @@
+//! FIR filters.
+
+use core::f32::consts::PI;
+
+/// Window applied to the taps of a windowed-sinc design.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum FirWindow {
+    /// No window, the sinc is truncated.
+    Rectangular,
+
+    /// Hann window.
+    Hann,
+
+    /// Hamming window.
+    #[default]
+    Hamming,
+
+    /// Blackman window.
+    Blackman,
+}
+
+impl FirWindow {
+    /// Returns the value of the window at `index` of `length` taps.
+    pub fn value(&self, index: usize, length: usize) -> f32 {
+        if length < 2 {
+            return 1.0;
+        }
+        // Phase relative to the center, so the window is exactly symmetric.
+        let offset = index as f32 - (length - 1) as f32 / 2.0;
+        let phase = 2.0 * PI * offset / (length - 1) as f32;
+        match self {
+            Self::Rectangular => 1.0,
+            Self::Hann => 0.5 + 0.5 * phase.cos(),
+            Self::Hamming => 0.54 + 0.46 * phase.cos(),
+            Self::Blackman => 0.42 + 0.5 * phase.cos() + 0.08 * (2.0 * phase).cos(),
+        }
+    }
+}
+
+/// FIR filter with arbitrary taps.
+#[derive(Debug, Clone)]
+pub struct FirFilter {
+    /// Taps, the first one is applied to the current input sample.
+    taps: Vec<f32>,
+
+    /// Delay line of the past input samples.
+    delay_line: Vec<f32>,
+
+    /// Position of the current input sample in the delay line.
+    position: usize,
+}
+
+impl FirFilter {
+    /// Returns a new instance.
+    ///
+    /// At least one tap must be given.
+    pub fn new(taps: Vec<f32>) -> Self {
+        assert!(!taps.is_empty(), "No taps");
+
+        Self {
+            delay_line: vec![0.0; taps.len()],
+            taps,
+            position: 0,
+        }
+    }
+
+    /// Returns the taps.
+    pub fn taps(&self) -> &[f32] {
+        &self.taps
+    }
+
+    /// Returns the group delay in samples if the taps are symmetric,
+    /// i.e. the filter has linear phase.
+    pub fn linear_phase_delay(&self) -> Option<f32> {
+        self.taps
+            .iter()
+            .eq(self.taps.iter().rev())
+            .then(|| (self.taps.len() - 1) as f32 / 2.0)
+    }
+
+    /// Clears the delay line.
+    pub fn reset(&mut self) {
+        self.delay_line.fill(0.0);
+        self.position = 0;
+    }
+
+    /// Processes a single sample.
+    pub fn process_sample(&mut self, sample: f32) -> f32 {
+        self.delay_line[self.position] = sample;
+
+        let length = self.delay_line.len();
+        let out_sample = self
+            .taps
+            .iter()
+            .enumerate()
+            .map(|(i, tap)| tap * self.delay_line[(self.position + length - i) % length])
+            .sum();
+        self.position = (self.position + 1) % length;
+
+        out_sample
+    }
+
+    /// Processes a block of samples in-place.
+    pub fn process_block(&mut self, samples: &mut [f32]) {
+        for sample in samples.iter_mut() {
+            *sample = self.process_sample(*sample);
+        }
+    }
+}
+
+/// Returns the taps of a windowed-sinc lowpass with a cutoff frequency in Hz,
+/// normalized to unity gain at DC.
+///
+/// An odd number of taps gives a symmetric filter with an integer delay.
+pub fn fir_lowpass_windowed(
+    num_taps: usize,
+    freq: f32,
+    sample_rate: f32,
+    window: FirWindow,
+) -> Vec<f32> {
+    assert!(num_taps > 0, "No taps");
+
+    let cutoff = freq / sample_rate;
+    let center = (num_taps - 1) as f32 / 2.0;
+    let mut taps: Vec<f32> = (0..num_taps)
+        .map(|i| {
+            let offset = i as f32 - center;
+            let sinc = if offset == 0.0 {
+                2.0 * cutoff
+            } else {
+                (2.0 * PI * cutoff * offset).sin() / (PI * offset)
+            };
+            sinc * window.value(i, num_taps)
+        })
+        .collect();
+
+    let sum: f32 = taps.iter().sum();
+    for tap in taps.iter_mut() {
+        *tap /= sum;
+    }
+
+    taps
+}
+
+/// Returns the taps of a windowed-sinc highpass with a cutoff frequency in Hz,
+/// by spectral inversion of the lowpass.
+///
+/// The number of taps must be odd.
+pub fn fir_highpass_windowed(
+    num_taps: usize,
+    freq: f32,
+    sample_rate: f32,
+    window: FirWindow,
+) -> Vec<f32> {
+    assert!(num_taps % 2 == 1, "Number of taps is even");
+
+    let mut taps = fir_lowpass_windowed(num_taps, freq, sample_rate, window);
+    for tap in taps.iter_mut() {
+        *tap = -*tap;
+    }
+    taps[num_taps / 2] += 1.0;
+
+    taps
+}
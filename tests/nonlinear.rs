@@ -0,0 +1,120 @@
+//! Tests for nonlinear processors.
+
+pub mod saturator;
+
+use dsp_analyze::*;
+use saturator::*;
+
+/// Sample rate in Hz.
+const SAMPLE_RATE: f32 = 48000.0;
+
+/// Block size in samples.
+const BLOCK_SIZE: usize = 16;
+
+/// Frequency of the test tone in Hz.
+const TONE_FREQ: f32 = 1000.0;
+
+#[test]
+fn hardclip() {
+    let saturator = Saturator::new(SaturatorCurve::HardClip, 2.0);
+    let out_samples = process_tone(|_, out_samples| saturator.process_block(out_samples));
+
+    let fundamental = harmonic_amplitude(&out_samples, 1);
+    for harmonic in [3, 5, 7] {
+        assert!(harmonic_amplitude(&out_samples, harmonic) > fundamental * 0.01);
+    }
+    for harmonic in [2, 4, 6] {
+        assert!(harmonic_amplitude(&out_samples, harmonic) < fundamental * 1e-4);
+    }
+}
+
+#[test]
+fn cubic() {
+    let drive = 0.5;
+    let saturator = Saturator::new(SaturatorCurve::Cubic, drive);
+    let out_samples = process_tone(|_, out_samples| saturator.process_block(out_samples));
+
+    // x - x^3 / 3 with x = d * sin(wt) yields a 3rd harmonic of d^3 / 12.
+    let expected = drive * drive * drive / 12.0;
+    assert!((harmonic_amplitude(&out_samples, 3) - expected).abs() < 1e-4);
+    assert!(harmonic_amplitude(&out_samples, 2) < 1e-4);
+}
+
+#[test]
+fn tanh() {
+    let saturator = Saturator::new(SaturatorCurve::Tanh, 4.0);
+    let out_samples = process_tone(|_, out_samples| saturator.process_block(out_samples));
+
+    assert!(out_samples.iter().all(|sample| sample.abs() < 1.0));
+    assert!(harmonic_amplitude(&out_samples, 3) > 0.01);
+}
+
+#[test]
+fn oversampling() {
+    // The 5th harmonic of 7kHz at 35kHz aliases to 13kHz at 48kHz.
+    let freq = 7000.0;
+    let tone: Vec<f32> = (0..SAMPLE_RATE as usize)
+        .map(|n| (core::f32::consts::TAU * freq * n as f32 / SAMPLE_RATE).sin())
+        .collect();
+    let saturator = Saturator::new(SaturatorCurve::HardClip, 4.0);
+
+    let mut alias = Vec::new();
+    for oversample in [1, 4] {
+        let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+            block_size: BLOCK_SIZE,
+            oversample,
+            ..Default::default()
+        });
+        analyzer
+            .run_with_signal(tone.clone(), |_, out_samples| {
+                saturator.process_block(out_samples)
+            })
+            .unwrap();
+        assert!(amplitude_at(&analyzer.out_samples, 3.0 * freq) > 0.1);
+        alias.push(amplitude_at(&analyzer.out_samples, 13000.0));
+    }
+
+    assert!(alias[0] > 0.1, "{alias:?}");
+    assert!(alias[1] < alias[0] * 0.01, "{alias:?}");
+}
+
+/// Runs a full-scale sine tone of one second through the function and
+/// returns the output samples.
+fn process_tone<F>(func: F) -> Vec<f32>
+where
+    F: FnMut(&[f32], &mut [f32]),
+{
+    let tone = (0..SAMPLE_RATE as usize)
+        .map(|n| (core::f32::consts::TAU * TONE_FREQ * n as f32 / SAMPLE_RATE).sin())
+        .collect();
+
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    analyzer.run_with_signal(tone, func).unwrap();
+
+    analyzer.out_samples
+}
+
+/// Returns the amplitude of a harmonic of the test tone.
+fn harmonic_amplitude(samples: &[f32], harmonic: usize) -> f32 {
+    amplitude_at(samples, TONE_FREQ * harmonic as f32)
+}
+
+/// Returns the amplitude of the component at a frequency in Hz.
+fn amplitude_at(samples: &[f32], freq: f32) -> f32 {
+    let omega = core::f64::consts::TAU * freq as f64 / SAMPLE_RATE as f64;
+    let (re, im) = samples
+        .iter()
+        .enumerate()
+        .fold((0.0, 0.0), |(re, im), (n, &sample)| {
+            let phase = omega * n as f64;
+            (
+                re + sample as f64 * phase.cos(),
+                im - sample as f64 * phase.sin(),
+            )
+        });
+
+    ((re * re + im * im).sqrt() * 2.0 / samples.len() as f64) as f32
+}
@@ -0,0 +1,385 @@
+//! Tests for the plots.
+
+use dsp_analyze::plotters::chart::SeriesLabelPosition;
+use dsp_analyze::plotters::style::{BLUE, RED};
+use dsp_analyze::*;
+
+/// Sample rate in Hz.
+const SAMPLE_RATE: f32 = 48000.0;
+
+#[test]
+fn two_series() {
+    let filename = "out/plot/two_series.svg";
+    let _ = std::fs::remove_file(filename);
+
+    let sine: Vec<f32> = (0..480)
+        .map(|i| (i as f32 * 2.0 * std::f32::consts::PI / 48.0).sin())
+        .collect();
+    let cosine: Vec<f32> = (0..480)
+        .map(|i| (i as f32 * 2.0 * std::f32::consts::PI / 48.0).cos())
+        .collect();
+
+    let plot = Plot::builder()
+        .title("Sine and cosine 1kHz")
+        .add_series(Series {
+            label: "Sine",
+            samplerate: SAMPLE_RATE,
+            series: &sine,
+            color: &BLUE,
+            ..Default::default()
+        })
+        .add_series(Series {
+            label: "Cosine",
+            samplerate: SAMPLE_RATE,
+            series: &cosine,
+            color: &RED,
+            ..Default::default()
+        })
+        .build();
+    assert!(plot.validate().is_ok());
+    plot.create_svg(filename).unwrap();
+
+    let svg = std::fs::read_to_string(filename).unwrap();
+    assert!(svg.contains("Sine") && svg.contains("Cosine"));
+}
+
+#[test]
+fn legend() {
+    let sine: Vec<f32> = (0..480)
+        .map(|i| (i as f32 * 2.0 * std::f32::consts::PI / 48.0).sin())
+        .collect();
+    let render = |filename, legend| {
+        let _ = std::fs::remove_file(filename);
+        Plot::builder()
+            .title("Sine 1kHz")
+            .add_series(Series {
+                label: "Sine",
+                series: &sine,
+                color: &BLUE,
+                ..Default::default()
+            })
+            .legend(legend)
+            .build()
+            .create_svg(filename)
+            .unwrap();
+        std::fs::read_to_string(filename).unwrap()
+    };
+
+    let svg = render("out/plot/legend_default.svg", LegendConfig::default());
+    assert!(svg.contains("\nSine\n"));
+
+    let svg = render(
+        "out/plot/legend_hidden.svg",
+        LegendConfig {
+            show: false,
+            ..Default::default()
+        },
+    );
+    assert!(svg.contains("Sine 1kHz") && svg.contains("#0000FF"));
+    assert!(!svg.contains("\nSine\n"));
+
+    let svg = render(
+        "out/plot/legend_upper_left.svg",
+        LegendConfig {
+            position: SeriesLabelPosition::UpperLeft,
+            ..Default::default()
+        },
+    );
+    assert!(svg.contains("\nSine\n"));
+}
+
+#[test]
+fn line_styles() {
+    let filename = "out/plot/line_styles.svg";
+    let _ = std::fs::remove_file(filename);
+
+    let sine: Vec<f32> = (0..480)
+        .map(|i| (i as f32 * 2.0 * std::f32::consts::PI / 48.0).sin())
+        .collect();
+    let attenuated: Vec<f32> = sine.iter().map(|v| v * 0.9).collect();
+
+    let solid = Plot::builder()
+        .add_series(Series {
+            label: "Sine",
+            series: &sine,
+            color: &BLUE,
+            ..Default::default()
+        })
+        .build();
+    let plot = Plot::builder()
+        .title("Overlapping sines")
+        .add_series(Series {
+            label: "Sine",
+            series: &sine,
+            color: &BLUE,
+            width: 2,
+            ..Default::default()
+        })
+        .add_series(Series {
+            label: "Attenuated",
+            series: &attenuated,
+            color: &RED,
+            style: LineStyle::Dashed,
+            ..Default::default()
+        })
+        .add_series(Series {
+            label: "Dotted",
+            series: &attenuated,
+            color: &BLUE,
+            style: LineStyle::Dotted,
+            ..Default::default()
+        })
+        .build();
+    assert!(plot.validate().is_ok());
+    plot.create_svg(filename).unwrap();
+
+    let svg = std::fs::read_to_string(filename).unwrap();
+    assert!(svg.contains("Attenuated") && svg.contains("Dotted"));
+    assert!(svg.contains(r#"stroke-width="2""#));
+    assert!(svg.contains("<circle"));
+
+    // Dashes are drawn as separate segments.
+    solid.create_svg("out/plot/line_styles_solid.svg").unwrap();
+    let solid_svg = std::fs::read_to_string("out/plot/line_styles_solid.svg").unwrap();
+    assert!(svg.matches("<polyline").count() > solid_svg.matches("<polyline").count() + 10);
+}
+
+#[test]
+fn constant_series() {
+    let filename = "out/plot/constant_series.svg";
+    let _ = std::fs::remove_file(filename);
+
+    let constant = vec![0.5; 480];
+    let series = Series {
+        label: "Constant",
+        samplerate: SAMPLE_RATE,
+        series: &constant,
+        color: &BLUE,
+        ..Default::default()
+    };
+
+    let range = series.y_range();
+    assert!(range.start < 0.5 && range.end > 0.5, "{range:?}");
+
+    Plot::builder()
+        .title("Constant")
+        .add_series(series)
+        .build()
+        .create_svg(filename)
+        .unwrap();
+    assert!(std::fs::metadata(filename).is_ok());
+}
+
+#[test]
+fn vector_formats() {
+    let samples: Vec<f32> = (0..480)
+        .map(|i| (i as f32 * 2.0 * std::f32::consts::PI / 48.0).sin())
+        .collect();
+    let plot = Plot::builder()
+        .title("Sine 1kHz (vector)")
+        .add_series(Series {
+            label: "Sine",
+            samplerate: SAMPLE_RATE,
+            series: &samples,
+            color: &BLUE,
+            ..Default::default()
+        })
+        .build();
+
+    for (filename, header) in [
+        ("out/plot/sine.pdf", "%PDF-"),
+        ("out/plot/sine.eps", "%!PS-Adobe"),
+    ] {
+        let _ = std::fs::remove_file(filename);
+        if filename.ends_with(".pdf") {
+            plot.create_pdf(filename).unwrap();
+        } else {
+            plot.create_eps(filename).unwrap();
+        }
+
+        let contents = std::fs::read_to_string(filename).unwrap();
+        assert!(contents.starts_with(header));
+        assert!(contents.contains("(Sine 1kHz \\(vector\\))"));
+        assert!(contents.ends_with("%%EOF\n"));
+    }
+}
+
+#[test]
+fn vector_write_error() {
+    let samples = vec![1.0; 16];
+    let plot = Plot::builder()
+        .add_series(Series {
+            label: "Ones",
+            series: &samples,
+            ..Default::default()
+        })
+        .build();
+
+    // The parent is a file, so the directory cannot be created.
+    assert!(matches!(
+        plot.create_pdf("Cargo.toml/plot.pdf"),
+        Err(AnalyzerError::Io(_))
+    ));
+
+    // The file is a directory, so it cannot be written.
+    assert!(matches!(
+        plot.create_eps("tests"),
+        Err(AnalyzerError::Io(_))
+    ));
+}
+
+#[test]
+fn svg_write_error() {
+    let samples = vec![1.0; 16];
+    let plot = Plot::builder()
+        .add_series(Series {
+            label: "Ones",
+            series: &samples,
+            ..Default::default()
+        })
+        .build();
+
+    // The file is a directory, so it cannot be written.
+    assert!(matches!(
+        plot.create_svg("tests"),
+        Err(AnalyzerError::Io(_))
+    ));
+}
+
+#[test]
+fn invalid_plots() {
+    let empty = Plot::builder().title("Empty").build();
+    assert!(empty.validate().is_err());
+
+    let nan = vec![f32::NAN; 16];
+    let non_finite = Plot::builder()
+        .add_series(Series {
+            label: "NaN",
+            samplerate: SAMPLE_RATE,
+            series: &nan,
+            color: &BLUE,
+            ..Default::default()
+        })
+        .build();
+    assert!(non_finite.validate().is_err());
+
+    let samples = vec![1.0; 16];
+    let empty_range = Plot::builder()
+        .add_series(Series {
+            label: "Ones",
+            samplerate: SAMPLE_RATE,
+            series: &samples,
+            color: &BLUE,
+            ..Default::default()
+        })
+        .y_range(AxisRange::ManualLin(1.0..1.0))
+        .build();
+    assert!(empty_range.validate().is_err());
+
+    let fill_out_of_range = Plot::builder()
+        .title("Fill out of range")
+        .add_series(Series {
+            label: "Ones",
+            series: &samples,
+            ..Default::default()
+        })
+        .fill_between(0, 1)
+        .build();
+    assert!(fill_out_of_range.validate().is_err());
+}
+
+#[test]
+fn frequency_range() {
+    let filename = "out/plot/frequency_range.svg";
+    let _ = std::fs::remove_file(filename);
+
+    let mut analyzer = FftAnalyzer::with_sample_rate(96000.0);
+    analyzer.run(|_, _| {}).unwrap();
+    analyzer
+        .plot_magnitude("Full band at 96kHz", "out/plot/frequency_range_full.svg")
+        .unwrap();
+    analyzer
+        .plot_magnitude_range("Audio band at 96kHz", filename, 20.0..20000.0)
+        .unwrap();
+
+    // Positions of the 1kHz and 10kHz ticks and of the last point of the magnitude.
+    let parse = |filename| {
+        let svg = std::fs::read_to_string(filename).unwrap();
+        let tick = |label: &str| -> f32 {
+            let label = svg.find(&format!("\n{label}\n")).unwrap();
+            let tick = &svg[svg[..label].rfind("<text x=\"").unwrap() + 9..];
+            tick[..tick.find('"').unwrap()].parse().unwrap()
+        };
+        let line = svg.lines().find(|line| line.contains("#0000FF")).unwrap();
+        let last = line.rsplit(' ').nth(1).unwrap();
+        let last_x: f32 = last[..last.find(',').unwrap()].parse().unwrap();
+        (tick("1000.0"), tick("10000.0"), last_x)
+    };
+    let (_, full_tick, _) = parse("out/plot/frequency_range_full.svg");
+    let (decade_tick, tick, end) = parse(filename);
+    assert!(tick > full_tick + 30.0, "{tick} vs {full_tick}");
+
+    // The magnitude ends at 20kHz on the right edge instead of at Nyquist.
+    let expected = tick + (tick - decade_tick) * 2.0f32.log10();
+    assert!((end - expected).abs() <= 2.0, "{end} vs {expected}");
+
+    let invalid = Plot::builder()
+        .bode(true)
+        .add_series(Series {
+            series: &analyzer.spectrum_magnitude,
+            ..Default::default()
+        })
+        .x_range(0.0..20000.0)
+        .build();
+    assert!(invalid.validate().is_err());
+}
+
+#[test]
+fn margins() {
+    let values: Vec<f32> = (0..480).map(|i| i as f32 * 2500.0).collect();
+    let render = |filename, left_margin| {
+        Plot::builder()
+            .title("Large values")
+            .add_series(Series {
+                label: "Ramp",
+                series: &values,
+                color: &BLUE,
+                ..Default::default()
+            })
+            .title_font(("sans-serif".to_string(), 60))
+            .left_margin(left_margin)
+            .bottom_margin(60)
+            .build()
+            .create_svg(filename)
+            .unwrap();
+        std::fs::read_to_string(filename).unwrap()
+    };
+
+    // Estimated left edge of the right-aligned Y axis tick labels,
+    // assuming an average glyph width of 0.6em.
+    let label_start = |svg: &str| {
+        svg.split("<text x=\"")
+            .skip(1)
+            .filter(|element| element.contains("text-anchor=\"end\""))
+            .map(|element| {
+                let x: f32 = element.split('"').next().unwrap().parse().unwrap();
+                let size: f32 = element
+                    .split("font-size=\"")
+                    .nth(1)
+                    .and_then(|rest| rest.split('"').next())
+                    .unwrap()
+                    .parse()
+                    .unwrap();
+                let label = element.split('>').nth(1).unwrap().trim_start();
+                let label = label.split('<').next().unwrap().trim();
+                x - label.len() as f32 * size * 0.6
+            })
+            .fold(f32::INFINITY, f32::min)
+    };
+
+    let svg = render("out/plot/margins_default.svg", plot::LABEL_AREA_SIZE);
+    assert!(label_start(&svg) < 0.0);
+
+    let svg = render("out/plot/margins_wide.svg", 80);
+    assert!(label_start(&svg) >= 0.0);
+}
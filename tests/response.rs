@@ -0,0 +1,373 @@
+//! Tests for frequency responses.
+
+pub mod biquad;
+
+use biquad::*;
+use dsp_analyze::*;
+
+/// Sample rate in Hz.
+const SAMPLE_RATE: f32 = 48000.0;
+
+/// Block size in samples.
+const BLOCK_SIZE: usize = 16;
+
+#[test]
+fn empty_response() {
+    let analyzer = FftAnalyzer::new(FftAnalyzerConfig::default());
+    let response = analyzer.response();
+    assert!(response.frequencies.is_empty());
+    assert!(response.magnitude_db.is_empty());
+    assert_eq!(response.cutoff, None);
+
+    assert_eq!(response.bin_index(1000.0), 0);
+    assert!(response.smooth(3.0).magnitude_db.is_empty());
+    assert!(response.smooth_phase(3.0).phase_deg.is_empty());
+    assert!(response.minimum_phase().is_empty());
+}
+
+#[test]
+fn match_gain() {
+    let response = measure(FilterParams::Peak {
+        freq: 1000.0,
+        q: 0.7,
+        gain: 6.0,
+    });
+
+    let mut shifted = response.clone();
+    shifted.offset_db(6.0);
+    assert!((shifted.peak_gain - response.peak_gain - 6.0).abs() < 1e-4);
+
+    let offset = shifted.match_gain_to(&response, 1000.0);
+    assert!((offset + 6.0).abs() < 1e-4);
+
+    shifted.offset_db(offset);
+    for (a, b) in shifted
+        .magnitude_db
+        .iter()
+        .zip(response.magnitude_db.iter())
+    {
+        assert!((a - b).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn minimum_phase() {
+    let response = measure(FilterParams::Peak {
+        freq: 1000.0,
+        q: 0.7,
+        gain: 12.0,
+    });
+    let phase = response.minimum_phase();
+    assert_eq!(phase.len(), response.phase_deg.len());
+
+    for (i, (minimum, measured)) in phase.iter().zip(response.phase_deg.iter()).enumerate() {
+        let freq = response.frequencies[i];
+        let difference = (minimum - measured + 180.0).rem_euclid(360.0) - 180.0;
+        assert!(
+            difference.abs() < 0.1,
+            "{freq}Hz: {minimum}° vs {measured}°"
+        );
+    }
+}
+
+#[test]
+fn octave_bands() {
+    // Pink noise by the filter of Paul Kellet, settled before recording.
+    let mut generator = NoiseGenerator::new(1);
+    let mut state = [0.0f32; 7];
+    let noise: Vec<f32> = (0..2 * SAMPLE_RATE as usize)
+        .map(|_| {
+            let white = generator.next_sample();
+            state[0] = 0.99886 * state[0] + white * 0.0555179;
+            state[1] = 0.99332 * state[1] + white * 0.0750759;
+            state[2] = 0.96900 * state[2] + white * 0.153852;
+            state[3] = 0.86650 * state[3] + white * 0.3104856;
+            state[4] = 0.55000 * state[4] + white * 0.5329522;
+            state[5] = -0.7616 * state[5] - white * 0.0168980;
+            let pink = state.iter().sum::<f32>() + white * 0.5362;
+            state[6] = white * 0.115926;
+            pink
+        })
+        .skip(SAMPLE_RATE as usize)
+        .collect();
+
+    let response = FftAnalyzer::from_impulse_response(&noise, SAMPLE_RATE)
+        .unwrap()
+        .response();
+    let bands = response.octave_bands();
+    assert_eq!(bands.len(), 10);
+    assert_eq!(bands[0].0, 31.5);
+    assert_eq!(bands[9].0, 16000.0);
+
+    let mean = bands.iter().map(|(_, level)| level).sum::<f32>() / bands.len() as f32;
+    // The lowest bands contain few bins, so their level varies more.
+    for (freq, level) in bands {
+        assert!(
+            (level - mean).abs() < 2.0,
+            "{freq}Hz: {level}dB vs {mean}dB"
+        );
+    }
+}
+
+#[test]
+fn summary() {
+    let response = measure(FilterParams::Lowpass {
+        freq: 1000.0,
+        q: 0.707,
+    });
+    let summary = response.to_string();
+    assert!(summary.starts_with("DC gain: "), "{summary}");
+
+    let cutoff: f32 = summary
+        .split("cutoff: ")
+        .nth(1)
+        .and_then(|s| s.split("Hz").next())
+        .and_then(|s| s.parse().ok())
+        .unwrap();
+    assert!((cutoff - 1000.0).abs() <= 2.0, "{summary}");
+    assert!(
+        summary.contains(&format!("-3dB points: [{cutoff}Hz]")),
+        "{summary}"
+    );
+
+    let response = measure(FilterParams::Bandpass {
+        freq: 1000.0,
+        q: 2.0,
+    });
+    assert_eq!(response.cutoff_points().len(), 2);
+}
+
+#[test]
+fn cascade() {
+    let first = FilterParams::Lowpass {
+        freq: 1000.0,
+        q: 0.707,
+    };
+    let second = FilterParams::Lowpass {
+        freq: 4000.0,
+        q: 2.0,
+    };
+    let combined = measure(first.clone()).cascade(&measure(second.clone()));
+
+    let mut filters = [
+        BiquadFilter2::new(SAMPLE_RATE),
+        BiquadFilter2::new(SAMPLE_RATE),
+    ];
+    filters[0].set_params(first.clone());
+    filters[1].set_params(second);
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    analyzer
+        .run(|_, out_samples| {
+            for filter in filters.iter_mut() {
+                filter.process_block(out_samples);
+            }
+        })
+        .unwrap();
+    let series = analyzer.response();
+
+    for (i, freq) in series.frequencies.iter().enumerate() {
+        if series.magnitude_db[i] < -60.0 {
+            continue;
+        }
+        let magnitude = combined.magnitude_db[i] - series.magnitude_db[i];
+        let phase = (combined.phase_deg[i] - series.phase_deg[i] + 180.0).rem_euclid(360.0) - 180.0;
+        assert!(magnitude.abs() < 0.01, "{freq}Hz: {magnitude}dB");
+        assert!(phase.abs() < 0.1, "{freq}Hz: {phase}°");
+    }
+
+    let response = measure(first);
+    let doubled = response.parallel(&response);
+    assert!((doubled.dc_gain - response.dc_gain - 6.02).abs() < 0.01);
+    assert_eq!(doubled.cutoff, response.cutoff);
+}
+
+#[test]
+fn invert() {
+    let response = measure(FilterParams::Peak {
+        freq: 1000.0,
+        q: 0.7,
+        gain: 12.0,
+    });
+    let flat = response.cascade(&response.invert());
+    for (freq, (magnitude, phase)) in flat
+        .frequencies
+        .iter()
+        .zip(flat.magnitude_db.iter().zip(flat.phase_deg.iter()))
+    {
+        assert!(magnitude.abs() < 0.01, "{freq}Hz: {magnitude}dB");
+        assert!(phase.abs() < 0.1, "{freq}Hz: {phase}°");
+    }
+
+    let notch = measure(FilterParams::Notch {
+        freq: 1000.0,
+        q: 0.7,
+    });
+    let inverse = notch.invert();
+    assert!(inverse.peak_gain <= 20.0);
+    assert!((inverse.magnitude_db[notch.bin_index(1000.0)] - 20.0).abs() < 1e-4);
+
+    let corrected = notch.cascade(&inverse);
+    for (freq, magnitude) in corrected
+        .frequencies
+        .iter()
+        .zip(corrected.magnitude_db.iter())
+    {
+        if notch.magnitude_db[notch.bin_index(*freq)] > -20.0 {
+            assert!(magnitude.abs() < 0.01, "{freq}Hz: {magnitude}dB");
+        }
+    }
+}
+
+#[test]
+fn resample_to_grid() {
+    let response = measure(FilterParams::Lowpass {
+        freq: 1000.0,
+        q: 0.707,
+    });
+    let grid = response.resample_to_grid(&[1000.0, 1000.5, 30000.0]);
+    let bin = response.bin_index(1000.0);
+
+    assert_eq!(
+        grid[0],
+        (response.magnitude_db[bin], response.phase_deg[bin])
+    );
+    let midpoint = (response.magnitude_db[bin] + response.magnitude_db[bin + 1]) / 2.0;
+    assert!((grid[1].0 - midpoint).abs() < 1e-4);
+    assert_eq!(grid[2].0, *response.magnitude_db.last().unwrap());
+}
+
+#[test]
+fn rolloff_slope() {
+    let response = measure(FilterParams::Lowpass {
+        freq: 200.0,
+        q: std::f32::consts::FRAC_1_SQRT_2,
+    });
+
+    let slope = response.rolloff_slope(1000.0, 4000.0);
+    assert!((slope + 12.0).abs() < 0.5, "{slope}dB/oct");
+    assert_eq!(response.rolloff_slope(4000.0, 1000.0), slope);
+    assert!(response.rolloff_slope(20.0, 50.0).abs() < 0.1);
+    assert!(response.rolloff_slope(1000.0, 1000.5).is_nan());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn json() {
+    let filename = "out/response/peak_1k.json";
+    let response = measure(FilterParams::Peak {
+        freq: 1000.0,
+        q: 0.7,
+        gain: 6.0,
+    });
+
+    response.save_json(filename).unwrap();
+    let loaded = FrequencyResponse::load_json(filename).unwrap();
+    assert_eq!(loaded, response);
+
+    let json = std::fs::read_to_string(filename).unwrap();
+    assert!(json.contains("\"sample_rate\": 48000.0"));
+    assert!(json.contains("\"frequencies\""));
+
+    std::fs::write("out/response/invalid.json", "{}").unwrap();
+    let error = FrequencyResponse::load_json("out/response/invalid.json").unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn check_mask() {
+    let response = measure(FilterParams::Lowpass {
+        freq: 1000.0,
+        q: std::f32::consts::FRAC_1_SQRT_2,
+    });
+
+    // ±1dB in the passband, below -40dB from one decade above the cutoff.
+    let mask = [
+        (20.0, -1.0, 1.0),
+        (300.0, -1.0, 1.0),
+        (1000.0, -4.0, 0.0),
+        (10000.0, f32::NEG_INFINITY, -40.0),
+        (20000.0, f32::NEG_INFINITY, -40.0),
+    ];
+    assert!(response.check_mask(&mask).is_empty());
+    assert!(response.check_mask(&[]).is_empty());
+
+    // The stopband of a lowpass at 2kHz is 12dB too high.
+    let violations = measure(FilterParams::Lowpass {
+        freq: 2000.0,
+        q: std::f32::consts::FRAC_1_SQRT_2,
+    })
+    .check_mask(&mask);
+    assert!(!violations.is_empty());
+    for violation in &violations {
+        assert!(violation.freq > 300.0, "{violation:?}");
+        assert!(violation.magnitude_db > violation.max_db, "{violation:?}");
+    }
+    assert!(violations.iter().any(|violation| violation.freq == 10000.0));
+}
+
+#[test]
+fn check_mask_from_dc() {
+    let response = measure(FilterParams::Lowpass {
+        freq: 1000.0,
+        q: std::f32::consts::FRAC_1_SQRT_2,
+    });
+
+    // The lowest magnitude falls linearly from -1dB at DC to -2dB at 4kHz,
+    // so the lowpass is about 1.75dB too low at 1kHz.
+    let mask = [(0.0, -1.0, 1.0), (4000.0, -2.0, 1.0)];
+    let violations = response.check_mask(&mask);
+    assert!(violations.iter().any(|violation| violation.freq == 1000.0));
+    for violation in &violations {
+        assert!(violation.magnitude_db < violation.min_db, "{violation:?}");
+        assert!((-2.0..=-1.0).contains(&violation.min_db), "{violation:?}");
+    }
+
+    let violation = violations
+        .iter()
+        .find(|violation| violation.freq == 1000.0)
+        .unwrap();
+    assert!((violation.min_db + 1.25).abs() < 1e-3, "{violation:?}");
+}
+
+#[test]
+fn auto_eq_dip() {
+    let response = measure(FilterParams::Peak {
+        freq: 2000.0,
+        q: 2.0,
+        gain: -6.0,
+    });
+    let target: Vec<(f32, f32)> = (0..=60)
+        .map(|i| (20.0 * 1000.0f32.powf(i as f32 / 60.0), 0.0))
+        .collect();
+
+    let bands = auto_eq(&response, &target, 3);
+    assert!(!bands.is_empty() && bands.len() <= 3);
+    match bands[0] {
+        FilterParams::Peak { freq, gain, .. } => {
+            assert!((freq / 2000.0 - 1.0).abs() < 0.1, "{freq}");
+            assert!(gain > 4.0 && gain < 8.0, "{gain}");
+        }
+        _ => panic!("expected a peak filter"),
+    }
+}
+
+/// Returns the measured response of a filter.
+fn measure(params: FilterParams) -> FrequencyResponse {
+    let mut filter = BiquadFilter2::new(SAMPLE_RATE);
+    filter.set_params(params);
+
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    analyzer
+        .run(|_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+
+    analyzer.response()
+}
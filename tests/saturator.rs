@@ -0,0 +1,63 @@
+//! Saturation with selectable transfer curves.
+
+/// Transfer curve of the saturator.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum SaturatorCurve {
+    /// Hyperbolic tangent, `y = tanh(x)`.
+    #[default]
+    Tanh,
+
+    /// Hard clipping at ±1.0.
+    HardClip,
+
+    /// Cubic soft clipping, `y = x - x^3 / 3` for `|x| <= 1`, constant above.
+    Cubic,
+}
+
+/// Memoryless saturator.
+#[derive(Debug, Clone)]
+pub struct Saturator {
+    /// Transfer curve.
+    curve: SaturatorCurve,
+
+    /// Linear gain applied before the curve.
+    drive: f32,
+}
+
+impl Saturator {
+    /// Returns a new instance.
+    pub fn new(curve: SaturatorCurve, drive: f32) -> Self {
+        Self { curve, drive }
+    }
+
+    /// Sets the transfer curve.
+    pub fn set_curve(&mut self, curve: SaturatorCurve) {
+        self.curve = curve;
+    }
+
+    /// Sets the linear gain applied before the curve.
+    pub fn set_drive(&mut self, drive: f32) {
+        self.drive = drive;
+    }
+
+    /// Processes a single sample.
+    pub fn process_sample(&self, sample: f32) -> f32 {
+        let x = sample * self.drive;
+
+        match self.curve {
+            SaturatorCurve::Tanh => x.tanh(),
+            SaturatorCurve::HardClip => x.clamp(-1.0, 1.0),
+            SaturatorCurve::Cubic => {
+                let x = x.clamp(-1.0, 1.0);
+                x - x * x * x / 3.0
+            }
+        }
+    }
+
+    /// Processes a block of samples in-place.
+    pub fn process_block(&self, samples: &mut [f32]) {
+        for sample in samples.iter_mut() {
+            *sample = self.process_sample(*sample);
+        }
+    }
+}
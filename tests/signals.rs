@@ -0,0 +1,475 @@
+//! Tests for the test signal generators.
+
+use dsp_analyze::*;
+
+/// Block size in samples.
+const BLOCK_SIZE: usize = 16;
+
+#[test]
+fn mls_autocorrelation() {
+    let sequence = mls(10);
+    let length = sequence.len();
+    assert_eq!(length, 1023);
+
+    for lag in 0..length {
+        let correlation: f32 = (0..length)
+            .map(|n| sequence[n] * sequence[(n + lag) % length])
+            .sum();
+        let expected = if lag == 0 { length as f32 } else { -1.0 };
+        assert_eq!(correlation, expected, "Lag {lag}");
+    }
+}
+
+#[test]
+fn mls_deconvolution() {
+    let sequence = mls(12);
+    let taps = [0.5, -0.25, 0.125];
+
+    // Run two periods through the FIR to reach steady state.
+    let excitation: Vec<f32> = sequence.iter().chain(sequence.iter()).copied().collect();
+    let response: Vec<f32> = (0..excitation.len())
+        .map(|n| {
+            taps.iter()
+                .enumerate()
+                .filter(|(k, _)| *k <= n)
+                .map(|(k, tap)| tap * excitation[n - k])
+                .sum()
+        })
+        .collect();
+
+    let impulse_response = mls_deconvolve(&response[sequence.len()..], &sequence);
+
+    for (index, value) in impulse_response.iter().enumerate() {
+        let expected = taps.get(index).copied().unwrap_or(0.0);
+        assert!((value - expected).abs() < 1e-3, "Index {index}");
+    }
+}
+
+#[test]
+fn mls_excitation() {
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        test_signal: TestSignal::Mls { order: 15 },
+        ..Default::default()
+    });
+    analyzer
+        .run(|_, out_samples| {
+            for sample in out_samples.iter_mut() {
+                *sample *= 0.5;
+            }
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude("MLS excitation", "out/signals/mls_mag.svg")
+        .unwrap();
+
+    for magnitude in analyzer.spectrum_magnitude.iter() {
+        assert!((magnitude + 6.02).abs() < 0.01);
+    }
+}
+
+#[test]
+fn sweep_progress() {
+    let mut generator = SweepGenerator::new(48000.0);
+    generator.set_range(100.0, 1000.0);
+    generator.set_time(0.1);
+    generator.start();
+    assert_eq!(generator.current_frequency(), 100.0);
+    assert_eq!(generator.progress(), 0.0);
+
+    let mut buffer = [0.0; BLOCK_SIZE];
+    let mut last_progress = 0.0;
+    while generator.process(&mut buffer).is_ok() {
+        assert!(generator.progress() >= last_progress);
+        last_progress = generator.progress();
+    }
+
+    assert_eq!(generator.progress(), 1.0);
+    assert!(generator.current_frequency() > 1000.0);
+}
+
+#[test]
+fn sweep_generator() {
+    let mut generator = SweepGenerator::new(48000.0);
+    let mut buffer = [0.0; BLOCK_SIZE];
+    let result = generator.process(&mut buffer);
+    assert!(matches!(result, Err(SweepError::NotStarted)));
+    assert_eq!(result.unwrap_err().to_string(), "Sweep was not started");
+
+    generator.set_range(100.0, 1000.0);
+    generator.set_time(0.1);
+    generator.start();
+
+    let mut samples = Vec::new();
+    while let Ok(count) = generator.process(&mut buffer) {
+        samples.extend_from_slice(&buffer[..count]);
+    }
+
+    assert!((samples.len() as f32 - 4800.0).abs() <= 1.0);
+    assert!(samples.iter().all(|sample| sample.abs() <= 1.0));
+
+    generator.set_end_taper(0.005);
+    generator.start();
+    let mut tapered = Vec::new();
+    while let Ok(count) = generator.process(&mut buffer) {
+        tapered.extend_from_slice(&buffer[..count]);
+    }
+    assert_eq!(tapered.len(), samples.len());
+    assert!(tapered.last().unwrap().abs() < 1e-3);
+}
+
+#[test]
+fn sweep_block_size() {
+    let generate = |block_size| {
+        let mut generator = SweepGenerator::new(48000.0);
+        generator.set_range(100.0, 1000.0);
+        generator.set_time(0.1);
+        generator.start();
+
+        let mut buffer = vec![0.0; block_size];
+        let mut samples = Vec::new();
+        let error = loop {
+            match generator.process(&mut buffer) {
+                Ok(count) => samples.extend_from_slice(&buffer[..count]),
+                Err(error) => break error,
+            }
+        };
+        assert!(matches!(error, SweepError::MaxFreqReached), "{error:?}");
+
+        samples
+    };
+
+    let reference = generate(1);
+    for block_size in [16, 1000] {
+        assert_eq!(generate(block_size), reference, "Block size {block_size}");
+    }
+}
+
+#[test]
+fn sweep_settings() {
+    let mut generator = SweepGenerator::new(48000.0);
+    assert_eq!(generator.range(), (20.0, 20000.0));
+    assert_eq!(generator.time(), 1.0);
+    assert_eq!(generator.gain(), 1.0);
+
+    generator.set_range(100.0, 1000.0);
+    generator.set_time(0.1);
+    generator.set_gain(0.5);
+    assert_eq!(generator.range(), (100.0, 1000.0));
+    assert_eq!(generator.time(), 0.1);
+    assert_eq!(generator.gain(), 0.5);
+}
+
+#[test]
+fn sweep_direction() {
+    let generate = |direction| {
+        let mut generator = SweepGenerator::new(48000.0);
+        generator.set_range(100.0, 1000.0);
+        generator.set_time(0.1);
+        generator.set_direction(direction);
+        generator.start();
+
+        let first_freq = generator.current_frequency();
+        let mut buffer = [0.0; BLOCK_SIZE];
+        let mut samples = Vec::new();
+        while let Ok(count) = generator.process(&mut buffer) {
+            samples.extend_from_slice(&buffer[..count]);
+        }
+        assert_eq!(generator.progress(), 1.0);
+
+        (first_freq, generator.current_frequency(), samples)
+    };
+
+    let (up_first, _, up) = generate(SweepDirection::Up);
+    let (down_first, down_last, down) = generate(SweepDirection::Down);
+    assert_eq!(up_first, 100.0);
+    assert_eq!(down_first, 1000.0);
+    assert!(down_last < 100.0);
+    assert!((down.len() as f32 - up.len() as f32).abs() <= 1.0);
+
+    // The second sample advances the phase by the start frequency.
+    let freq = down[1].asin() * 48000.0 / std::f32::consts::TAU;
+    assert!((freq - 1000.0).abs() < 1.0, "{freq}Hz");
+}
+
+#[test]
+fn sweep_hook() {
+    let mut generator = SweepGenerator::new(48000.0);
+    generator.set_range(100.0, 1000.0);
+    generator.set_time(0.1);
+    generator.start();
+
+    let mut buffer = [0.0; BLOCK_SIZE];
+    let mut freqs = Vec::new();
+    while generator
+        .process_with(&mut buffer, |freq| freqs.push(freq))
+        .is_ok()
+    {}
+
+    assert!(freqs.len() >= 4800 / BLOCK_SIZE);
+    assert_eq!(freqs[0], 100.0);
+    assert!(freqs.windows(2).all(|pair| pair[1] > pair[0]));
+    assert!(freqs.iter().all(|freq| *freq <= 1000.0));
+}
+
+#[test]
+fn resample_sine() {
+    let sine = |rate: f32, length: usize| -> Vec<f32> {
+        (0..length)
+            .map(|i| (std::f32::consts::TAU * 1000.0 * i as f32 / rate).sin())
+            .collect()
+    };
+
+    let resampled = resample(&sine(48000.0, 4800), 48000.0, 44100.0);
+    assert_eq!(resampled.len(), 4410);
+
+    // Compare away from the edges, where the kernel runs past the signal.
+    let expected = sine(44100.0, 4410);
+    for (i, (sample, expected)) in resampled
+        .iter()
+        .zip(expected)
+        .enumerate()
+        .take(4300)
+        .skip(100)
+    {
+        assert!(
+            (sample - expected).abs() < 1e-3,
+            "{i}: {sample} vs {expected}"
+        );
+    }
+}
+
+#[test]
+fn noise_seed() {
+    let generate = |seed| {
+        let mut generator = NoiseGenerator::new(seed);
+        let mut buffer = vec![0.0; 4096];
+        generator.process(&mut buffer);
+        buffer
+    };
+
+    let samples = generate(42);
+    assert_eq!(samples, generate(42));
+    assert_ne!(samples, generate(43));
+    assert!(samples.iter().all(|sample| sample.abs() <= 1.0));
+
+    let mut generator = NoiseGenerator::new(43);
+    generator.set_seed(42);
+    assert_eq!(generator.next_sample(), samples[0]);
+
+    let run = |noise_seed| {
+        let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+            block_size: BLOCK_SIZE,
+            excitation_noise_db: Some(-20.0),
+            noise_seed,
+            ..Default::default()
+        });
+        analyzer.run(|_, _| {}).unwrap();
+        analyzer.in_samples
+    };
+    assert_eq!(run(7), run(7));
+    assert_ne!(run(7), run(8));
+}
+
+#[test]
+fn log_sweep() {
+    let mut generator = SweepGenerator::new(48000.0);
+    generator.set_range(100.0, 1000.0);
+    generator.set_time(0.1);
+    generator.set_mode(SweepMode::Logarithmic);
+    generator.start();
+
+    let mut buffer = [0.0; BLOCK_SIZE];
+    let mut count = 0;
+    let mut half_time_freq = 0.0;
+    while let Ok(written) = generator.process(&mut buffer) {
+        count += written;
+        if count == 2400 {
+            half_time_freq = generator.current_frequency();
+        }
+    }
+    assert!((count as f32 - 4800.0).abs() <= 1.0);
+    assert_eq!(generator.progress(), 1.0);
+
+    // Geometric mean of the range at half of the sweep time.
+    assert!((half_time_freq - 316.2).abs() < 1.0, "{half_time_freq}Hz");
+}
+
+#[test]
+fn log_sweep_snr() {
+    // Mean deviation in dB from the unity gain between 20Hz and 100Hz
+    // for a processor adding noise at -40dB.
+    let deviation = |test_signal| {
+        let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+            block_size: BLOCK_SIZE,
+            test_signal,
+            ..Default::default()
+        });
+        let mut noise = NoiseGenerator::new(1);
+        noise.set_gain(0.01);
+        analyzer
+            .run(|_, out_samples| {
+                for sample in out_samples.iter_mut() {
+                    *sample += noise.next_sample();
+                }
+            })
+            .unwrap();
+
+        let bins = &analyzer.spectrum_magnitude[20..100];
+        bins.iter().map(|magnitude| magnitude.abs()).sum::<f32>() / bins.len() as f32
+    };
+
+    let linear = deviation(TestSignal::Sweep);
+    let logarithmic = deviation(TestSignal::LogSweep);
+    assert!(logarithmic < linear / 4.0, "{logarithmic}dB vs {linear}dB");
+}
+
+#[test]
+fn sweep_end_taper() {
+    // Peak-to-peak ripple in dB of the input spectrum below the end of the
+    // sweep at 20kHz.
+    let ripple = |sweep_end_taper| {
+        let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+            block_size: BLOCK_SIZE,
+            test_signal: TestSignal::Sweep,
+            sweep_end_taper,
+            ..Default::default()
+        });
+        analyzer.run(|_, _| {}).unwrap();
+
+        let resolution = analyzer.frequency_resolution();
+        let band: Vec<f32> = analyzer.input_spectrum()
+            [(15000.0 / resolution) as usize..(19500.0 / resolution) as usize]
+            .iter()
+            .map(|v| 20.0 * v.log10())
+            .collect();
+        let max = band.iter().fold(f32::MIN, |max, v| max.max(*v));
+        let min = band.iter().fold(f32::MAX, |min, v| min.min(*v));
+        max - min
+    };
+
+    let abrupt = ripple(0.0);
+    let tapered = ripple(0.005);
+    assert!(tapered < abrupt / 10.0, "{abrupt}dB vs {tapered}dB");
+}
+
+#[test]
+fn decimator_response() {
+    let mut decimator = Decimator::new(2);
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    analyzer
+        .run(|_, out_samples| decimator.filter_block(out_samples))
+        .unwrap();
+    analyzer
+        .plot_magnitude("Decimator 2x lowpass", "out/signals/decimator_mag.svg")
+        .unwrap();
+
+    // Flat up to 80% of the new Nyquist frequency, attenuated above it.
+    let response = analyzer.response();
+    for (freq, magnitude) in response.frequencies.iter().zip(&response.magnitude_db) {
+        if *freq <= 9600.0 {
+            assert!(magnitude.abs() < 0.01, "{freq}Hz: {magnitude}dB");
+        } else if *freq >= 12000.0 {
+            assert!(*magnitude < -70.0, "{freq}Hz: {magnitude}dB");
+        }
+    }
+}
+
+#[test]
+fn decimator_process() {
+    let sine = |freq: f32, length: usize| -> Vec<f32> {
+        (0..length)
+            .map(|i| (std::f32::consts::TAU * freq * i as f32 / 48000.0).sin())
+            .collect()
+    };
+
+    // Level in dB of the output once the filter has settled, fed in blocks of odd length.
+    let level = |freq| {
+        let mut decimator = Decimator::new(3);
+        let input = sine(freq, 9600);
+        let mut output = vec![0.0; 3200];
+        let mut count = 0;
+        for block in input.chunks(7) {
+            count += decimator.process(block, &mut output[count..]);
+        }
+        assert_eq!(count, 3200);
+        let skip = 2 * decimator.latency() / decimator.factor() + 1;
+        let settled = &output[skip..];
+        let rms = (settled.iter().map(|sample| sample * sample).sum::<f32>()
+            / settled.len() as f32)
+            .sqrt();
+        20.0 * (rms * std::f32::consts::SQRT_2).log10()
+    };
+
+    let passband = level(1000.0);
+    assert!(passband.abs() < 0.01, "{passband}dB");
+    // Aliases to 3kHz at 16kHz without the lowpass.
+    let stopband = level(13000.0);
+    assert!(stopband < -70.0, "{stopband}dB");
+}
+
+#[test]
+fn interpolator_round_trip() {
+    let mut interpolator = Interpolator::new(4);
+    let mut decimator = Decimator::new(4);
+    let mut upsampled = vec![0.0; BLOCK_SIZE * 4];
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    analyzer
+        .run(|in_samples, out_samples| {
+            interpolator.process(in_samples, &mut upsampled);
+            assert_eq!(decimator.process(&upsampled, out_samples), BLOCK_SIZE);
+        })
+        .unwrap();
+    analyzer
+        .plot_magnitude(
+            "Interpolator and decimator 4x",
+            "out/signals/interpolator_mag.svg",
+        )
+        .unwrap();
+
+    // The latency is a whole number of samples at the original rate.
+    assert_eq!(
+        (interpolator.latency() + decimator.latency()) % interpolator.factor(),
+        0
+    );
+    let response = analyzer.response();
+    for (freq, magnitude) in response.frequencies.iter().zip(&response.magnitude_db) {
+        if *freq <= 19200.0 {
+            assert!(magnitude.abs() < 0.02, "{freq}Hz: {magnitude}dB");
+        }
+    }
+}
+
+#[test]
+fn impulse_generator() {
+    let mut generator = ImpulseGenerator::new(100);
+    generator.set_amplitude(0.5);
+    assert_eq!((generator.position(), generator.amplitude()), (100, 0.5));
+
+    // Generated in blocks not aligned to the position.
+    let mut samples = vec![0.0; 4096];
+    for block in samples.chunks_mut(BLOCK_SIZE + 1) {
+        generator.process(block);
+    }
+    let nonzero: Vec<_> = samples
+        .iter()
+        .enumerate()
+        .filter(|(_, sample)| **sample != 0.0)
+        .collect();
+    assert_eq!(nonzero, [(100, &0.5)]);
+    assert_eq!(generator.generate(4096), samples);
+
+    // The impulse is only emitted again after a reset.
+    assert_eq!(generator.next_sample(), 0.0);
+    generator.set_position(0);
+    assert_eq!(generator.next_sample(), 0.0);
+    generator.reset();
+    assert_eq!(generator.next_sample(), 0.5);
+    assert_eq!(generator.next_sample(), 0.0);
+}
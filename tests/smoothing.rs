@@ -0,0 +1,80 @@
+//! Tests for fractional-octave smoothing.
+
+use dsp_analyze::smoothing::{smooth, smooth_phase, smooth_with, SmoothingKind};
+
+#[test]
+fn smooth_flat() {
+    let values = vec![-6.0; 24001];
+    let smoothed = smooth(&values, 1.0, 3.0);
+
+    assert_eq!(smoothed, values);
+}
+
+#[test]
+fn smooth_ripple() {
+    let values: Vec<f32> = (0..24001)
+        .map(|i| if i % 2 == 0 { 1.0 } else { -1.0 })
+        .collect();
+    let smoothed = smooth(&values, 1.0, 3.0);
+
+    // Windows above a few hundred bins contain nearly equal counts of both values.
+    for value in &smoothed[1000..] {
+        assert!(value.abs() < 0.01);
+    }
+}
+
+#[test]
+fn smooth_gaussian() {
+    let flat = vec![-6.0; 4801];
+    for value in smooth_with(&flat, 1.0, 3.0, SmoothingKind::Gaussian) {
+        assert!((value + 6.0).abs() < 1e-4);
+    }
+    assert_eq!(
+        smooth_with(&flat, 1.0, 3.0, SmoothingKind::Rectangular),
+        smooth(&flat, 1.0, 3.0)
+    );
+
+    // Sum of the squared differences of neighboring bins.
+    let roughness = |values: &[f32]| -> f32 {
+        values[500..]
+            .windows(2)
+            .map(|pair| (pair[1] - pair[0]).powi(2))
+            .sum()
+    };
+    let values: Vec<f32> = (0..4801).map(|i| (i as f32 * 0.37).sin()).collect();
+    let rectangular = roughness(&smooth_with(&values, 1.0, 3.0, SmoothingKind::Rectangular));
+    let gaussian = roughness(&smooth_with(&values, 1.0, 3.0, SmoothingKind::Gaussian));
+    assert!(gaussian < rectangular / 10.0, "{gaussian} vs {rectangular}");
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn smooth_parallel_identical() {
+    let values: Vec<f32> = (0..100_000).map(|i| (i as f32 * 0.37).sin()).collect();
+
+    let run = |threads| {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap()
+            .install(|| smooth(&values, 0.5, 6.0))
+    };
+    assert_eq!(run(4), run(1));
+}
+
+#[test]
+fn smooth_phase_wrap() {
+    // Phase alternating on both sides of the ±180° boundary.
+    let values: Vec<f32> = (0..24001)
+        .map(|i| if i % 2 == 0 { 178.0 } else { -178.0 })
+        .collect();
+
+    // A naive average is close to 0° instead.
+    let naive = smooth(&values, 1.0, 3.0);
+    assert!(naive[10000].abs() < 1.0);
+
+    let smoothed = smooth_phase(&values, 1.0, 3.0);
+    for value in &smoothed[1000..] {
+        assert!(value.abs() > 179.0, "{value}°");
+    }
+}
@@ -0,0 +1,74 @@
+//! Tests for the streaming analyzer.
+
+pub mod biquad;
+
+use biquad::*;
+use dsp_analyze::*;
+
+/// Sample rate in Hz.
+const SAMPLE_RATE: f32 = 48000.0;
+
+/// Block size in samples.
+const BLOCK_SIZE: usize = 64;
+
+#[test]
+fn streaming_converges() {
+    let params = FilterParams::Peak {
+        freq: 1000.0,
+        q: 0.7,
+        gain: 6.0,
+    };
+
+    // A periodic MLS makes the rolling window match a circular analysis.
+    let sequence = mls(12);
+    let mut streaming = StreamingAnalyzer::new(SAMPLE_RATE, sequence.len());
+    streaming.update_interval = Some(sequence.len());
+
+    let mut filter = BiquadFilter2::new(SAMPLE_RATE);
+    filter.set_params(params.clone());
+    let input: Vec<f32> = sequence
+        .iter()
+        .copied()
+        .cycle()
+        .take(sequence.len() * 4)
+        .collect();
+    for (index, in_block) in input.chunks(BLOCK_SIZE).enumerate() {
+        let mut out_block = in_block.to_vec();
+        filter.process_block(&mut out_block);
+        streaming.push(in_block, &out_block);
+        if (index + 1) * BLOCK_SIZE < sequence.len() {
+            assert!(streaming.response().is_none());
+        }
+    }
+    let response = streaming.response().unwrap();
+
+    let mut impulse = vec![0.0; sequence.len()];
+    impulse[0] = 1.0;
+    let mut filter = BiquadFilter2::new(SAMPLE_RATE);
+    filter.set_params(params);
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        allow_short_blocks: true,
+        ..Default::default()
+    });
+    analyzer
+        .run_with_signal(impulse, |_, out_samples| {
+            filter.process_block(out_samples);
+        })
+        .unwrap();
+
+    assert_eq!(
+        response.magnitude_db.len(),
+        analyzer.spectrum_magnitude.len()
+    );
+    for (streaming, offline) in response
+        .magnitude_db
+        .iter()
+        .zip(analyzer.spectrum_magnitude.iter())
+    {
+        assert!(
+            (streaming - offline).abs() < 0.01,
+            "{streaming}dB vs {offline}dB"
+        );
+    }
+}
@@ -0,0 +1,130 @@
+//! Tests for WAV file input and output.
+
+use dsp_analyze::*;
+
+/// Block size in samples.
+const BLOCK_SIZE: usize = 16;
+
+#[test]
+fn save_measurement() {
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        test_signal: TestSignal::Sweep,
+        ..Default::default()
+    });
+    analyzer
+        .run(|_, out_samples| {
+            for sample in out_samples.iter_mut() {
+                *sample *= 0.5;
+            }
+        })
+        .unwrap();
+    analyzer.save_measurement("out/wav/measurement.wav");
+
+    let mut file = std::fs::File::open("out/wav/measurement.wav").unwrap();
+    let (header, data) = wav::read(&mut file).unwrap();
+    assert_eq!(header.channel_count, 2);
+    assert_eq!(header.sampling_rate, 48000);
+
+    let data = data.as_thirty_two_float().unwrap();
+    assert_eq!(data.len(), analyzer.in_samples.len() * 2);
+    for (frame, (input, output)) in data
+        .chunks(2)
+        .zip(analyzer.in_samples.iter().zip(analyzer.out_samples.iter()))
+    {
+        assert_eq!(frame[0], *input);
+        assert_eq!(frame[1], *output);
+    }
+}
+
+#[test]
+fn save_output_mono() {
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        ..Default::default()
+    });
+    analyzer.run(|_, _| {}).unwrap();
+    analyzer.save_output("out/wav/output.wav");
+
+    let mut file = std::fs::File::open("out/wav/output.wav").unwrap();
+    let (header, data) = wav::read(&mut file).unwrap();
+    assert_eq!(header.channel_count, 1);
+
+    let data = data.as_thirty_two_float().unwrap();
+    assert_eq!(data.len(), analyzer.out_samples.len());
+    assert_eq!(data, analyzer.out_samples.as_slice());
+}
+
+#[test]
+fn save_output_with_gain() {
+    let mut analyzer = FftAnalyzer::new(FftAnalyzerConfig {
+        block_size: BLOCK_SIZE,
+        test_signal: TestSignal::Sweep,
+        ..Default::default()
+    });
+    analyzer
+        .run(|_, out_samples| {
+            for sample in out_samples.iter_mut() {
+                *sample *= 0.01;
+            }
+        })
+        .unwrap();
+    let magnitude = analyzer.magnitude();
+    analyzer.save_output_with_gain("out/wav/output_gain.wav", 20.0);
+
+    let (_, data) = wav_reader::read_channel("out/wav/output_gain.wav", 0).unwrap();
+    let peak = |samples: &[f32]| samples.iter().fold(0.0f32, |peak, v| peak.max(v.abs()));
+    assert!((peak(&data) / peak(&analyzer.out_samples) - 10.0).abs() < 1e-5);
+    for (sample, output) in data.iter().zip(analyzer.out_samples.iter()) {
+        assert!((sample - output * 10.0).abs() < 1e-6);
+    }
+
+    // The analysis is not affected.
+    assert!(analyzer.output_peak() < 0.011);
+    assert_eq!(analyzer.magnitude(), magnitude);
+}
+
+#[test]
+fn read_channels() {
+    let filename = "out/wav/read_channels.wav";
+    let reference: Vec<f32> = (0..480).map(|i| (i as f32 / 48.0).sin()).collect();
+    let measurement: Vec<f32> = reference.iter().map(|sample| sample * 0.5).collect();
+    wav_writer::write_channels(filename, 44100, &[&reference, &measurement]).unwrap();
+
+    let (sample_rate, channels) = wav_reader::read_all_channels(filename).unwrap();
+    assert_eq!(sample_rate, 44100);
+    assert_eq!(channels, vec![reference.clone(), measurement.clone()]);
+
+    let (sample_rate, channel) = wav_reader::read_channel(filename, 1).unwrap();
+    assert_eq!(sample_rate, 44100);
+    assert_eq!(channel, measurement);
+
+    let error = wav_reader::read_channel(filename, 2).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(wav_reader::read_channel("out/wav/missing.wav", 0).is_err());
+}
+
+#[test]
+fn write_invalid_channels() {
+    let filename = "out/wav/invalid_channels.wav";
+    let _ = std::fs::remove_file(filename);
+
+    let error = wav_writer::write_channels(filename, 48000, &[]).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+
+    let error = wav_writer::write_channels(filename, 48000, &[&[0.0; 4], &[0.0; 3]]).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(std::fs::metadata(filename).is_err());
+}
+
+#[test]
+fn read_integer_format() {
+    let filename = "out/wav/read_integer_format.wav";
+    let mut file = std::fs::File::create(filename).unwrap();
+    let header = wav::Header::new(wav::WAV_FORMAT_PCM, 2, 48000, 16);
+    let data = wav::BitDepth::Sixteen(vec![16384, -16384, -32768, 0]);
+    wav::write(header, &data, &mut file).unwrap();
+
+    let (_, channels) = wav_reader::read_all_channels(filename).unwrap();
+    assert_eq!(channels, vec![vec![0.5, -1.0], vec![-0.5, 0.0]]);
+}